@@ -1,36 +1,77 @@
-use bevy::prelude::*;
+use bevy::{prelude::*, utils::HashMap};
 use bevy_kira_audio::prelude::*;
 
 use crate::{
-    common::{app_state::AppState, events::PelletEaten},
-    ghosts::GhostMode,
+    common::{
+        app_state::{AppState, DeadState},
+        events::{CollisionPauseTimer, GhostModeChanged, LevelComplete},
+    },
+    ghosts::{Ghost, GhostMode},
+    pellets::PelletsEaten,
 };
 
+/// Length of the level complete jingle. The ghosts stay on screen and the maze
+/// only starts flashing once it's over.
+pub const LEVEL_COMPLETE_JINGLE_SECS: f32 = 3.0;
+
 #[derive(Resource, Default)]
-struct BackgroundSounds {
+pub struct BackgroundSounds {
     sirens: [Handle<AudioSource>; 5],
     ghost_going_home: Handle<AudioSource>,
     ghost_frite: Handle<AudioSource>,
+    level_complete: Handle<AudioSource>,
     currently_playing: Option<Handle<AudioSource>>,
     playing_instance: Option<Handle<AudioInstance>>,
+    ducked: bool,
 }
 
-#[derive(Resource)]
-struct PelletEatenCounter(usize);
+/// The mode of every ghost that changed mode since they were spawned, kept up
+/// to date from `GhostModeChanged`. Ghosts always spawn in a mode that plays
+/// the siren.
+#[derive(Resource, Default)]
+struct GhostModes(HashMap<Ghost, GhostMode>);
+
+impl GhostModes {
+    /// The mode that picks the background sound: any eaten ghost going home
+    /// wins over any frightened one, which wins over the siren.
+    fn loudest(&self) -> GhostMode {
+        self.0.values().fold(GhostMode::Scatter, |acc, mode| {
+            if let GhostMode::Dead = acc {
+                return acc;
+            }
+
+            match mode {
+                GhostMode::Dead | GhostMode::DeadEnterHome => return GhostMode::Dead,
+                GhostMode::Frightened | GhostMode::DeadPause => return GhostMode::Frightened,
+                _ => (),
+            }
+
+            acc
+        })
+    }
+}
 
 pub struct BackgroundSoundPlugin;
 
 impl Plugin for BackgroundSoundPlugin {
     fn build(&self, app: &mut App) {
         app.add_systems(Startup, load_sounds);
-        app.add_systems(OnEnter(AppState::LevelStart), zero_pellet_eaten);
         app.add_systems(
             Update,
-            change_background_sound.run_if(in_state(AppState::MainGame)),
+            (
+                track_ghost_modes,
+                change_background_sound,
+                duck_while_ghost_eaten,
+                play_level_complete,
+            )
+                .chain()
+                .run_if(in_state(AppState::MainGame)),
         );
         app.add_systems(OnExit(AppState::MainGame), stop_sirens);
+        app.add_systems(OnEnter(AppState::LevelStart), forget_ghost_modes);
+        app.add_systems(OnEnter(DeadState::Restart), forget_ghost_modes);
         app.insert_resource(BackgroundSounds::default());
-        app.insert_resource(PelletEatenCounter(0));
+        app.insert_resource(GhostModes::default());
     }
 }
 
@@ -40,45 +81,52 @@ fn load_sounds(mut background_sounds: ResMut<BackgroundSounds>, asset_server: Re
     }
     background_sounds.ghost_going_home = asset_server.load("sounds/ghost_going_home.wav");
     background_sounds.ghost_frite = asset_server.load("sounds/ghosts_frite.wav");
+    background_sounds.level_complete = asset_server.load("sounds/level_complete.wav");
 }
 
-fn zero_pellet_eaten(mut pellet_eaten: ResMut<PelletEatenCounter>) {
-    pellet_eaten.0 = 0;
+fn forget_ghost_modes(mut ghost_modes: ResMut<GhostModes>) {
+    ghost_modes.0.clear();
 }
 
-fn change_background_sound(
-    mut background_sounds: ResMut<BackgroundSounds>,
-    audio: Res<Audio>,
-    mut audio_instances: ResMut<Assets<AudioInstance>>,
-    mut pellet_eaten: ResMut<PelletEatenCounter>,
-    mut pellet_eaten_events: EventReader<PelletEaten>,
-    ghost_mode_query: Query<&GhostMode>,
+fn track_ghost_modes(
+    mut ghost_modes: ResMut<GhostModes>,
+    mut mode_changed_events: EventReader<GhostModeChanged>,
 ) {
-    pellet_eaten.0 += pellet_eaten_events.read().count();
-    let siren = match pellet_eaten.0 {
+    for event in mode_changed_events.read() {
+        ghost_modes.0.insert(event.ghost, event.to);
+    }
+}
+
+/// Which of the sirens plays, faster the more pellets were eaten.
+pub fn siren(pellets_eaten: usize) -> usize {
+    match pellets_eaten {
         0..=114 => 0,
         115..=179 => 1,
         180..=209 => 2,
         210..=224 => 3,
         225.. => 4,
-        _ => unreachable!(),
-    };
+    }
+}
 
-    let ghosts_mode = ghost_mode_query
-        .iter()
-        .fold(GhostMode::Scatter, |acc, mode| {
-            if let GhostMode::Dead = acc {
-                return acc;
-            }
+#[cfg(test)]
+impl BackgroundSounds {
+    /// The index of the siren playing, `None` while another sound plays.
+    pub fn playing_siren(&self) -> Option<usize> {
+        let playing = self.currently_playing.as_ref()?;
+        self.sirens.iter().position(|siren| siren == playing)
+    }
+}
 
-            match mode {
-                GhostMode::Dead | GhostMode::DeadEnterHome => return GhostMode::Dead,
-                GhostMode::Frightened | GhostMode::DeadPause => return GhostMode::Frightened,
-                _ => (),
-            }
+fn change_background_sound(
+    mut background_sounds: ResMut<BackgroundSounds>,
+    audio: Res<Audio>,
+    mut audio_instances: ResMut<Assets<AudioInstance>>,
+    pellets_eaten: Res<PelletsEaten>,
+    ghost_modes: Res<GhostModes>,
+) {
+    let siren = siren(pellets_eaten.0);
 
-            acc
-        });
+    let ghosts_mode = ghost_modes.loudest();
 
     let background_sound_handle = if let GhostMode::Dead = ghosts_mode {
         background_sounds.ghost_going_home.clone()
@@ -106,19 +154,76 @@ fn change_background_sound(
 
         background_sounds.playing_instance = Some(handle);
         background_sounds.currently_playing = Some(background_sound_handle);
+        background_sounds.ducked = false;
+    }
+}
+
+/// Like the arcade, the background sound stops while the game pauses on eating
+/// a ghost, leaving only the eating sound, and picks up where it left off after.
+fn duck_while_ghost_eaten(
+    mut background_sounds: ResMut<BackgroundSounds>,
+    mut audio_instances: ResMut<Assets<AudioInstance>>,
+    pause_timer: Res<CollisionPauseTimer>,
+) {
+    let should_duck = !pause_timer.0.finished();
+    if should_duck == background_sounds.ducked {
+        return;
+    }
+
+    // A sound started this frame isn't an instance yet, so it's tried again on
+    // the next one.
+    let Some(handle) = background_sounds.playing_instance.clone() else {
+        background_sounds.ducked = should_duck;
+        return;
+    };
+    let Some(instance) = audio_instances.get_mut(handle) else {
+        return;
+    };
+
+    if should_duck {
+        instance.pause(AudioTween::default());
+    } else {
+        instance.resume(AudioTween::default());
+    }
+    background_sounds.ducked = should_duck;
+}
+
+/// Replaces the siren with the jingle. Runs after the siren is picked, so it
+/// isn't started again before the state changes.
+fn play_level_complete(
+    mut level_complete_events: EventReader<LevelComplete>,
+    mut background_sounds: ResMut<BackgroundSounds>,
+    mut audio_instances: ResMut<Assets<AudioInstance>>,
+    audio: Res<Audio>,
+) {
+    if level_complete_events.read().last().is_none() {
+        return;
     }
+
+    stop_playing(&mut background_sounds, &mut audio_instances);
+    audio.play(background_sounds.level_complete.clone());
 }
 
 fn stop_sirens(
     mut background_sounds: ResMut<BackgroundSounds>,
     mut audio_instances: ResMut<Assets<AudioInstance>>,
 ) {
-    if let Some(instance) =
-        audio_instances.get_mut(&background_sounds.playing_instance.clone().unwrap())
+    stop_playing(&mut background_sounds, &mut audio_instances);
+}
+
+fn stop_playing(
+    background_sounds: &mut BackgroundSounds,
+    audio_instances: &mut Assets<AudioInstance>,
+) {
+    if let Some(instance) = background_sounds
+        .playing_instance
+        .clone()
+        .and_then(|handle| audio_instances.get_mut(handle))
     {
         instance.stop(AudioTween::default());
     }
 
     background_sounds.playing_instance = None;
     background_sounds.currently_playing = None;
+    background_sounds.ducked = false;
 }
@@ -0,0 +1,233 @@
+use std::collections::{HashSet, VecDeque};
+
+use bevy::prelude::*;
+use strum::IntoEnumIterator;
+
+use crate::{
+    common::sets::GameLoop,
+    ghosts::{Ghost, GhostMode},
+    pellets::PelletType,
+    player::Player,
+    points::BonusSymbol,
+    replay::Recorder,
+    services::map::{Direction, Location, Map},
+};
+
+const BOT_ARG: &str = "--bot";
+
+/// Tiles around a ghost the built in bot keeps away from.
+const GHOST_AVOID_DISTANCE: i32 = 2;
+
+/// The game as seen by a bot at the end of a game loop tick, in plain values
+/// so it can be handed to code that knows nothing about the ECS. Locations are
+/// `(x, y)` in tiles, with `y` going up.
+#[derive(Clone, Debug)]
+pub struct GameSnapshot {
+    /// The game loop tick the snapshot was taken on, counted from the start of
+    /// the game.
+    pub tick: u64,
+    pub width: usize,
+    pub height: usize,
+    /// One entry per tile, row by row from `y = 0`, set where the player can't
+    /// go. The ghost house counts as a wall.
+    pub walls: Vec<bool>,
+    pub pellets: Vec<PelletSnapshot>,
+    pub bonus_symbol: Option<(f32, f32)>,
+    pub ghosts: Vec<GhostSnapshot>,
+    pub player: PlayerSnapshot,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct PelletSnapshot {
+    pub location: (f32, f32),
+    pub power: bool,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct GhostSnapshot {
+    pub ghost: Ghost,
+    pub location: (f32, f32),
+    pub mode: GhostMode,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct PlayerSnapshot {
+    pub location: (f32, f32),
+    pub direction: Direction,
+}
+
+impl GameSnapshot {
+    /// Whether the tile is a wall. Columns past the sides wrap around like the
+    /// tunnels do, and rows past the top and bottom are walls.
+    pub fn is_wall(&self, x: i32, y: i32) -> bool {
+        if y < 0 || y >= self.height as i32 {
+            return true;
+        }
+        let x = x.rem_euclid(self.width as i32);
+        self.walls[y as usize * self.width + x as usize]
+    }
+}
+
+/// Lets something other than the keyboard play: while this resource exists the
+/// player follows `input` instead of the keys, and `snapshot` is refreshed
+/// after every game loop tick. Insert it to take over the game, remove it to
+/// hand control back.
+#[derive(Resource, Default)]
+pub struct BotInterface {
+    snapshot: Option<GameSnapshot>,
+    input: Option<Direction>,
+}
+
+impl BotInterface {
+    /// The game as it was after the last tick, if a game is being played.
+    pub fn snapshot(&self) -> Option<&GameSnapshot> {
+        self.snapshot.as_ref()
+    }
+
+    /// The direction held from the next tick on, like a key kept pressed.
+    pub fn set_input(&mut self, direction: Option<Direction>) {
+        self.input = direction;
+    }
+
+    pub fn input(&self) -> Option<Direction> {
+        self.input
+    }
+}
+
+/// With `--bot`, a simple built in bot plays every game through the
+/// `BotInterface`, in a window or headless.
+pub struct BotPlugin;
+
+impl Plugin for BotPlugin {
+    fn build(&self, app: &mut App) {
+        if std::env::args().any(|arg| arg == BOT_ARG) {
+            app.insert_resource(BotInterface::default());
+            app.add_systems(
+                FixedUpdate,
+                drive_greedy_bot
+                    .before(GameLoop::Planning)
+                    .run_if(resource_exists::<BotInterface>()),
+            );
+        }
+
+        app.add_systems(
+            FixedUpdate,
+            collect_snapshot
+                .after(GameLoop::Collisions)
+                .run_if(resource_exists::<BotInterface>()),
+        );
+    }
+}
+
+fn collect_snapshot(
+    mut bot: ResMut<BotInterface>,
+    map: Res<Map>,
+    recorder: Res<Recorder>,
+    player: Query<(&Location, &Direction), With<Player>>,
+    ghosts: Query<(&Ghost, &Location, &GhostMode)>,
+    pellets: Query<(&Location, &PelletType)>,
+    bonus_symbols: Query<&Location, With<BonusSymbol>>,
+) {
+    let Ok((player_location, player_direction)) = player.get_single() else {
+        bot.snapshot = None;
+        return;
+    };
+
+    let walls = (0..map.height())
+        .flat_map(|y| (0..map.width()).map(move |x| (x, y)))
+        .map(|(x, y)| map.is_blocked(Location::new(x as f32, y as f32)))
+        .collect();
+
+    bot.snapshot = Some(GameSnapshot {
+        tick: recorder.tick(),
+        width: map.width(),
+        height: map.height(),
+        walls,
+        pellets: pellets
+            .iter()
+            .map(|(location, pellet_type)| PelletSnapshot {
+                location: (location.x(), location.y()),
+                power: matches!(pellet_type, PelletType::Power),
+            })
+            .collect(),
+        bonus_symbol: bonus_symbols
+            .iter()
+            .next()
+            .map(|location| (location.x(), location.y())),
+        ghosts: ghosts
+            .iter()
+            .map(|(ghost, location, mode)| GhostSnapshot {
+                ghost: *ghost,
+                location: (location.x(), location.y()),
+                mode: *mode,
+            })
+            .collect(),
+        player: PlayerSnapshot {
+            location: (player_location.x(), player_location.y()),
+            direction: *player_direction,
+        },
+    });
+}
+
+/// Plays with `greedy_direction` on every tick.
+pub fn drive_greedy_bot(mut bot: ResMut<BotInterface>) {
+    let direction = bot.snapshot().and_then(greedy_direction);
+    if direction.is_some() {
+        bot.set_input(direction);
+    }
+}
+
+/// Heads for the closest pellet or bonus symbol along the maze, going around
+/// the tiles near ghosts that can eat the player.
+fn greedy_direction(snapshot: &GameSnapshot) -> Option<Direction> {
+    let tile = |(x, y): (f32, f32)| (x.round() as i32, y.round() as i32);
+    let wrap = |(x, y): (i32, i32)| (x.rem_euclid(snapshot.width as i32), y);
+
+    let dangerous_ghosts = snapshot
+        .ghosts
+        .iter()
+        .filter(|ghost| matches!(ghost.mode, GhostMode::Chase | GhostMode::Scatter))
+        .map(|ghost| tile(ghost.location))
+        .collect::<Vec<_>>();
+    let is_dangerous = |(x, y): (i32, i32)| {
+        dangerous_ghosts.iter().any(|(ghost_x, ghost_y)| {
+            (x - ghost_x).abs() + (y - ghost_y).abs() <= GHOST_AVOID_DISTANCE
+        })
+    };
+
+    let targets = snapshot
+        .pellets
+        .iter()
+        .map(|pellet| tile(pellet.location))
+        .chain(snapshot.bonus_symbol.map(tile))
+        .collect::<HashSet<_>>();
+
+    // Keep going the same way when that's as good as turning.
+    let current = snapshot.player.direction;
+    let directions = std::iter::once(current)
+        .chain(Direction::iter().filter(|direction| *direction != current))
+        .collect::<Vec<_>>();
+
+    // Each tile reached remembers the direction of the first step towards it.
+    let start = wrap(tile(snapshot.player.location));
+    let mut visited = HashSet::from([start]);
+    let mut queue = VecDeque::from([(start, None)]);
+
+    while let Some((position, first_step)) = queue.pop_front() {
+        for direction in &directions {
+            let step = direction.get_vec().to_vec();
+            let next = wrap((position.0 + step.x as i32, position.1 + step.y as i32));
+            if snapshot.is_wall(next.0, next.1) || is_dangerous(next) || !visited.insert(next) {
+                continue;
+            }
+
+            let first_step = first_step.or(Some(*direction));
+            if targets.contains(&next) {
+                return first_step;
+            }
+            queue.push_back((next, first_step));
+        }
+    }
+
+    None
+}
@@ -0,0 +1,108 @@
+use bevy::{
+    prelude::*,
+    render::camera::ScalingMode,
+    window::{MonitorSelection, PrimaryWindow, WindowPosition, WindowResized},
+};
+
+/// The part of the world that's always shown, in pixels of the game's art.
+const VIEW_SIZE: Vec2 = Vec2::new(226.0, 288.0);
+
+/// Scales the view by the largest whole number of screen pixels per art pixel
+/// that fits the window, so sprites don't shimmer when the window size isn't a
+/// multiple of the view. Otherwise the view is stretched to fit the window.
+#[derive(Resource, Default)]
+pub struct PixelPerfect(pub bool);
+
+pub const MAX_WINDOW_SCALE: u8 = 4;
+
+/// How many screen pixels each pixel of the game's art takes in a window, from
+/// 1 to `MAX_WINDOW_SCALE`. The window is resized to fit on startup and every
+/// time it changes, and can still be resized by hand after.
+#[derive(Resource, Clone, Copy, Debug, PartialEq)]
+pub struct WindowScale(pub u8);
+
+impl Default for WindowScale {
+    fn default() -> Self {
+        Self(2)
+    }
+}
+
+pub struct CameraPlugin;
+
+impl Plugin for CameraPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(PixelPerfect::default());
+        app.insert_resource(WindowScale::default());
+        app.add_systems(Startup, camera_setup);
+        app.add_systems(
+            Update,
+            (
+                resize_window.run_if(resource_changed::<WindowScale>()),
+                update_scaling,
+            )
+                .chain(),
+        );
+    }
+}
+
+fn camera_setup(mut commands: Commands) {
+    let mut camera = Camera2dBundle::default();
+    camera.projection.scaling_mode = ScalingMode::AutoMin {
+        min_width: VIEW_SIZE.x,
+        min_height: VIEW_SIZE.y,
+    };
+    commands.spawn(camera);
+}
+
+/// Sizes the window in physical pixels, so the view fills it at exactly the
+/// scale whatever the monitor's scale factor, and centers it again.
+fn resize_window(
+    window_scale: Res<WindowScale>,
+    mut windows: Query<&mut Window, With<PrimaryWindow>>,
+) {
+    let Ok(mut window) = windows.get_single_mut() else {
+        return;
+    };
+
+    let size = VIEW_SIZE * window_scale.0.clamp(1, MAX_WINDOW_SCALE) as f32;
+    window
+        .resolution
+        .set_physical_resolution(size.x as u32, size.y as u32);
+    window.position = WindowPosition::Centered(MonitorSelection::Current);
+}
+
+fn update_scaling(
+    mut resized_events: EventReader<WindowResized>,
+    pixel_perfect: Res<PixelPerfect>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    mut cameras: Query<(&mut OrthographicProjection, &mut Transform)>,
+) {
+    if resized_events.read().count() == 0 && !pixel_perfect.is_changed() {
+        return;
+    }
+    let Ok(window) = windows.get_single() else {
+        return;
+    };
+
+    let physical_size = Vec2::new(
+        window.physical_width() as f32,
+        window.physical_height() as f32,
+    );
+    let scale = (physical_size / VIEW_SIZE).min_element().floor().max(1.0);
+
+    for (mut projection, mut transform) in cameras.iter_mut() {
+        if pixel_perfect.0 {
+            projection.scaling_mode = ScalingMode::WindowSize(scale / window.scale_factor() as f32);
+            // With an odd number of pixels the center of the window falls in the
+            // middle of a pixel, so move the view by half a pixel to land on one.
+            let odd = Vec2::new(physical_size.x % 2.0, physical_size.y % 2.0);
+            transform.translation = (odd * 0.5 / scale).extend(transform.translation.z);
+        } else {
+            projection.scaling_mode = ScalingMode::AutoMin {
+                min_width: VIEW_SIZE.x,
+                min_height: VIEW_SIZE.y,
+            };
+            transform.translation = Vec2::ZERO.extend(transform.translation.z);
+        }
+    }
+}
@@ -0,0 +1,134 @@
+use bevy::prelude::*;
+
+use crate::{
+    common::{app_state::AppState, levels::Levels},
+    level_stats::GameStats,
+    replay::Replay,
+    services::{files::write_atomically, map::Location, text::TextProvider},
+};
+
+const CAREER_STATS_FILE: &str = "stats";
+
+/// Totals over every game ever played, kept in the `stats` file. The file has
+/// one `key=value` per line: `games_played`, `pellets`, `ghosts` and
+/// `highest_level`. A missing file or line counts as zero.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+struct CareerStats {
+    games_played: u64,
+    pellets: u64,
+    ghosts: u64,
+    highest_level: usize,
+}
+
+impl CareerStats {
+    fn load() -> Self {
+        let mut stats = Self::default();
+
+        let Ok(text) = std::fs::read_to_string(CAREER_STATS_FILE) else {
+            return stats;
+        };
+
+        for line in text.lines().map(str::trim).filter(|line| !line.is_empty()) {
+            let Some((key, value)) = line.split_once('=') else {
+                warn!("Ignoring malformed stats line: {}", line);
+                continue;
+            };
+
+            let (key, value) = (key.trim(), value.trim());
+            let result = match key {
+                "games_played" => value.parse().map(|value| stats.games_played = value),
+                "pellets" => value.parse().map(|value| stats.pellets = value),
+                "ghosts" => value.parse().map(|value| stats.ghosts = value),
+                "highest_level" => value.parse().map(|value| stats.highest_level = value),
+                other => {
+                    warn!("Unknown stat {:?}", other);
+                    continue;
+                }
+            };
+            if let Err(error) = result {
+                warn!("Ignoring stat {} = {:?}: {}", key, value, error);
+            }
+        }
+
+        stats
+    }
+
+    fn save(&self) {
+        let text = format!(
+            "games_played={}\npellets={}\nghosts={}\nhighest_level={}\n",
+            self.games_played, self.pellets, self.ghosts, self.highest_level
+        );
+
+        if let Err(error) = write_atomically(CAREER_STATS_FILE, text) {
+            warn!("Failed to write stats file: {}", error);
+        }
+    }
+
+    /// The stats as lines of text, in the order they're shown.
+    fn lines(&self) -> [String; 4] {
+        [
+            format!("Games played: {}", self.games_played),
+            format!("Pellets eaten: {}", self.pellets),
+            format!("Ghosts eaten: {}", self.ghosts),
+            format!("Highest level: {}", self.highest_level),
+        ]
+    }
+}
+
+pub struct CareerStatsPlugin;
+
+impl Plugin for CareerStatsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(AppState::GameOver), add_finished_game);
+        app.add_systems(OnEnter(AppState::Stats), setup);
+        app.add_systems(OnExit(AppState::Stats), despawn);
+    }
+}
+
+/// Replayed games were already counted when they were played.
+fn add_finished_game(game_stats: Res<GameStats>, levels: Res<Levels>, replay: Option<Res<Replay>>) {
+    if replay.is_some() {
+        return;
+    }
+
+    let mut stats = CareerStats::load();
+    stats.games_played += 1;
+    stats.pellets += game_stats.0.pellets as u64;
+    stats.ghosts += game_stats.0.ghosts as u64;
+    stats.highest_level = stats.highest_level.max(levels.current());
+    stats.save();
+}
+
+fn setup(
+    mut commands: Commands,
+    mut text_provider: ResMut<TextProvider>,
+    asset_server: Res<AssetServer>,
+) {
+    commands.spawn((
+        Location::new(13.5, 27.0),
+        SpriteBundle {
+            texture: text_provider.get_image("Stats", Color::WHITE, &asset_server),
+            sprite: Sprite {
+                custom_size: Some(text_provider.get_size("Stats") * 1.5),
+                ..default()
+            },
+            ..default()
+        },
+    ));
+
+    for (i, line) in CareerStats::load().lines().iter().enumerate() {
+        commands.spawn((
+            Location::new(13.5, 21.0 - 2.0 * i as f32),
+            SpriteBundle {
+                texture: text_provider.get_image(line, Color::WHITE, &asset_server),
+                ..default()
+            },
+        ));
+    }
+}
+
+fn despawn(mut commands: Commands, query: Query<Entity, With<Location>>) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
@@ -1,4 +1,6 @@
-use bevy::prelude::*;
+use std::time::Duration;
+
+use bevy::{ecs::schedule::ScheduleLabel, prelude::*};
 
 #[derive(States, Clone, Eq, PartialEq, Hash, Debug, Default)]
 pub enum AppState {
@@ -7,9 +9,13 @@ pub enum AppState {
     LevelStart,
     MainGame,
     LevelComplete,
+    Intermission,
     PlayerDied,
     GameOver,
     Leaderboard,
+    Stats,
+    LevelSelect,
+    BadMap,
 }
 
 #[derive(States, Clone, Eq, PartialEq, Hash, Debug, Default)]
@@ -22,5 +28,63 @@ pub enum DeadState {
     NotDead,
 }
 
+/// Runs when a new game starts, on leaving the menu and when retrying from the
+/// game over screen. Holds the resets of everything that lasts a whole run,
+/// like the score and lives.
+#[derive(ScheduleLabel, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct NewRun;
+
 #[derive(Resource)]
 pub struct StateTimer(pub Timer);
+
+/// How long the game stays in each timed state before moving on by itself.
+#[derive(Resource)]
+pub struct StateDwellTimes {
+    /// The READY! pause when a game starts, long enough for the intro jingle.
+    pub game_start: Duration,
+    /// The READY! pause before every later level.
+    pub level_start: Duration,
+    /// The maze flashing after a level is cleared.
+    pub level_complete: Duration,
+    /// The cutscene after some levels, unless it's skipped.
+    pub intermission: Duration,
+}
+
+impl Default for StateDwellTimes {
+    fn default() -> Self {
+        Self {
+            game_start: Duration::from_secs(4),
+            level_start: Duration::from_secs(2),
+            level_complete: Duration::from_secs(6),
+            intermission: Duration::from_secs(8),
+        }
+    }
+}
+
+impl StateDwellTimes {
+    /// The time to spend in `state` after entering it from `previous`, or
+    /// `None` for states that are left some other way.
+    pub fn get(&self, state: &AppState, previous: &AppState) -> Option<Duration> {
+        match state {
+            AppState::LevelStart => match previous {
+                AppState::MainMenu | AppState::LevelSelect | AppState::GameOver => {
+                    Some(self.game_start)
+                }
+                _ => Some(self.level_start),
+            },
+            AppState::LevelComplete => Some(self.level_complete),
+            AppState::Intermission => Some(self.intermission),
+            _ => None,
+        }
+    }
+}
+
+#[derive(States, Clone, Eq, PartialEq, Hash, Debug, Default)]
+pub enum PauseState {
+    #[default]
+    Running,
+    Paused,
+    /// Still paused, with the options opened from the pause overlay shown
+    /// in its place.
+    Options,
+}
@@ -1,6 +1,6 @@
 use bevy::prelude::*;
 
-use crate::ghosts::Ghost;
+use crate::ghosts::{Ghost, GhostMode};
 use crate::services::map::Location;
 
 #[derive(Event)]
@@ -19,8 +19,22 @@ pub struct GhostEaten {
     pub eaten_ghosts: usize,
 }
 
+/// Sent every time a ghost's mode changes, but not when ghosts are spawned.
+#[derive(Event, Clone, Copy, Debug)]
+pub struct GhostModeChanged {
+    pub ghost: Ghost,
+    /// Nothing reacts to a particular change yet, only to the new mode.
+    #[allow(dead_code)]
+    pub from: GhostMode,
+    pub to: GhostMode,
+}
+
 #[derive(Resource)]
 pub struct CollisionPauseTimer(pub Timer);
 
 #[derive(Event)]
 pub struct GetExtraLife;
+
+/// Sent once, on the tick the last pellet of a level is eaten.
+#[derive(Event)]
+pub struct LevelComplete;
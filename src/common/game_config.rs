@@ -0,0 +1,37 @@
+use bevy::prelude::*;
+
+const STARTING_LIVES_RANGE: std::ops::RangeInclusive<usize> = 1..=5;
+const STRICT_SCORES_ARG: &str = "--strict-scores";
+
+#[derive(Resource)]
+pub struct GameConfig {
+    starting_lives: usize,
+    strict_scores: bool,
+}
+
+impl Default for GameConfig {
+    fn default() -> Self {
+        Self {
+            starting_lives: 3,
+            strict_scores: std::env::args().any(|arg| arg == STRICT_SCORES_ARG),
+        }
+    }
+}
+
+impl GameConfig {
+    pub fn starting_lives(&self) -> usize {
+        self.starting_lives
+    }
+
+    /// Set with `--strict-scores`. Makes a malformed line in the scores file
+    /// panic instead of being skipped, to catch bugs in how scores are saved.
+    pub fn strict_scores(&self) -> bool {
+        self.strict_scores
+    }
+
+    /// Clamped to between 1 and 5 lives.
+    pub fn set_starting_lives(&mut self, lives: usize) {
+        self.starting_lives =
+            lives.clamp(*STARTING_LIVES_RANGE.start(), *STARTING_LIVES_RANGE.end());
+    }
+}
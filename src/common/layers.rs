@@ -1,4 +1,5 @@
 #[derive(Copy, Clone)]
+#[allow(clippy::upper_case_acronyms)]
 pub enum Layers {
     Map,
     Pellets,
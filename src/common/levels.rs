@@ -1,21 +1,157 @@
 use bevy::prelude::*;
+use serde::Deserialize;
 
-use crate::{ghosts::Ghost, points::BonusSymbol};
+use crate::{
+    ghosts::Ghost,
+    points::{BonusSymbol, BonusSymbolDef},
+};
 
-#[derive(Resource, Default)]
+const MODE_SWITCHES_PER_LEVEL: usize = 7;
+/// The arcade game can't draw this level, so unless endless mode is on the game
+/// ends once the level before it is cleared.
+const KILL_SCREEN_LEVEL: usize = 256;
+/// Levels cleared before the arcade plays an intermission, counted like
+/// `Levels::advancements`.
+const INTERMISSION_LEVELS: [usize; 3] = [2, 5, 9];
+
+#[derive(Deserialize, Debug)]
+struct LevelParams {
+    player_speed: f32,
+    player_frite_speed: f32,
+    #[serde(default)]
+    player_tunnel_speed: Option<f32>,
+    ghost_normal_speed: f32,
+    ghost_tunnel_speed: f32,
+    ghost_frite_speed: f32,
+    elroy_1_dots: usize,
+    elroy_1_speed: f32,
+    elroy_2_dots: usize,
+    elroy_2_speed: f32,
+    frite_duration: u64,
+    frite_flashes: u32,
+    inky_home_exit_dots: usize,
+    clyde_home_exit_dots: usize,
+    ghost_exit_home_duration: u64,
+    ghost_switch_global_mode: Vec<f32>,
+    #[serde(default = "default_bonus_symbol_dots")]
+    bonus_symbol_dots: [usize; 2],
+    #[serde(default = "default_maze_color")]
+    maze_color: (f32, f32, f32),
+    #[serde(default = "default_clyde_scatter_distance")]
+    clyde_scatter_distance: f32,
+}
+
+fn default_bonus_symbol_dots() -> [usize; 2] {
+    [70, 170]
+}
+
+fn default_maze_color() -> (f32, f32, f32) {
+    (1.0, 1.0, 1.0)
+}
+
+fn default_clyde_scatter_distance() -> f32 {
+    8.0
+}
+
+impl LevelParams {
+    fn parse_table(text: &str) -> Result<Vec<Self>, String> {
+        let table: Vec<Self> = ron::from_str(text).map_err(|error| error.to_string())?;
+
+        if table.is_empty() {
+            return Err("Level table has no levels".to_string());
+        }
+
+        for (i, level) in table.iter().enumerate() {
+            if level.ghost_switch_global_mode.len() != MODE_SWITCHES_PER_LEVEL {
+                return Err(format!(
+                    "Level {} has {} mode switch durations, expected {}",
+                    i + 1,
+                    level.ghost_switch_global_mode.len(),
+                    MODE_SWITCHES_PER_LEVEL
+                ));
+            }
+        }
+
+        Ok(table)
+    }
+}
+
+#[derive(Resource)]
 pub struct Levels {
     advancements: usize,
     current: usize,
     pub hard_mode: bool,
+    pub endless: bool,
+    /// Stretches or shortens every scatter and chase period at random, like
+    /// Ms. Pac-Man, so the patterns can't be learned.
+    pub random_mode_timing: bool,
+    level_selected: bool,
+    params: Vec<LevelParams>,
+    bonus_symbols: Vec<BonusSymbolDef>,
+}
+
+impl Default for Levels {
+    fn default() -> Self {
+        const LEVELS_TEXT: &str = include_str!("levels.ron");
+        const BONUS_SYMBOLS_TEXT: &str = include_str!("bonus_symbols.ron");
+
+        Self {
+            advancements: 0,
+            current: 0,
+            hard_mode: false,
+            endless: false,
+            random_mode_timing: false,
+            level_selected: false,
+            params: LevelParams::parse_table(LEVELS_TEXT).expect("Error parsing levels file"),
+            bonus_symbols: BonusSymbolDef::parse_table(BONUS_SYMBOLS_TEXT)
+                .expect("Error parsing bonus symbols file"),
+        }
+    }
 }
 
 impl Levels {
     pub fn reset(&mut self) {
         self.advancements = 0;
         self.current = 0;
+        self.level_selected = false;
+    }
+
+    /// Makes `level` the current level, clamped to the levels the table defines.
+    /// The following call to `next` keeps it, so the game starts on it.
+    pub fn select(&mut self, level: usize) {
+        let level = level.clamp(1, self.defined_levels());
+        self.current = level;
+        self.advancements = level;
+        self.level_selected = true;
+    }
+
+    /// Picks up a saved game on its level. Unlike `select`, the levels moved
+    /// through so far are kept, as in hard mode they differ from the level.
+    /// The following call to `next` keeps it.
+    pub fn resume(&mut self, current: usize, advancements: usize) {
+        self.current = current.clamp(1, self.defined_levels());
+        self.advancements = advancements;
+        self.level_selected = true;
+    }
+
+    pub fn current(&self) -> usize {
+        self.current
+    }
+
+    pub fn advancements(&self) -> usize {
+        self.advancements
+    }
+
+    pub fn defined_levels(&self) -> usize {
+        self.params.len()
     }
 
     pub fn next(&mut self) {
+        if self.level_selected {
+            self.level_selected = false;
+            return;
+        }
+
         self.advancements += 1;
 
         if !self.hard_mode {
@@ -31,145 +167,118 @@ impl Levels {
         }
     }
 
+    /// Whether clearing the current level should end the game. In endless mode
+    /// levels keep counting up, using the parameters of the last defined level.
+    pub fn is_last(&self) -> bool {
+        !self.endless && self.current + 1 >= KILL_SCREEN_LEVEL
+    }
+
+    /// Whether clearing the current level is followed by an intermission. Goes
+    /// by the levels played rather than their number, which hard mode skips
+    /// through.
+    pub fn intermission_follows(&self) -> bool {
+        INTERMISSION_LEVELS.contains(&self.advancements)
+    }
+
     pub fn player_speed(&self) -> f32 {
-        match self.current {
-            1 => 0.8,
-            5..=20 => 1.0,
-            _ => 0.9,
-        }
+        self.params().player_speed
     }
 
     pub fn player_frite_speed(&self) -> f32 {
-        match self.current {
-            1 => 0.9,
-            2..=4 => 0.95,
-            _ => 1.0,
-        }
+        self.params().player_frite_speed
+    }
+
+    /// Speed of the player in the tunnels, if it should differ from the usual.
+    pub fn player_tunnel_speed(&self) -> Option<f32> {
+        self.params().player_tunnel_speed
     }
 
     pub fn ghost_normal_speed(&self) -> f32 {
-        match self.current {
-            1 => 0.75,
-            2..=4 => 0.85,
-            _ => 0.95,
-        }
+        self.params().ghost_normal_speed
     }
 
     pub fn ghost_tunnel_speed(&self) -> f32 {
-        match self.current {
-            1 => 0.4,
-            2..=4 => 0.45,
-            _ => 0.5,
-        }
+        self.params().ghost_tunnel_speed
     }
 
     pub fn elroy_1_dots(&self) -> usize {
-        match self.current {
-            1 => 20,
-            2 => 30,
-            3..=5 => 40,
-            6..=8 => 50,
-            9..=11 => 60,
-            12..=14 => 80,
-            15..=18 => 100,
-            _ => 120,
-        }
+        self.params().elroy_1_dots
     }
 
     pub fn elroy_2_dots(&self) -> usize {
-        match self.current {
-            1 => 10,
-            2 => 15,
-            3..=5 => 20,
-            6..=8 => 25,
-            9..=11 => 30,
-            12..=14 => 40,
-            15..=18 => 50,
-            _ => 60,
-        }
+        self.params().elroy_2_dots
     }
 
     pub fn elroy_1_speed(&self) -> f32 {
-        match self.current {
-            1 => 0.8,
-            2..=4 => 0.9,
-            _ => 1.0,
-        }
+        self.params().elroy_1_speed
     }
 
     pub fn elroy_2_speed(&self) -> f32 {
-        match self.current {
-            1 => 0.85,
-            2..=4 => 0.95,
-            _ => 1.05,
-        }
+        self.params().elroy_2_speed
     }
 
     pub fn ghost_frite_speed(&self) -> f32 {
-        match self.current {
-            1 => 0.5,
-            2..=4 => 0.55,
-            _ => 0.6,
-        }
+        self.params().ghost_frite_speed
     }
 
     pub fn frite_duration(&self) -> u64 {
-        match self.current {
-            1 => 6,
-            2 | 6 | 10 => 5,
-            3 => 4,
-            4 | 14 => 3,
-            5 | 7 | 8 | 11 => 2,
-            9 | 12 | 13 | 15 | 16 | 18 => 1,
-            _ => 0,
-        }
+        self.params().frite_duration
     }
 
     pub fn number_of_frite_flashes(&self) -> f32 {
-        match self.current {
-            1..=8 => 5.0,
-            9 => 3.0,
-            _ => 0.0,
-        }
+        self.params().frite_flashes as f32
     }
 
     pub fn ghost_switch_global_mode(&self, index: usize) -> Option<f32> {
-        match self.current {
-            1 => [7.0, 20.0, 7.0, 20.0, 5.0, 20.0, 5.0],
-            2..=4 => [7.0, 20.0, 7.0, 20.0, 5.0, 1033.0, 1.0 / 60.0],
-            5.. => [5.0, 20.0, 5.0, 20.0, 5.0, 1037.0, 1.0 / 60.0],
-            _ => unreachable!(),
+        self.params().ghost_switch_global_mode.get(index).copied()
+    }
+
+    /// The pellets a ghost counts on its own before it leaves the house, while
+    /// it's the next one to leave.
+    pub fn home_exit_dots(&self, ghost: Ghost) -> usize {
+        match ghost {
+            Ghost::Pinky => 0,
+            Ghost::Inky => self.params().inky_home_exit_dots,
+            Ghost::Clyde => self.params().clyde_home_exit_dots,
+            Ghost::Blinky => unreachable!(),
         }
-        .get(index)
-        .copied()
     }
 
-    pub fn home_exit_dots(&self, ghost: Ghost, life_lost: bool) -> usize {
+    /// The pellets eaten since a life was lost at which a ghost leaves the
+    /// house, the same on every level.
+    pub fn global_home_exit_dots(&self, ghost: Ghost) -> usize {
         match ghost {
-            Ghost::Pinky => {
-                assert!(life_lost);
-                return 7;
-            }
-            Ghost::Inky => match self.current {
-                _ if life_lost => 10,
-                1 => 30,
-                _ => 0,
-            },
-            Ghost::Clyde => match self.current {
-                _ if life_lost => 15,
-                1 => 60,
-                2 => 50,
-                _ => 0,
-            },
+            Ghost::Pinky => 7,
+            Ghost::Inky => 17,
+            Ghost::Clyde => 32,
             Ghost::Blinky => unreachable!(),
         }
     }
 
+    /// The number of pellets eaten at which each of the level's two bonus
+    /// symbols appears.
+    pub fn bonus_symbol_dots(&self) -> [usize; 2] {
+        self.params().bonus_symbol_dots
+    }
+
+    /// Distance in tiles from the player at which Clyde gives up the chase.
+    pub fn clyde_scatter_distance(&self) -> f32 {
+        self.params().clyde_scatter_distance
+    }
+
+    /// Tint multiplied into the maze sprite. White keeps the maze's own colors.
+    pub fn maze_color(&self) -> Color {
+        let (red, green, blue) = self.params().maze_color;
+        Color::rgb(red, green, blue)
+    }
+
     pub fn ghost_exit_home_duration(&self) -> u64 {
-        match self.current {
-            1..=4 => 4,
-            _ => 3,
-        }
+        self.params().ghost_exit_home_duration
+    }
+
+    fn params(&self) -> &LevelParams {
+        let index = self.current.saturating_sub(1).min(self.params.len() - 1);
+        &self.params[index]
     }
 
     pub fn bonus_symbol(&self) -> BonusSymbol {
@@ -184,16 +293,24 @@ impl Levels {
             .collect()
     }
 
+    /// The images of every bonus symbol, both the fruit and its points.
+    pub fn bonus_symbol_assets(&self) -> Vec<String> {
+        self.bonus_symbols
+            .iter()
+            .flat_map(|def| def.assets())
+            .map(str::to_string)
+            .collect()
+    }
+
     fn bonus_symbol_internal(&self, index: usize) -> BonusSymbol {
-        match index {
-            1 => BonusSymbol::Cherries,
-            2 => BonusSymbol::Strawberry,
-            3..=4 => BonusSymbol::Peach,
-            5..=6 => BonusSymbol::Apple,
-            7..=8 => BonusSymbol::Grapes,
-            9..=10 => BonusSymbol::Galaxian,
-            11..=12 => BonusSymbol::Bell,
-            _ => BonusSymbol::Key,
+        let mut next_symbol_level = 1;
+        for def in &self.bonus_symbols {
+            next_symbol_level += def.levels();
+            if index < next_symbol_level {
+                return BonusSymbol::new(def);
+            }
         }
+
+        BonusSymbol::new(self.bonus_symbols.last().unwrap())
     }
 }
@@ -1,5 +1,6 @@
 pub mod app_state;
 pub mod events;
+pub mod game_config;
 pub mod layers;
 pub mod levels;
 pub mod sets;
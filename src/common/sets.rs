@@ -6,3 +6,7 @@ pub enum GameLoop {
     Movement,
     Collisions,
 }
+
+/// Systems that only change how things look, which the headless mode leaves out.
+#[derive(SystemSet, Clone, Eq, PartialEq, Debug, Hash)]
+pub struct Rendering;
@@ -0,0 +1,117 @@
+use bevy::{
+    diagnostic::{DiagnosticsStore, FrameTimeDiagnosticsPlugin},
+    prelude::*,
+};
+
+use crate::{
+    common::{layers::Layers, sets::Rendering},
+    ghosts::DebugOverlay,
+    map_render::NoMapWrap,
+    services::{map::Location, text::TextProvider},
+};
+
+/// Frames drawn and game loop ticks run over the last second, shown in the top
+/// right corner while the debug overlay is on. With the frame rate capped to the
+/// tick rate, a lower frame rate means frames are being dropped.
+#[derive(Resource)]
+struct RateCounter {
+    ticks: u32,
+    tick_rate: u32,
+    timer: Timer,
+}
+
+impl Default for RateCounter {
+    fn default() -> Self {
+        Self {
+            ticks: 0,
+            tick_rate: 0,
+            timer: Timer::from_seconds(1.0, TimerMode::Repeating),
+        }
+    }
+}
+
+#[derive(Component)]
+struct FpsText;
+
+pub struct FpsOverlayPlugin;
+
+impl Plugin for FpsOverlayPlugin {
+    fn build(&self, app: &mut App) {
+        let overlay_on = |overlay: Res<DebugOverlay>| overlay.0;
+
+        app.add_plugins(FrameTimeDiagnosticsPlugin);
+        app.insert_resource(RateCounter::default());
+        app.add_systems(FixedUpdate, count_ticks.run_if(overlay_on));
+        app.add_systems(
+            Update,
+            draw_fps_overlay.in_set(Rendering).run_if(overlay_on),
+        );
+        app.add_systems(
+            Update,
+            despawn_fps_overlay.run_if(resource_changed::<DebugOverlay>()),
+        );
+    }
+}
+
+fn count_ticks(mut counter: ResMut<RateCounter>) {
+    counter.ticks += 1;
+}
+
+fn draw_fps_overlay(
+    mut commands: Commands,
+    mut counter: ResMut<RateCounter>,
+    mut query: Query<&mut Handle<Image>, With<FpsText>>,
+    diagnostics: Res<DiagnosticsStore>,
+    mut text_provider: ResMut<TextProvider>,
+    asset_server: Res<AssetServer>,
+    time: Res<Time>,
+) {
+    let spawned = !query.is_empty();
+    if spawned && !counter.timer.tick(time.delta()).just_finished() {
+        return;
+    }
+
+    counter.tick_rate = counter.ticks;
+    counter.ticks = 0;
+
+    let fps = diagnostics
+        .get(FrameTimeDiagnosticsPlugin::FPS)
+        .and_then(|fps| fps.smoothed())
+        .unwrap_or(0.0);
+    let text = format!("{:.0}FPS {}TPS", fps, counter.tick_rate);
+    let image = text_provider.get_image(text, Color::GREEN, &asset_server);
+
+    if !spawned {
+        commands.spawn((
+            FpsText,
+            NoMapWrap,
+            Location::new(22.0, 32.0),
+            SpriteBundle {
+                texture: image,
+                transform: Transform::from_xyz(0.0, 0.0, Layers::HUD.as_f32()),
+                ..default()
+            },
+        ));
+        return;
+    }
+
+    for mut handle in query.iter_mut() {
+        *handle = image.clone();
+    }
+}
+
+fn despawn_fps_overlay(
+    mut commands: Commands,
+    overlay: Res<DebugOverlay>,
+    mut counter: ResMut<RateCounter>,
+    query: Query<Entity, With<FpsText>>,
+) {
+    if overlay.0 {
+        return;
+    }
+
+    *counter = RateCounter::default();
+    for entity in query.iter() {
+        commands.entity(entity).despawn();
+    }
+}
@@ -2,17 +2,32 @@ use bevy::{
     input::{keyboard::KeyboardInput, ButtonState},
     prelude::*,
 };
-use std::{io::Write, time::Duration};
+use bevy_kira_audio::prelude::*;
+use std::time::Duration;
 
 use crate::{
     common::{
         app_state::{AppState, DeadState},
         layers::Layers,
     },
+    level_stats::GameStats,
     points::Points,
-    services::{map::Location, text::TextProvider},
+    replay::{Recorder, Replay},
+    run_timer::RunTimer,
+    services::{
+        files::write_atomically,
+        input::{GamepadInput, KeyBindings},
+        map::Location,
+        scores::{self, SCORES_FILE},
+        text::TextProvider,
+    },
+    StartGameSound,
 };
 
+const MAX_SAVED_SCORES: usize = 100;
+/// Name entry takes the letter keys, so retrying uses one that can't be typed.
+const RETRY_KEY: KeyCode = KeyCode::Tab;
+
 #[derive(Component)]
 struct LetterIndex(usize);
 
@@ -37,6 +52,13 @@ impl Plugin for GameOverPlugin {
         app.add_systems(OnEnter(AppState::GameOver), (setup, despawn_game_over));
         app.add_systems(Update, update.run_if(in_state(AppState::GameOver)));
         app.add_systems(OnExit(AppState::GameOver), (save_score, despawn).chain());
+        app.add_systems(
+            OnTransition {
+                from: AppState::GameOver,
+                to: AppState::LevelStart,
+            },
+            play_game_start_sound,
+        );
         app.add_systems(
             OnEnter(DeadState::GameOver),
             (spawn_game_over, reset_game_over_timer),
@@ -53,6 +75,7 @@ fn setup(
     mut text_provider: ResMut<TextProvider>,
     asset_server: Res<AssetServer>,
     points: Res<Points>,
+    game_stats: Res<GameStats>,
     mut next_dead_state: ResMut<NextState<DeadState>>,
 ) {
     next_dead_state.set(DeadState::NotDead);
@@ -113,8 +136,34 @@ fn setup(
                 ));
             }
         });
+
+    commands.spawn((
+        Location::new(13.5, 11.0),
+        SpriteBundle {
+            texture: text_provider.get_image(
+                "Enter: menu  Tab: retry",
+                Color::WHITE,
+                &asset_server,
+            ),
+            ..default()
+        },
+    ));
+
+    for (i, line) in game_stats.0.lines().iter().enumerate() {
+        commands.spawn((
+            Location::new(13.5, 8.0 - 1.5 * i as f32),
+            SpriteBundle {
+                texture: text_provider.get_image(line, Color::WHITE, &asset_server),
+                ..default()
+            },
+        ));
+    }
 }
 
+/// Confirming goes back to the menu, while the retry key starts a new game with
+/// the same options right away. Either way the score is saved first, when
+/// leaving this screen.
+#[allow(clippy::too_many_arguments)]
 fn update(
     mut player_name_query: Query<(&mut PlayerName, &Children)>,
     mut letter_query: Query<(
@@ -124,11 +173,14 @@ fn update(
         &mut Visibility,
     )>,
     mut keyboard_events: EventReader<KeyboardInput>,
+    gamepad: GamepadInput,
+    bindings: Res<KeyBindings>,
     mut text_provider: ResMut<TextProvider>,
     asset_server: Res<AssetServer>,
     mut flash_timer: ResMut<FlashTimer>,
     time: Res<Time>,
     mut next_state: ResMut<NextState<AppState>>,
+    replay: Option<Res<Replay>>,
 ) {
     let (mut player_name, children) = player_name_query.single_mut();
     for event in keyboard_events.read() {
@@ -157,12 +209,18 @@ fn update(
                 player_name.0.pop();
             }
 
-            if let KeyCode::Return = key {
+            if *key == bindings.confirm {
                 next_state.set(AppState::MainMenu);
+            } else if *key == RETRY_KEY && replay.is_none() {
+                next_state.set(AppState::LevelStart);
             }
         }
     }
 
+    if gamepad.confirm_just_pressed() {
+        next_state.set(AppState::MainMenu);
+    }
+
     flash_timer.0.tick(time.delta());
 
     for child in children {
@@ -199,28 +257,72 @@ fn update(
     }
 }
 
+fn play_game_start_sound(
+    asset_server: Res<AssetServer>,
+    audio: Res<Audio>,
+    mut start_game_sound: ResMut<StartGameSound>,
+) {
+    start_game_sound.0 = audio
+        .play(asset_server.load("sounds/game_start.wav"))
+        .handle();
+}
+
 fn despawn(mut commands: Commands, query: Query<Entity, With<Location>>) {
     for entity in query.iter() {
         commands.entity(entity).despawn_recursive();
     }
 }
 
-fn save_score(mut points: ResMut<Points>, player_name_query: Query<&PlayerName>) {
-    let player_name = player_name_query.single();
-    if player_name.0.is_empty() {
+/// Replayed games aren't saved, since the score is already on the leaderboard.
+fn save_score(
+    points: Res<Points>,
+    player_name_query: Query<&PlayerName>,
+    recorder: Res<Recorder>,
+    replay: Option<Res<Replay>>,
+    run_timer: Res<RunTimer>,
+) {
+    let player_name = sanitize_name(&player_name_query.single().0);
+    if player_name.is_empty() || replay.is_some() {
         return;
     }
 
-    let mut scores_file = std::fs::OpenOptions::new()
-        .write(true)
-        .create(true)
-        .append(true)
-        .open("scores")
-        .expect("Failed to open scores file");
+    let new_line = format!("{}:{}:{}", player_name, points.score, run_timer.secs());
+    write_scores(new_line);
+    recorder.save(&player_name, points.score);
+}
 
-    writeln!(scores_file, "{}:{}", player_name.0, points.score).expect("Failed to write score");
+/// Keeps only letters, digits and spaces, so the name can't break the
+/// `name:score:time` lines of the `scores` file, and drops trailing spaces.
+fn sanitize_name(name: &str) -> String {
+    name.chars()
+        .filter(|c| c.is_ascii_alphanumeric() || *c == ' ')
+        .collect::<String>()
+        .trim_end()
+        .to_string()
+}
 
-    points.score = 0;
+/// Adds a line to the `scores` file, keeping only the best `MAX_SAVED_SCORES`
+/// entries.
+fn write_scores(new_line: String) {
+    let mut lines = std::fs::read_to_string(SCORES_FILE)
+        .map(|text| text.lines().map(str::to_string).collect::<Vec<_>>())
+        .unwrap_or_default();
+    lines.push(new_line);
+
+    let score = |line: &str| scores::parse_line(line).map(|(_, score, _)| score);
+    lines.retain(|line| {
+        let valid = score(line).is_some();
+        if !valid {
+            warn!("Dropping malformed scores line {:?}", line);
+        }
+        valid
+    });
+    lines.sort_by_key(|line| std::cmp::Reverse(score(line)));
+    lines.truncate(MAX_SAVED_SCORES);
+
+    if let Err(error) = write_atomically(SCORES_FILE, lines.join("\n") + "\n") {
+        warn!("Failed to save scores: {}", error);
+    }
 }
 
 fn spawn_game_over(
@@ -259,3 +361,27 @@ fn goto_game_over_screen(
         next_state.set(AppState::GameOver);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn saved_names_read_back_unchanged() {
+        for (typed, saved) in [
+            ("PAC MAN", "PAC MAN"),
+            ("AB:12", "AB12"),
+            ("A\nB  ", "AB"),
+            (":::", ""),
+        ] {
+            let name = sanitize_name(typed);
+            assert_eq!(name, saved);
+
+            let line = format!("{}:{}:{}", name, 1230, 45);
+            assert_eq!(
+                scores::parse_line(&line),
+                Some((saved.to_string(), 1230, Some(45)))
+            );
+        }
+    }
+}
@@ -0,0 +1,123 @@
+use bevy::prelude::*;
+
+use crate::ghosts::Ghost;
+use crate::services::map::{Direction, Location, Map};
+
+/// Everything a chase target can be computed from.
+pub struct ChaseContext<'a> {
+    pub map: &'a Map,
+    pub current_tile: Location,
+    pub blinky_tile: Location,
+    pub player_tile: Location,
+    pub player_direction: Direction,
+    /// How close, in tiles, Pokey gets to the player before backing off.
+    pub clyde_scatter_distance: f32,
+}
+
+/// How a ghost picks its target tile in scatter and chase modes. The variants
+/// are the arcade behaviours, named after each ghost's nickname.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum GhostStrategy {
+    /// Blinky: chases the player's tile and scatters to the top right.
+    Shadow,
+    /// Pinky: aims four tiles ahead of the player and scatters to the top left.
+    Speedy,
+    /// Inky: doubles the vector from Blinky to two tiles ahead of the player and
+    /// scatters to the bottom right.
+    Bashful,
+    /// Clyde: chases the player until within eight tiles, or the level's
+    /// `clyde_scatter_distance`, then heads to his scatter corner at the bottom
+    /// left.
+    Pokey,
+}
+
+impl GhostStrategy {
+    /// Scatter corners sit just outside the maze, so they can never be reached.
+    pub fn scatter_target(&self, map: &Map) -> Location {
+        let width = map.width() as f32;
+        let height = map.height() as f32;
+
+        match self {
+            GhostStrategy::Shadow => Location::new(width - 3.0, height + 2.0),
+            GhostStrategy::Speedy => Location::new(2.0, height + 2.0),
+            GhostStrategy::Bashful => Location::new(width - 1.0, -1.0),
+            GhostStrategy::Pokey => Location::new(0.0, -1.0),
+        }
+    }
+
+    pub fn chase_target(&self, ctx: &ChaseContext) -> Location {
+        match self {
+            GhostStrategy::Shadow => ctx.player_tile,
+            GhostStrategy::Speedy => ctx.player_tile + ctx.player_direction.get_vec() * 4.0,
+            GhostStrategy::Bashful => {
+                let offset_tile = ctx.player_tile + ctx.player_direction.get_vec() * 2.0;
+                ctx.blinky_tile.lerp(offset_tile, 2.0)
+            }
+            GhostStrategy::Pokey => {
+                let scatter_distance = ctx.clyde_scatter_distance;
+                if ctx.player_tile.distance_squared(ctx.current_tile)
+                    > scatter_distance * scatter_distance
+                {
+                    ctx.player_tile
+                } else {
+                    self.scatter_target(ctx.map)
+                }
+            }
+        }
+    }
+}
+
+/// Ghosts that chase once the nightmare toggle is on.
+const NIGHTMARE_GHOSTS: [Ghost; 2] = [Ghost::Blinky, Ghost::Pinky];
+
+/// The strategy each ghost uses. Replacing an entry changes how that ghost
+/// targets without touching its movement.
+#[derive(Resource)]
+pub struct GhostStrategies {
+    pub blinky: GhostStrategy,
+    pub pinky: GhostStrategy,
+    pub inky: GhostStrategy,
+    pub clyde: GhostStrategy,
+    /// Ghosts that, when chasing, follow the shortest path to the player's tile
+    /// instead of their strategy's target and the arcade's tile by tile choice.
+    pub shortest_path: Vec<Ghost>,
+}
+
+impl Default for GhostStrategies {
+    fn default() -> Self {
+        Self {
+            blinky: GhostStrategy::Shadow,
+            pinky: GhostStrategy::Speedy,
+            inky: GhostStrategy::Bashful,
+            clyde: GhostStrategy::Pokey,
+            shortest_path: Vec::new(),
+        }
+    }
+}
+
+impl GhostStrategies {
+    pub fn get(&self, ghost: Ghost) -> GhostStrategy {
+        match ghost {
+            Ghost::Blinky => self.blinky,
+            Ghost::Pinky => self.pinky,
+            Ghost::Inky => self.inky,
+            Ghost::Clyde => self.clyde,
+        }
+    }
+
+    pub fn uses_shortest_path(&self, ghost: Ghost) -> bool {
+        self.shortest_path.contains(&ghost)
+    }
+
+    pub fn nightmare(&self) -> bool {
+        !self.shortest_path.is_empty()
+    }
+
+    pub fn set_nightmare(&mut self, nightmare: bool) {
+        self.shortest_path = if nightmare {
+            NIGHTMARE_GHOSTS.to_vec()
+        } else {
+            Vec::new()
+        };
+    }
+}
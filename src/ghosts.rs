@@ -1,38 +1,101 @@
+use std::collections::HashMap;
 use std::time::Duration;
 
 use bevy::prelude::*;
 use bevy_kira_audio::prelude::*;
 use strum::{EnumIter, IntoEnumIterator};
 
-use crate::advance_level;
-use crate::common::app_state::{AppState, DeadState, StateTimer};
-use crate::common::events::{CollisionPauseTimer, GhostEaten, PelletEaten};
+use crate::background_sound::LEVEL_COMPLETE_JINGLE_SECS;
+use crate::common::app_state::{AppState, DeadState, PauseState, StateTimer};
+use crate::common::events::{CollisionPauseTimer, GhostEaten, GhostModeChanged, PelletEaten};
 use crate::common::layers::Layers;
 use crate::common::levels::Levels;
-use crate::common::sets::GameLoop;
-use crate::pellets::TotalPellets;
+use crate::common::sets::{GameLoop, Rendering};
+use crate::ghost_strategy::{ChaseContext, GhostStrategies, GhostStrategy};
+use crate::pellets::{PelletsEaten, TotalPellets};
 use crate::player::Player;
-use crate::services::map::{Direction, Location, Map};
-use crate::services::speed::CharacterSpeed;
+use crate::services::a_star::AStar;
+use crate::services::map::{Direction, Location, Map, Markers, TILE_SIZE};
+use crate::services::rng::GameRng;
+use crate::services::speed::{CharacterSpeed, MAX_SPEED};
+use crate::services::text::TextProvider;
+#[cfg(debug_assertions)]
+use crate::NoClip;
+use crate::{advance_level, game_loop_running};
 
 const GHOST_DEBUG: bool = false;
 
+/// Draws each ghost's target tile and planned direction, along with the frame
+/// and tick rates, when on. Toggled with F3.
+#[derive(Resource, Default)]
+pub struct DebugOverlay(pub bool);
+
+/// Marks each ghost with its initial and draws frightened ghosts white instead
+/// of blue, for players who can't tell Inky from a frightened ghost.
+#[derive(Resource, Default)]
+pub struct ColorblindMode(pub bool);
+
 #[derive(Resource)]
 pub struct FriteTimer(pub Timer);
 
+/// Ghosts eaten since the last power pellet, which sets the points for the
+/// next one. Ghosts still dead from an earlier power pellet don't count.
+#[derive(Resource, Default)]
+struct FriteChain(usize);
+
 #[derive(Resource, Default)]
 struct ExitHomeTimer(Timer);
 
 #[derive(Resource, Default)]
-struct GlobalGhostModeTimer {
+pub struct GlobalGhostModeTimer {
     timer: Timer,
     duration_index: usize,
 }
 
+/// Counts pellets towards letting the ghosts out of the house, like the arcade.
+/// Until a life is lost each ghost counts on its own, and only while it's the
+/// next one to leave. Losing a life switches to one count shared by all of
+/// them, which ends when it reaches Clyde's limit with Clyde still inside and
+/// the ghosts go back to their own counts.
 #[derive(Resource, Default)]
-struct GhostPelletEatenCounter {
-    counter: usize,
-    life_lost: bool,
+pub struct GhostPelletEatenCounter {
+    personal: HashMap<Ghost, usize>,
+    /// The value of `PelletsEaten` when the shared count started.
+    global_from: Option<usize>,
+    /// The value of `PelletsEaten` already counted, so each tick only counts
+    /// the pellets eaten since.
+    pub counted_to: usize,
+}
+
+impl GhostPelletEatenCounter {
+    fn is_global(&self) -> bool {
+        self.global_from.is_some()
+    }
+
+    /// Counts the pellets eaten this tick for the next ghost to leave and
+    /// tells whether it may leave. `pellets_eaten` is the level's total so far.
+    fn limit_reached(
+        &mut self,
+        ghost: Ghost,
+        pellets_eaten_now: usize,
+        pellets_eaten: usize,
+        levels: &Levels,
+    ) -> bool {
+        match self.global_from {
+            Some(counted_from) => {
+                let count = pellets_eaten.saturating_sub(counted_from);
+                if ghost == Ghost::Clyde && count >= levels.global_home_exit_dots(Ghost::Clyde) {
+                    self.global_from = None;
+                }
+                count >= levels.global_home_exit_dots(ghost)
+            }
+            None => {
+                let count = self.personal.entry(ghost).or_default();
+                *count += pellets_eaten_now;
+                *count >= levels.home_exit_dots(ghost)
+            }
+        }
+    }
 }
 
 #[derive(Resource, Component, Debug, Clone, Copy, PartialEq, Default)]
@@ -55,7 +118,10 @@ enum GhostSprite {
     Frightened,
 }
 
-#[derive(Component, EnumIter, Clone, Copy, PartialEq, Debug)]
+#[derive(Component)]
+struct GhostLetter;
+
+#[derive(Component, EnumIter, Clone, Copy, PartialEq, Eq, Hash, Debug)]
 pub enum Ghost {
     Blinky,
     Pinky,
@@ -63,8 +129,98 @@ pub enum Ghost {
     Clyde,
 }
 
+/// The ghosts spawned at the start of each level and life, all four unless
+/// debugging. Debug builds pick them on the level select screen, to study one
+/// ghost's pathing on its own.
+#[derive(Resource)]
+pub struct SpawnedGhosts(pub Vec<Ghost>);
+
+impl Default for SpawnedGhosts {
+    fn default() -> Self {
+        if GHOST_DEBUG {
+            Self(vec![Ghost::Blinky])
+        } else {
+            Self(Ghost::iter().collect())
+        }
+    }
+}
+
+#[cfg(debug_assertions)]
+impl SpawnedGhosts {
+    /// Steps through all four ghosts and then each one alone.
+    pub fn cycle(&mut self, step: isize) {
+        let mut choices = vec![Ghost::iter().collect::<Vec<_>>()];
+        choices.extend(Ghost::iter().map(|ghost| vec![ghost]));
+
+        let current = choices
+            .iter()
+            .position(|choice| *choice == self.0)
+            .unwrap_or(0) as isize;
+        let next = (current + step).rem_euclid(choices.len() as isize) as usize;
+        self.0 = choices.swap_remove(next);
+    }
+
+    pub fn describe(&self) -> String {
+        match self.0.as_slice() {
+            [ghost] => format!("{:?}", ghost),
+            ghosts if ghosts.len() == Ghost::iter().len() => "All".to_string(),
+            ghosts => format!("{:?}", ghosts),
+        }
+    }
+}
+
+/// The ghost house, from the map's markers. Ghosts wait in their slots bobbing
+/// half a tile up and down, move sideways to the middle slot to leave, and go
+/// straight up from there to the exit just outside the door.
+#[derive(Resource, Clone, Copy, Debug)]
+pub struct GhostPen {
+    /// Where ghosts come out of the house, and where dead ghosts head to get
+    /// back in. Blinky starts here.
+    pub exit: Location,
+    /// Pinky's slot in the middle, below the exit, then Inky's and Clyde's.
+    pub slots: [Location; 3],
+}
+
+impl GhostPen {
+    pub fn from_markers(markers: &Markers) -> Self {
+        Self {
+            exit: markers.blinky,
+            slots: [markers.pinky, markers.inky, markers.clyde],
+        }
+    }
+
+    /// Where the ghost waits in the house. Blinky starts outside and has none.
+    pub fn slot(&self, ghost: Ghost) -> Option<Location> {
+        match ghost {
+            Ghost::Blinky => None,
+            Ghost::Pinky => Some(self.slots[0]),
+            Ghost::Inky => Some(self.slots[1]),
+            Ghost::Clyde => Some(self.slots[2]),
+        }
+    }
+
+    /// Where dead ghosts stop going down into the house and turn to leave.
+    pub fn middle(&self) -> Location {
+        self.slots[0]
+    }
+
+    pub fn bob_top(&self) -> f32 {
+        self.middle().y() + 0.5
+    }
+
+    pub fn bob_bottom(&self) -> f32 {
+        self.middle().y() - 0.5
+    }
+}
+
+impl FromWorld for GhostPen {
+    fn from_world(world: &mut World) -> Self {
+        Self::from_markers(world.resource::<Map>().markers())
+    }
+}
+
 #[derive(Component, Debug, Clone, Copy)]
-struct GhostDirections {
+pub struct GhostDirections {
     current: Direction,
     planned: Option<Direction>,
 }
@@ -95,6 +251,73 @@ impl GhostDirections {
     }
 }
 
+/// Where a continued game puts a ghost back.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GhostPlace {
+    /// In the house, waiting or on its way out. Eaten ghosts are put back
+    /// here, as if they already made it home.
+    Home { leaving: bool },
+    /// On the board, at the last tile it passed and going on the same way.
+    Board {
+        tile: Location,
+        direction: Direction,
+    },
+}
+
+/// The ghosts of a saved game, snapped to the tile. Frightened ghosts go back
+/// to scattering or chasing, so only the scatter and chase timing is kept.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct GhostState {
+    /// Scatter or chase, for the whole level.
+    pub mode: GhostMode,
+    /// Which of the level's scatter and chase periods it's in.
+    pub mode_period: usize,
+    /// `None` once the last period started, which lasts the rest of the level.
+    pub mode_secs_left: Option<f32>,
+    pub ghosts: Vec<(Ghost, GhostPlace)>,
+}
+
+impl GhostState {
+    pub fn capture<'a>(
+        mode: GhostMode,
+        mode_timer: &GlobalGhostModeTimer,
+        ghosts: impl IntoIterator<Item = (&'a Ghost, &'a GhostMode, &'a Location, &'a GhostDirections)>,
+    ) -> Self {
+        let ghosts = ghosts
+            .into_iter()
+            .map(|(ghost, ghost_mode, location, directions)| {
+                let place = match ghost_mode {
+                    GhostMode::Home(_) => GhostPlace::Home { leaving: false },
+                    GhostMode::HomeExit(_)
+                    | GhostMode::DeadPause
+                    | GhostMode::Dead
+                    | GhostMode::DeadEnterHome => GhostPlace::Home { leaving: true },
+                    GhostMode::Scatter | GhostMode::Chase | GhostMode::Frightened => {
+                        GhostPlace::Board {
+                            tile: location.last_tile(directions.current),
+                            direction: directions.current,
+                        }
+                    }
+                };
+                (*ghost, place)
+            })
+            .collect();
+
+        Self {
+            mode,
+            mode_period: mode_timer.duration_index,
+            mode_secs_left: (!mode_timer.timer.finished())
+                .then(|| mode_timer.timer.remaining_secs()),
+            ghosts,
+        }
+    }
+}
+
+/// Set when continuing a saved game, and taken by the level start that
+/// follows.
+#[derive(Resource, Default)]
+pub struct SavedGhosts(pub Option<GhostState>);
+
 #[derive(Bundle)]
 struct GhostBundle {
     location: Location,
@@ -109,9 +332,16 @@ pub struct GhostPlugin;
 impl Plugin for GhostPlugin {
     fn build(&self, app: &mut App) {
         app.insert_resource(GhostMode::default());
+        app.insert_resource(DebugOverlay::default());
+        app.insert_resource(ColorblindMode::default());
+        app.insert_resource(GhostStrategies::default());
+        app.insert_resource(SpawnedGhosts::default());
+        app.init_resource::<GhostPen>();
         app.insert_resource(GlobalGhostModeTimer::default());
         app.insert_resource(FriteTimer(Timer::from_seconds(0.0, TimerMode::Once)));
+        app.insert_resource(FriteChain::default());
         app.insert_resource(GhostPelletEatenCounter::default());
+        app.insert_resource(SavedGhosts::default());
         app.insert_resource(ExitHomeTimer(Timer::from_seconds(
             0.0,
             TimerMode::Repeating,
@@ -119,14 +349,25 @@ impl Plugin for GhostPlugin {
 
         app.add_systems(
             OnEnter(AppState::LevelStart),
-            (init_level_resources.after(advance_level), spawn_ghosts).chain(),
+            (
+                init_level_resources.after(advance_level),
+                restore_ghost_modes,
+                spawn_ghosts,
+            )
+                .chain(),
         );
         app.add_systems(
             OnEnter(DeadState::Restart),
             (reset_resources_on_death, spawn_ghosts).chain(),
         );
+        app.add_systems(OnEnter(AppState::MainGame), catch_up_pellet_count);
 
-        app.add_systems(FixedUpdate, ghost_eaten_system.before(GameLoop::Planning));
+        app.add_systems(
+            FixedUpdate,
+            ghost_eaten_system
+                .before(GameLoop::Planning)
+                .run_if(game_loop_running),
+        );
         app.add_systems(
             FixedUpdate,
             (
@@ -154,9 +395,17 @@ impl Plugin for GhostPlugin {
         app.add_systems(OnEnter(AppState::GameOver), despawn_ghosts);
         app.add_systems(OnEnter(DeadState::Animation), despawn_ghosts);
 
+        app.add_systems(Update, toggle_debug_overlay);
+        app.add_systems(Update, rise_and_fade.in_set(Rendering));
+        app.add_systems(
+            Update,
+            draw_debug_overlay.in_set(Rendering).run_if(
+                in_state(AppState::MainGame).and_then(|overlay: Res<DebugOverlay>| overlay.0),
+            ),
+        );
         app.add_systems(
             Update,
-            draw_ghosts.run_if(
+            draw_ghosts.in_set(Rendering).run_if(
                 in_state(AppState::MainGame)
                     .or_else(in_state(AppState::LevelStart))
                     .or_else(in_state(DeadState::Restart)),
@@ -165,50 +414,62 @@ impl Plugin for GhostPlugin {
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn spawn_ghosts(
     mut commands: Commands,
     asset_server: Res<AssetServer>,
     mut texture_atlases: ResMut<Assets<TextureAtlas>>,
+    mut text_provider: ResMut<TextProvider>,
     pellets_eaten_counter: Res<GhostPelletEatenCounter>,
+    map: Res<Map>,
+    spawned_ghosts: Res<SpawnedGhosts>,
+    mut saved_ghosts: ResMut<SavedGhosts>,
+    global_ghost_mode: Res<GhostMode>,
 ) {
-    if GHOST_DEBUG {
+    let saved_ghosts = saved_ghosts.0.take().map(|saved| saved.ghosts);
+    for ghost in Ghost::iter().filter(|ghost| spawned_ghosts.0.contains(ghost)) {
+        let saved_place = saved_ghosts.as_ref().and_then(|saved| {
+            saved
+                .iter()
+                .find(|(saved_ghost, _)| *saved_ghost == ghost)
+                .map(|(_, place)| *place)
+        });
+
         spawn_ghost(
-            Ghost::Blinky,
+            ghost,
             &mut commands,
             &asset_server,
             &mut texture_atlases,
-            false,
+            &mut text_provider,
+            map.markers(),
+            pellets_eaten_counter.is_global(),
+            saved_place.map(|place| (place, *global_ghost_mode)),
         );
-    } else {
-        for ghost in Ghost::iter() {
-            spawn_ghost(
-                ghost,
-                &mut commands,
-                &asset_server,
-                &mut texture_atlases,
-                pellets_eaten_counter.life_lost,
-            );
-        }
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn spawn_ghost(
     ghost: Ghost,
     commands: &mut Commands,
     asset_server: &Res<AssetServer>,
     texture_atlases: &mut ResMut<Assets<TextureAtlas>>,
+    text_provider: &mut ResMut<TextProvider>,
+    markers: &Markers,
     life_lost: bool,
+    saved: Option<(GhostPlace, GhostMode)>,
 ) {
-    let (texture_path, location, directions, mode) = match ghost {
+    let location = start_location(ghost, markers);
+    let (texture_path, letter, directions, mode) = match ghost {
         Ghost::Blinky => (
             "blinky_body.png",
-            Location::new(13.5, 19.0),
+            "B",
             GhostDirections::new(Direction::Left),
             GhostMode::HomeExit(false),
         ),
         Ghost::Pinky => (
             "pinky_body.png",
-            Location::new(13.5, 16.0),
+            "P",
             GhostDirections::new(Direction::Down),
             if life_lost {
                 GhostMode::Home(false)
@@ -218,18 +479,34 @@ fn spawn_ghost(
         ),
         Ghost::Inky => (
             "inky_body.png",
-            Location::new(11.5, 16.0),
+            "I",
             GhostDirections::new(Direction::Up),
             GhostMode::Home(false),
         ),
         Ghost::Clyde => (
             "clyde_body.png",
-            Location::new(15.5, 16.0),
+            "C",
             GhostDirections::new(Direction::Up),
             GhostMode::Home(false),
         ),
     };
 
+    let (location, directions, mode) = match saved {
+        None => (location, directions, mode),
+        Some((GhostPlace::Home { leaving }, _)) => (
+            location,
+            directions,
+            if leaving {
+                GhostMode::HomeExit(false)
+            } else {
+                GhostMode::Home(false)
+            },
+        ),
+        Some((GhostPlace::Board { tile, direction }, global_mode)) => {
+            (tile, GhostDirections::new(direction), global_mode)
+        }
+    };
+
     commands
         .spawn((
             GhostBundle {
@@ -270,28 +547,67 @@ fn spawn_ghost(
                     ghost_sprite,
                 ));
             }
+
+            parent.spawn((
+                SpriteBundle {
+                    texture: text_provider.get_image(letter, Color::WHITE, asset_server),
+                    transform: Transform::from_xyz(0.0, 11.0, Layers::GhostsEyes.as_f32()),
+                    visibility: Visibility::Hidden,
+                    ..default()
+                },
+                GhostLetter,
+            ));
         });
 }
 
+/// Blinky starts just outside the ghost house door, which is also where every
+/// ghost leaves the house and where eaten ghosts head back to. Pinky starts in
+/// the middle of the house.
+fn start_location(ghost: Ghost, markers: &Markers) -> Location {
+    match ghost {
+        Ghost::Blinky => markers.blinky,
+        Ghost::Pinky => markers.pinky,
+        Ghost::Inky => markers.inky,
+        Ghost::Clyde => markers.clyde,
+    }
+}
+
+/// How far random mode timing moves a scatter or chase period, as a fraction of
+/// its length.
+const MODE_TIMING_JITTER: f32 = 0.25;
+
+/// The level's `index`th scatter or chase period. With random mode timing it's
+/// made up to `MODE_TIMING_JITTER` longer or shorter, otherwise the random
+/// numbers aren't touched.
+fn global_mode_duration(levels: &Levels, index: usize, rng: &mut GameRng) -> Option<Duration> {
+    let secs = levels.ghost_switch_global_mode(index)?;
+    let secs = if levels.random_mode_timing {
+        secs * (1.0 + MODE_TIMING_JITTER * (rng.f32() * 2.0 - 1.0))
+    } else {
+        secs
+    };
+
+    Some(Duration::from_secs_f32(secs))
+}
+
 fn init_level_resources(
     mut global_ghost_mode: ResMut<GhostMode>,
     mut global_mode_timer: ResMut<GlobalGhostModeTimer>,
     mut pellet_eaten_counter: ResMut<GhostPelletEatenCounter>,
     mut exit_home_timer: ResMut<ExitHomeTimer>,
     levels: Res<Levels>,
+    mut rng: ResMut<GameRng>,
 ) {
     *global_ghost_mode = GhostMode::Scatter;
 
-    global_mode_timer
-        .timer
-        .set_duration(Duration::from_secs_f32(
-            levels.ghost_switch_global_mode(0).unwrap(),
-        ));
+    global_mode_timer.timer.set_duration(
+        global_mode_duration(&levels, 0, &mut rng).expect("Level has no scatter period"),
+    );
     global_mode_timer.timer.reset();
     global_mode_timer.duration_index = 0;
 
-    pellet_eaten_counter.counter = 0;
-    pellet_eaten_counter.life_lost = false;
+    pellet_eaten_counter.personal.clear();
+    pellet_eaten_counter.global_from = None;
 
     exit_home_timer
         .0
@@ -299,16 +615,52 @@ fn init_level_resources(
     exit_home_timer.0.reset();
 }
 
+/// Picks up the scatter and chase timing of a continued game where it was
+/// saved.
+fn restore_ghost_modes(
+    saved_ghosts: Res<SavedGhosts>,
+    mut global_ghost_mode: ResMut<GhostMode>,
+    mut global_mode_timer: ResMut<GlobalGhostModeTimer>,
+) {
+    let Some(saved) = &saved_ghosts.0 else {
+        return;
+    };
+
+    *global_ghost_mode = saved.mode;
+    global_mode_timer.duration_index = saved.mode_period;
+    global_mode_timer
+        .timer
+        .set_duration(Duration::from_secs_f32(
+            saved.mode_secs_left.unwrap_or_default(),
+        ));
+    global_mode_timer.timer.reset();
+    if saved.mode_secs_left.is_none() {
+        // Already finished, so the last period isn't ended again. A paused
+        // timer wouldn't tick, and `timer_pause` pauses it again if needed.
+        global_mode_timer.timer.unpause();
+        global_mode_timer.timer.tick(Duration::ZERO);
+    }
+}
+
 fn reset_resources_on_death(
     mut pellet_eaten_counter: ResMut<GhostPelletEatenCounter>,
     mut exit_home_timer: ResMut<ExitHomeTimer>,
+    pellets_eaten: Res<PelletsEaten>,
 ) {
-    pellet_eaten_counter.life_lost = true;
-    pellet_eaten_counter.counter = 0;
+    pellet_eaten_counter.global_from = Some(pellets_eaten.0);
 
     exit_home_timer.0.reset();
 }
 
+/// A continued game starts its level with pellets already eaten, which no
+/// ghost should count, so counting starts from wherever the level is.
+fn catch_up_pellet_count(
+    mut pellet_eaten_counter: ResMut<GhostPelletEatenCounter>,
+    pellets_eaten: Res<PelletsEaten>,
+) {
+    pellet_eaten_counter.counted_to = pellets_eaten.0;
+}
+
 fn timer_pause(
     pause_timer: Res<CollisionPauseTimer>,
     mut frite_timer: ResMut<FriteTimer>,
@@ -330,47 +682,54 @@ fn timer_pause(
     }
 }
 
+#[allow(clippy::too_many_arguments, clippy::collapsible_match)]
 fn update_ghost_mode(
     mut query: Query<(&mut GhostMode, &mut GhostDirections, &Location, &Ghost)>,
     global_ghost_mode: Res<GhostMode>,
-    mut pellet_eaten_events: EventReader<PelletEaten>,
     mut ghost_pellet_eaten_counter: ResMut<GhostPelletEatenCounter>,
+    pellets_eaten: Res<PelletsEaten>,
     mut ghost_eaten_events: EventReader<GhostEaten>,
     mut frite_timer: ResMut<FriteTimer>,
     mut exit_home_timer: ResMut<ExitHomeTimer>,
     pause_timer: Res<CollisionPauseTimer>,
     levels: Res<Levels>,
     time: Res<Time>,
+    pen: Res<GhostPen>,
+    mut mode_changed_events: EventWriter<GhostModeChanged>,
 ) {
     let frite_timer_finished = frite_timer.0.tick(time.delta()).just_finished();
 
-    ghost_pellet_eaten_counter.counter += pellet_eaten_events.len();
+    let pellets_eaten_now = pellets_eaten
+        .0
+        .saturating_sub(ghost_pellet_eaten_counter.counted_to);
+    ghost_pellet_eaten_counter.counted_to = pellets_eaten.0;
 
-    if pellet_eaten_events.read().count() != 0 {
+    if pellets_eaten_now != 0 {
         exit_home_timer.0.reset();
     }
     let exit_home_timer_finished = exit_home_timer.0.tick(time.delta()).just_finished();
 
-    if exit_home_timer_finished {
-        ghost_pellet_eaten_counter.counter = 0;
-    }
-
     let eaten_ghosts = ghost_eaten_events
         .read()
         .map(|event| event.ghost)
         .collect::<Vec<_>>();
 
-    let (inky_is_in_home, pinky_is_in_home) = query
-        .iter()
-        .filter(|(_, _, _, ghost)| matches!(ghost, Ghost::Inky | Ghost::Pinky))
-        .map(|(mode, _, _, ghost)| (matches!(*mode, GhostMode::Home(_)), ghost))
-        .fold((false, false), |acc, (in_home, ghost)| match ghost {
-            Ghost::Inky => (in_home, acc.1),
-            Ghost::Pinky => (acc.0, in_home),
-            _ => unreachable!(),
+    // Only the first ghost still inside, in the order they leave, counts and
+    // can leave. A ghost that wasn't spawned doesn't hold the others in.
+    let next_to_leave = [Ghost::Pinky, Ghost::Inky, Ghost::Clyde]
+        .into_iter()
+        .find(|next| {
+            query
+                .iter()
+                .any(|(mode, _, _, ghost)| ghost == next && matches!(*mode, GhostMode::Home(_)))
         });
 
+    let count_reached = next_to_leave.is_some_and(|ghost| {
+        ghost_pellet_eaten_counter.limit_reached(ghost, pellets_eaten_now, pellets_eaten.0, &levels)
+    });
+
     for (mut mode, mut directions, location, ghost) in query.iter_mut() {
+        let prev_mode = *mode;
         match *mode {
             GhostMode::Frightened => {
                 if eaten_ghosts.contains(ghost) {
@@ -385,12 +744,12 @@ fn update_ghost_mode(
                 }
             }
             GhostMode::Dead => {
-                if *location == Location::new(13.5, 19.0) {
+                if *location == pen.exit {
                     *mode = GhostMode::DeadEnterHome;
                 }
             }
             GhostMode::DeadEnterHome => {
-                if *location == Location::new(13.5, 16.0) {
+                if *location == pen.middle() {
                     *mode = GhostMode::HomeExit(false);
                 }
             }
@@ -400,20 +759,8 @@ fn update_ghost_mode(
                     frightened = false;
                 }
 
-                let can_leave = match *ghost {
-                    Ghost::Blinky => unreachable!(),
-                    Ghost::Pinky => true,
-                    Ghost::Inky => !pinky_is_in_home,
-                    Ghost::Clyde => !pinky_is_in_home && !inky_is_in_home,
-                };
-
-                if can_leave
-                    && (ghost_pellet_eaten_counter.counter
-                        >= levels.home_exit_dots(*ghost, ghost_pellet_eaten_counter.life_lost)
-                        || exit_home_timer_finished)
-                {
+                if next_to_leave == Some(*ghost) && (count_reached || exit_home_timer_finished) {
                     *mode = GhostMode::HomeExit(frightened);
-                    ghost_pellet_eaten_counter.counter = 0;
                 }
             }
             GhostMode::HomeExit(mut frightened) => {
@@ -421,7 +768,7 @@ fn update_ghost_mode(
                     frightened = false;
                     *mode = GhostMode::HomeExit(false);
                 }
-                if location.y == 19.0 {
+                if location.y() == pen.exit.y() {
                     directions.current = Direction::Left;
                     directions.planned = Some(Direction::Left);
 
@@ -438,13 +785,23 @@ fn update_ghost_mode(
             }
             _ => (),
         }
+
+        if *mode != prev_mode {
+            mode_changed_events.send(GhostModeChanged {
+                ghost: *ghost,
+                from: prev_mode,
+                to: *mode,
+            });
+        }
     }
 }
 
 fn detect_power_pellet(
-    mut query: Query<(&mut GhostMode, &mut GhostDirections), With<Ghost>>,
+    mut query: Query<(&mut GhostMode, &mut GhostDirections, &Ghost)>,
     mut frite_timer: ResMut<FriteTimer>,
+    mut frite_chain: ResMut<FriteChain>,
     mut pellet_eaten_events: EventReader<PelletEaten>,
+    mut mode_changed_events: EventWriter<GhostModeChanged>,
     levels: Res<Levels>,
 ) {
     let power_pellet_eaten = pellet_eaten_events
@@ -452,65 +809,67 @@ fn detect_power_pellet(
         .find(|event| event.power)
         .is_some();
 
-    if power_pellet_eaten {
-        frite_timer.0.reset();
-        frite_timer
-            .0
-            .set_duration(Duration::from_secs(levels.frite_duration()));
-
-        for (mut mode, mut directions) in query.iter_mut() {
-            let prev_mode = *mode;
-            *mode = match *mode {
-                GhostMode::Home(_) => GhostMode::Home(true),
-                GhostMode::HomeExit(_) => GhostMode::HomeExit(true),
-                GhostMode::DeadPause => GhostMode::DeadPause,
-                GhostMode::Dead => GhostMode::Dead,
-                GhostMode::DeadEnterHome => GhostMode::DeadEnterHome,
-                _ => GhostMode::Frightened,
-            };
+    // Later levels have no fright at all, so the ghosts carry on as if the
+    // power pellet was a regular one, only its points are different.
+    if !power_pellet_eaten || levels.frite_duration() == 0 {
+        return;
+    }
 
-            if prev_mode != *mode {
-                directions.reverse();
-            }
+    frite_timer.0.reset();
+    frite_timer
+        .0
+        .set_duration(Duration::from_secs(levels.frite_duration()));
+    frite_chain.0 = 0;
+
+    // Ghosts that are already frightened keep going the same way, only
+    // their timer is extended.
+    for (mut mode, mut directions, ghost) in query.iter_mut() {
+        let prev_mode = *mode;
+        *mode = match *mode {
+            GhostMode::Home(_) => GhostMode::Home(true),
+            GhostMode::HomeExit(_) => GhostMode::HomeExit(true),
+            GhostMode::DeadPause => GhostMode::DeadPause,
+            GhostMode::Dead => GhostMode::Dead,
+            GhostMode::DeadEnterHome => GhostMode::DeadEnterHome,
+            _ => GhostMode::Frightened,
+        };
+
+        if prev_mode != *mode {
+            directions.reverse();
+            mode_changed_events.send(GhostModeChanged {
+                ghost: *ghost,
+                from: prev_mode,
+                to: *mode,
+            });
         }
     }
 }
 
 fn update_ghost_speed(
     mut query: Query<(&mut CharacterSpeed, &GhostMode, &Location, &Ghost)>,
-    pellets_eaten_counter: Res<GhostPelletEatenCounter>,
+    pellets_eaten: Res<PelletsEaten>,
     total_pellets: Res<TotalPellets>,
     pause_timer: Res<CollisionPauseTimer>,
     levels: Res<Levels>,
+    map: Res<Map>,
 ) {
     query
         .par_iter_mut()
         .for_each(|(mut speed, mode, location, ghost)| {
-            let in_tunnel = location.y == 16.0 && (location.x <= 5.0 || location.x >= 22.0);
+            let in_tunnel = map.is_tunnel(*location);
 
             let mode_speed = if let GhostMode::Dead | GhostMode::DeadEnterHome = *mode {
-                1.05
+                MAX_SPEED
             } else if !pause_timer.0.finished() {
                 0.0
             } else if in_tunnel {
                 levels.ghost_tunnel_speed()
             } else {
-                let remaining_pellets = total_pellets.0 - pellets_eaten_counter.counter;
+                let remaining_pellets = total_pellets.0 - pellets_eaten.0;
                 match *mode {
                     GhostMode::Frightened => levels.ghost_frite_speed(),
                     GhostMode::Home(_) | GhostMode::HomeExit(_) => 0.4,
-                    // Elroy!!!!!
-                    _ if matches!(*ghost, Ghost::Blinky)
-                        && remaining_pellets <= levels.elroy_2_dots() =>
-                    {
-                        levels.elroy_2_speed()
-                    }
-                    _ if matches!(*ghost, Ghost::Blinky)
-                        && remaining_pellets <= levels.elroy_1_dots() =>
-                    {
-                        levels.elroy_1_speed()
-                    }
-                    _ => levels.ghost_normal_speed(),
+                    _ => normal_speed(*ghost, remaining_pellets, &levels),
                 }
             };
 
@@ -519,6 +878,16 @@ fn update_ghost_speed(
         });
 }
 
+/// Speed while scattering or chasing, given the pellets left on the board.
+fn normal_speed(ghost: Ghost, remaining_pellets: usize, levels: &Levels) -> f32 {
+    match ghost {
+        // Elroy!!!!!
+        Ghost::Blinky if remaining_pellets <= levels.elroy_2_dots() => levels.elroy_2_speed(),
+        Ghost::Blinky if remaining_pellets <= levels.elroy_1_dots() => levels.elroy_1_speed(),
+        _ => levels.ghost_normal_speed(),
+    }
+}
+
 fn ghost_tile_change_detection(
     mut query: Query<(&Location, &mut GhostDirections, &CharacterSpeed), With<Ghost>>,
 ) {
@@ -534,12 +903,23 @@ fn ghost_tile_change_detection(
         });
 }
 
+#[allow(clippy::too_many_arguments)]
 fn plan_ghosts(
     mut query: Query<(&Location, &mut GhostDirections, &Ghost, &GhostMode), Without<Player>>,
     player_query: Query<(&Location, &Direction), With<Player>>,
     map: Res<Map>,
+    mut rng: ResMut<GameRng>,
+    strategies: Res<GhostStrategies>,
+    levels: Res<Levels>,
+    pen: Res<GhostPen>,
+    mut shortest_paths: Local<HashMap<Ghost, AStar>>,
 ) {
+    if map.is_changed() {
+        shortest_paths.clear();
+    }
+
     let map = &*map;
+    let rng = &mut *rng;
     let (player_location, player_direction) = player_query.single();
     let player_tile = player_location.get_tile(*player_direction);
 
@@ -552,7 +932,7 @@ fn plan_ghosts(
     }
 
     query
-        .par_iter_mut()
+        .iter_mut()
         .for_each(|(location, mut directions, ghost, mode)| {
             if let GhostMode::Home(_)
             | GhostMode::HomeExit(_)
@@ -572,35 +952,48 @@ fn plan_ghosts(
                 return;
             }
 
-            let target_tile = match *mode {
-                GhostMode::Scatter => Some(scatter(*ghost)),
-                GhostMode::Chase => Some(chase_target(
-                    *ghost,
-                    location.get_tile(directions.current),
+            let target_tile = target_tile(
+                strategies.get(*ghost),
+                *mode,
+                &ChaseContext {
+                    map,
+                    current_tile: location.get_tile(directions.current),
                     blinky_tile,
                     player_tile,
-                    *player_direction,
-                )),
-                GhostMode::Frightened => None,
-                GhostMode::Dead => Some(Location::new(13.5, 19.0)),
-                GhostMode::Home(_)
-                | GhostMode::HomeExit(_)
-                | GhostMode::DeadEnterHome
-                | GhostMode::DeadPause => unreachable!(),
-            };
+                    player_direction: *player_direction,
+                    clyde_scatter_distance: levels.clyde_scatter_distance(),
+                },
+            );
 
             let next_tile = location.next_tile(directions.current);
-            let in_special_zone = 10.0 <= location.x
-                && location.x <= 17.0
-                && (location.y == 7.0 || location.y == 19.0);
-
-            let planned_direction = ghost_path_finder(
-                next_tile,
-                target_tile,
-                map,
-                directions.current,
-                in_special_zone,
-            );
+            let in_special_zone = (location.x() - pen.exit.x()).abs() <= 3.5
+                && (location.y() == map.markers().player.y() || location.y() == pen.exit.y());
+
+            let shortest_path_direction =
+                if *mode == GhostMode::Chase && strategies.uses_shortest_path(*ghost) {
+                    shortest_path_step(
+                        &mut shortest_paths,
+                        *ghost,
+                        next_tile,
+                        player_tile,
+                        map,
+                        directions.current,
+                        in_special_zone,
+                    )
+                } else {
+                    None
+                };
+
+            let planned_direction = shortest_path_direction.or_else(|| {
+                ghost_path_finder(
+                    next_tile,
+                    target_tile,
+                    map,
+                    directions.current,
+                    in_special_zone,
+                    rng,
+                )
+            });
 
             if GHOST_DEBUG || planned_direction.is_none() {
                 println!("Directions: {:?}", directions);
@@ -613,39 +1006,49 @@ fn plan_ghosts(
         });
 }
 
-fn scatter(ghost: Ghost) -> Location {
-    match ghost {
-        Ghost::Blinky => Location::new(25.0, 33.0),
-        Ghost::Pinky => Location::new(2.0, 33.0),
-        Ghost::Inky => Location::new(27.0, -1.0),
-        Ghost::Clyde => Location::new(0.0, -1.0),
+fn target_tile(strategy: GhostStrategy, mode: GhostMode, ctx: &ChaseContext) -> Option<Location> {
+    match mode {
+        GhostMode::Scatter => Some(strategy.scatter_target(ctx.map)),
+        GhostMode::Chase => Some(strategy.chase_target(ctx)),
+        GhostMode::Frightened => None,
+        GhostMode::Dead => Some(ctx.map.markers().blinky),
+        GhostMode::Home(_)
+        | GhostMode::HomeExit(_)
+        | GhostMode::DeadEnterHome
+        | GhostMode::DeadPause => unreachable!(),
     }
 }
 
-fn chase_target(
+/// The ghost's next step along its shortest path to the player. The path is
+/// only searched again once the player reaches another tile or the ghost is
+/// off it, and `None` when the player can't be reached.
+fn shortest_path_step(
+    shortest_paths: &mut HashMap<Ghost, AStar>,
     ghost: Ghost,
-    current_tile: Location,
-    blinky_tile: Location,
+    next_tile: Location,
     player_tile: Location,
-    player_direction: Direction,
-) -> Location {
-    match ghost {
-        Ghost::Blinky => player_tile,
-        Ghost::Pinky => player_tile + player_direction.get_vec() * 4.0,
-        Ghost::Inky => {
-            let offset_tile = player_tile + player_direction.get_vec() * 2.0;
-            let blinky_offset_vector = offset_tile - blinky_tile;
-            blinky_tile + blinky_offset_vector * 2.0
-        }
-        Ghost::Clyde => {
-            let distance = (player_tile - current_tile).length_squared();
-            if distance > 8.0 * 8.0 {
-                player_tile
-            } else {
-                scatter(ghost)
-            }
-        }
+    map: &Map,
+    current_direction: Direction,
+    is_in_special_zone: bool,
+) -> Option<Direction> {
+    let allowed = |direction: Direction| {
+        direction != current_direction.opposite()
+            && !(is_in_special_zone && direction == Direction::Up)
+    };
+
+    let cached_step = shortest_paths
+        .get(&ghost)
+        .filter(|path| path.goal() == player_tile)
+        .and_then(|path| path.step(next_tile))
+        .filter(|direction| allowed(*direction));
+    if cached_step.is_some() {
+        return cached_step;
     }
+
+    let path = AStar::find(map, next_tile, player_tile, allowed)?;
+    let step = path.step(next_tile);
+    shortest_paths.insert(ghost, path);
+    step
 }
 
 fn ghost_path_finder(
@@ -654,36 +1057,40 @@ fn ghost_path_finder(
     map: &Map,
     current_direction: Direction,
     is_in_special_zone: bool,
+    rng: &mut GameRng,
 ) -> Option<Direction> {
-    let mut possible_directions = map.possible_directions(next_tile);
-
-    possible_directions.retain(|direction| {
-        if is_in_special_zone && *direction == Direction::Up {
-            return false;
-        }
+    let mut neighbors = map
+        .neighbors(next_tile)
+        .filter(|(direction, _)| {
+            if is_in_special_zone && *direction == Direction::Up {
+                return false;
+            }
 
-        *direction != current_direction.opposite()
-    });
+            *direction != current_direction.opposite()
+        })
+        .collect::<Vec<_>>();
 
     if let Some(target_tile) = target_tile {
-        possible_directions.sort_by(|direction1, direction2| {
-            let tile1 = next_tile.next_tile(*direction1);
-            let tile2 = next_tile.next_tile(*direction2);
-
-            let distance1 = (tile1 - target_tile).length_squared();
-            let distance2 = (tile2 - target_tile).length_squared();
-
-            distance1.partial_cmp(&distance2).unwrap()
+        neighbors.sort_by(|(direction1, tile1), (direction2, tile2)| {
+            let distance1 = tile1.distance_squared(target_tile);
+            let distance2 = tile2.distance_squared(target_tile);
+
+            distance1
+                .partial_cmp(&distance2)
+                .unwrap()
+                .then_with(|| direction1.turn_priority().cmp(&direction2.turn_priority()))
         });
 
-        possible_directions.get(0).copied()
+        neighbors.first().map(|(direction, _)| *direction)
     } else {
-        let range = 0..possible_directions.len();
+        let range = 0..neighbors.len();
         if range.is_empty() {
             return None;
         }
-        let direction_index = fastrand::usize(range);
-        possible_directions.get(direction_index).copied()
+        let direction_index = rng.usize(range);
+        neighbors
+            .get(direction_index)
+            .map(|(direction, _)| *direction)
     }
 }
 
@@ -696,7 +1103,12 @@ fn move_ghosts(
         &CharacterSpeed,
     )>,
     next_game_state: Res<NextState<AppState>>,
+    pen: Res<GhostPen>,
 ) {
+    let home_exit = pen.exit;
+    let home_bottom = pen.bob_bottom();
+    let home_top = pen.bob_top();
+
     query
         .par_iter_mut()
         .for_each(|(mut location, mut directions, mode, ghost, speed)| {
@@ -706,49 +1118,38 @@ fn move_ghosts(
 
             match *mode {
                 GhostMode::Home(_) => {
-                    match ghost {
-                        Ghost::Pinky => location.x = 13.5,
-                        Ghost::Inky => location.x = 11.5,
-                        Ghost::Clyde => location.x = 15.5,
-                        Ghost::Blinky => unreachable!(),
-                    }
+                    let slot = pen.slot(*ghost).expect("Blinky never waits in the house");
+                    location.set_x(slot.x());
 
-                    if location.y >= 16.5 {
+                    if location.y() >= home_top {
                         directions.current = Direction::Down;
-                    } else if location.y <= 15.5 {
+                    } else if location.y() <= home_bottom {
                         directions.current = Direction::Up;
                     }
                 }
                 GhostMode::HomeExit(_) => match *ghost {
                     Ghost::Blinky => {
-                        debug_assert!(location.x == 13.5);
-                        debug_assert!(location.y >= 15.5 && location.y <= 19.0);
+                        debug_assert!(location.x() == home_exit.x());
+                        debug_assert!(location.y() >= home_bottom && location.y() <= home_exit.y());
 
                         directions.current = Direction::Up;
                     }
                     Ghost::Pinky => {
-                        debug_assert!(location.x == 13.5);
-                        debug_assert!(location.y >= 15.5 && location.y <= 19.0);
+                        debug_assert!(location.x() == home_exit.x());
+                        debug_assert!(location.y() >= home_bottom && location.y() <= home_exit.y());
 
                         directions.current = Direction::Up;
                     }
-                    Ghost::Inky => {
-                        debug_assert!(location.y >= 15.5 && location.y <= 19.0);
-
-                        if location.x != 13.5 {
-                            directions.current = Direction::Right;
-                        } else {
-                            directions.current = Direction::Up;
-                        }
-                    }
-                    Ghost::Clyde => {
-                        debug_assert!(location.y >= 15.5 && location.y <= 19.0);
+                    Ghost::Inky | Ghost::Clyde => {
+                        debug_assert!(location.y() >= home_bottom && location.y() <= home_exit.y());
 
-                        if location.x != 13.5 {
-                            directions.current = Direction::Left;
+                        directions.current = if location.x() < home_exit.x() {
+                            Direction::Right
+                        } else if location.x() > home_exit.x() {
+                            Direction::Left
                         } else {
-                            directions.current = Direction::Up;
-                        }
+                            Direction::Up
+                        };
                     }
                 },
                 GhostMode::DeadEnterHome => directions.current = Direction::Down,
@@ -759,6 +1160,107 @@ fn move_ghosts(
         });
 }
 
+/// Ghosts look where they're about to turn once they're past the middle of the
+/// tile before the turn. Only the sprite changes, `move_ghosts` still follows
+/// `directions.current`.
+fn eyes_direction(
+    directions: &GhostDirections,
+    location: &Location,
+    mode: &GhostMode,
+) -> Direction {
+    // Only these modes path find, the rest steer by setting `current` directly
+    // and can leave a stale plan behind.
+    let path_finding = matches!(
+        *mode,
+        GhostMode::Scatter | GhostMode::Chase | GhostMode::Frightened | GhostMode::Dead
+    );
+    let Some(planned) = directions.planned.filter(|_| path_finding) else {
+        return directions.current;
+    };
+
+    let to_tile_center = location.get_tile(directions.current) - *location;
+    if to_tile_center
+        .to_vec()
+        .dot(directions.current.get_vec().to_vec())
+        > 0.0
+    {
+        planned
+    } else {
+        directions.current
+    }
+}
+
+fn toggle_debug_overlay(key: Res<Input<KeyCode>>, mut overlay: ResMut<DebugOverlay>) {
+    if key.just_pressed(KeyCode::F3) {
+        overlay.0 = !overlay.0;
+    }
+}
+
+fn draw_debug_overlay(
+    mut gizmos: Gizmos,
+    query: Query<(&Location, &GhostDirections, &Ghost, &GhostMode), Without<Player>>,
+    player_query: Query<(&Location, &Direction), With<Player>>,
+    map: Res<Map>,
+    strategies: Res<GhostStrategies>,
+    levels: Res<Levels>,
+) {
+    let Ok((player_location, player_direction)) = player_query.get_single() else {
+        return;
+    };
+    let player_tile = player_location.get_tile(*player_direction);
+
+    let blinky_tile = query
+        .iter()
+        .find(|(_, _, ghost, _)| matches!(ghost, Ghost::Blinky))
+        .map(|(location, directions, _, _)| location.get_tile(directions.current))
+        .unwrap_or(Location::new(0.0, 0.0));
+
+    for (location, directions, ghost, mode) in query.iter() {
+        let color = match ghost {
+            Ghost::Blinky => Color::RED,
+            Ghost::Pinky => Color::PINK,
+            Ghost::Inky => Color::CYAN,
+            Ghost::Clyde => Color::ORANGE,
+        };
+
+        let direction = directions.planned.unwrap_or(directions.current);
+        let start = location.world_position();
+        let end = start + direction.get_vec().to_vec() * TILE_SIZE;
+        let back = -direction.get_vec().to_vec() * 3.0;
+        let side = back.perp();
+        gizmos.line_2d(start, end, color);
+        gizmos.line_2d(end, end + back + side, color);
+        gizmos.line_2d(end, end + back - side, color);
+
+        if let GhostMode::Home(_)
+        | GhostMode::HomeExit(_)
+        | GhostMode::DeadEnterHome
+        | GhostMode::DeadPause = *mode
+        {
+            continue;
+        }
+
+        let target = target_tile(
+            strategies.get(*ghost),
+            *mode,
+            &ChaseContext {
+                map: &map,
+                current_tile: location.get_tile(directions.current),
+                blinky_tile,
+                player_tile,
+                player_direction: *player_direction,
+                clyde_scatter_distance: levels.clyde_scatter_distance(),
+            },
+        );
+
+        if let Some(target) = target {
+            gizmos.rect_2d(target.world_position(), 0.0, Vec2::splat(TILE_SIZE), color);
+            gizmos.line_2d(start, target.world_position(), color.with_a(0.3));
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
 fn draw_ghosts(
     mut query: Query<
         (
@@ -774,10 +1276,23 @@ fn draw_ghosts(
         (&mut TextureAtlasSprite, &mut Visibility, &GhostSprite),
         Without<Ghost>,
     >,
+    mut letters_query: Query<
+        &mut Visibility,
+        (With<GhostLetter>, Without<Ghost>, Without<GhostSprite>),
+    >,
+    colorblind_mode: Res<ColorblindMode>,
     frite_timer: Res<FriteTimer>,
     levels: Res<Levels>,
     pause_timer: Res<CollisionPauseTimer>,
+    pause_state: Res<State<PauseState>>,
+    state: Res<State<AppState>>,
 ) {
+    // Ghosts stand still on their tile centers during READY!, which would
+    // otherwise flip their body sprite every frame.
+    let ghosts_moving = *state.get() == AppState::MainGame
+        && pause_timer.0.finished()
+        && *pause_state.get() == PauseState::Running;
+
     for (directions, location, mode, mut visibility, children) in query.iter_mut() {
         if let GhostMode::DeadPause = *mode {
             *visibility = Visibility::Hidden;
@@ -786,18 +1301,28 @@ fn draw_ghosts(
             *visibility = Visibility::Inherited;
         }
 
+        let is_frightened = matches!(
+            *mode,
+            GhostMode::Frightened | GhostMode::Home(true) | GhostMode::HomeExit(true)
+        );
+
         for child in children.iter() {
+            if let Ok(mut visibility) = letters_query.get_mut(*child) {
+                let is_dead = matches!(*mode, GhostMode::Dead | GhostMode::DeadEnterHome);
+                *visibility = if colorblind_mode.0 && !is_frightened && !is_dead {
+                    Visibility::Inherited
+                } else {
+                    Visibility::Hidden
+                };
+                continue;
+            }
+
             let (mut sprite, mut visibility, sprite_type) =
                 sprites_query.get_mut(*child).expect("Ghost without sprite");
 
-            let is_frightened = matches!(
-                *mode,
-                GhostMode::Frightened | GhostMode::Home(true) | GhostMode::HomeExit(true)
-            );
-
-            let change_variation = pause_timer.0.finished()
+            let change_variation = ghosts_moving
                 && match *mode {
-                    GhostMode::Home(_) | GhostMode::HomeExit(_) => location.y.fract() == 0.5,
+                    GhostMode::Home(_) | GhostMode::HomeExit(_) => location.y().fract() == 0.5,
                     _ => location.is_tile_center(),
                 };
             let variation = (sprite.index + if change_variation { 1 } else { 0 }) % 2;
@@ -819,7 +1344,8 @@ fn draw_ghosts(
                     } else {
                         *visibility = Visibility::Inherited;
 
-                        let rotation = (directions.current.rotation() * 4.0) as usize;
+                        let rotation =
+                            (eyes_direction(directions, location, mode).rotation() * 4.0) as usize;
                         sprite.index = rotation;
                     }
                 }
@@ -830,18 +1356,24 @@ fn draw_ghosts(
                         *visibility = Visibility::Inherited;
 
                         let remaining_time = frite_timer.0.remaining_secs();
-
-                        const FLASHING_TIMING: f32 = 1.0 / 4.0;
-                        let start_flashing_time: f32 =
-                            FLASHING_TIMING * levels.number_of_frite_flashes();
-                        let flashing = if remaining_time > start_flashing_time {
-                            false
+                        let start_flashing_time = frite_flash_window(&levels);
+                        let flashing = frite_flash_white(remaining_time, start_flashing_time);
+
+                        // Darkens as the fright runs out, then goes back to full
+                        // brightness for the flashes.
+                        const DARKEST_TINT: f32 = 0.5;
+                        let brightness = if remaining_time > start_flashing_time {
+                            DARKEST_TINT + (1.0 - DARKEST_TINT) * frite_timer.0.percent_left()
                         } else {
-                            let cycle = (remaining_time % FLASHING_TIMING) / FLASHING_TIMING;
-                            cycle > 0.5
+                            1.0
                         };
+                        sprite.color = Color::rgb(brightness, brightness, brightness);
 
-                        sprite.index = variation + if flashing { 2 } else { 0 };
+                        // The sheet has blue frames followed by white ones. In colorblind
+                        // mode the ghost is white and flashes blue, so it never looks
+                        // like Inky.
+                        let white = flashing != colorblind_mode.0;
+                        sprite.index = variation + if white { 2 } else { 0 };
                     }
                 }
             }
@@ -849,11 +1381,29 @@ fn draw_ghosts(
     }
 }
 
+/// Time each flash of the frightened ghosts takes, white and then blue. Five
+/// flashes take the last 2.5 seconds of a fright, like the arcade.
+const FLASH_DURATION: f32 = 1.0 / 2.0;
+
+/// How long before the end of a fright the ghosts start flashing. It fits the
+/// level's number of flashes, or as many whole ones as the fright lasts.
+fn frite_flash_window(levels: &Levels) -> f32 {
+    let fitting_flashes = (levels.frite_duration() as f32 / FLASH_DURATION).floor();
+    FLASH_DURATION * levels.number_of_frite_flashes().min(fitting_flashes)
+}
+
+/// Whether a frightened ghost shows its flash color. Every flash starts with
+/// it, so the last one ends right as the fright does.
+fn frite_flash_white(remaining_secs: f32, flash_window: f32) -> bool {
+    remaining_secs < flash_window && remaining_secs % FLASH_DURATION >= FLASH_DURATION / 2.0
+}
+
 fn update_global_ghost_mode(
     mut global_ghost_mode: ResMut<GhostMode>,
     mut mode: ResMut<GlobalGhostModeTimer>,
     time: Res<Time>,
     levels: Res<Levels>,
+    mut rng: ResMut<GameRng>,
 ) {
     if !mode.timer.tick(time.delta()).just_finished() {
         return;
@@ -866,46 +1416,84 @@ fn update_global_ghost_mode(
     };
 
     mode.duration_index += 1;
-    if let Some(duration) = levels.ghost_switch_global_mode(mode.duration_index) {
-        mode.timer.set_duration(Duration::from_secs_f32(duration));
+    if let Some(duration) = global_mode_duration(&levels, mode.duration_index, &mut rng) {
+        mode.timer.set_duration(duration);
         mode.timer.reset();
     }
 }
 
+/// Where the player and the ghosts were on the previous collision check.
+#[derive(Default)]
+struct LastLocations {
+    player: Option<Location>,
+    ghosts: Vec<(Ghost, Location)>,
+}
+
+/// Whether two characters on the same row or column swapped sides since the
+/// previous tick. Head on they can step past each other within one tick and
+/// never be close enough to collide.
+fn passed_through(previous: (Location, Location), current: (Location, Location)) -> bool {
+    let before = (previous.0 - previous.1).to_vec();
+    let after = (current.0 - current.1).to_vec();
+    if before.length_squared() >= 1.0 || after.length_squared() >= 1.0 {
+        return false;
+    }
+
+    let crossed_x = before.y == 0.0 && after.y == 0.0 && before.x.signum() != after.x.signum();
+    let crossed_y = before.x == 0.0 && after.x == 0.0 && before.y.signum() != after.y.signum();
+    crossed_x || crossed_y
+}
+
+#[allow(clippy::too_many_arguments)]
 fn collision_detection(
     query: Query<(&Location, &Ghost, &GhostMode)>,
     player_query: Query<&Location, With<Player>>,
     mut ghost_eaten_events: EventWriter<GhostEaten>,
+    mut frite_chain: ResMut<FriteChain>,
     asset_server: Res<AssetServer>,
     audio: Res<Audio>,
     mut next_state: ResMut<NextState<AppState>>,
     mut next_dead_state: ResMut<NextState<DeadState>>,
+    mut last_locations: Local<LastLocations>,
+    #[cfg(debug_assertions)] no_clip: Res<NoClip>,
 ) {
     let player_location = player_query.single();
-    let number_of_fritened_ghosts = query
-        .iter()
-        .filter(|(_, _, mode)| {
-            matches!(
-                mode,
-                GhostMode::Frightened | GhostMode::Home(true) | GhostMode::HomeExit(true)
-            )
-        })
-        .count();
+    let last_player_location = last_locations.player.replace(*player_location);
 
     for (location, ghost, mode) in query.iter() {
-        let location_dif = *location - *player_location;
-        let distance_squared = location_dif.length_squared();
+        let last_location = match last_locations
+            .ghosts
+            .iter_mut()
+            .find(|(last_ghost, _)| last_ghost == ghost)
+        {
+            Some((_, last_location)) => Some(std::mem::replace(last_location, *location)),
+            None => {
+                last_locations.ghosts.push((*ghost, *location));
+                None
+            }
+        };
+        let passed_player = last_location.zip(last_player_location).is_some_and(
+            |(last_location, last_player_location)| {
+                passed_through(
+                    (last_location, last_player_location),
+                    (*location, *player_location),
+                )
+            },
+        );
 
-        if distance_squared < 0.5 * 0.5 {
+        if passed_player || location.distance_squared(*player_location) < 0.5 * 0.5 {
             match mode {
                 GhostMode::Frightened => {
                     ghost_eaten_events.send(GhostEaten {
                         ghost: *ghost,
-                        eaten_ghosts: 4 - number_of_fritened_ghosts,
+                        eaten_ghosts: frite_chain.0,
                     });
+                    frite_chain.0 += 1;
 
                     audio.play(asset_server.load("sounds/eat_ghost.wav"));
                 }
+                #[cfg(debug_assertions)]
+                GhostMode::Scatter | GhostMode::Chase if no_clip.0 => (),
                 GhostMode::Scatter | GhostMode::Chase => {
                     next_state.set(AppState::PlayerDied);
                     next_dead_state.set(DeadState::Pause);
@@ -917,7 +1505,7 @@ fn collision_detection(
 }
 
 fn despawn_timer_check(timer: Res<StateTimer>) -> bool {
-    timer.0.elapsed_secs() >= 3.0
+    timer.0.elapsed_secs() >= LEVEL_COMPLETE_JINGLE_SECS
 }
 
 fn despawn_ghosts(mut commands: Commands, query: Query<Entity, With<Ghost>>) {
@@ -929,6 +1517,16 @@ fn despawn_ghosts(mut commands: Commands, query: Query<Entity, With<Ghost>>) {
 #[derive(Component)]
 struct GhostEatenText;
 
+/// Floats an entity up from where it was spawned while fading it out, over the
+/// collision pause it's shown for.
+#[derive(Component)]
+struct RiseAndFade {
+    from: Location,
+}
+
+/// How far the points of an eaten ghost rise before they're gone.
+const GHOST_EATEN_TEXT_RISE: f32 = 0.5;
+
 fn ghost_eaten_system(
     mut commands: Commands,
     ghost_query: Query<(&Ghost, &Location), Without<GhostEatenText>>,
@@ -973,6 +1571,372 @@ fn ghost_eaten_system(
             },
             text_location,
             GhostEatenText,
+            RiseAndFade {
+                from: text_location,
+            },
         ));
     }
 }
+
+fn rise_and_fade(
+    mut query: Query<(&RiseAndFade, &mut Location, &mut Sprite)>,
+    pause_timer: Res<CollisionPauseTimer>,
+) {
+    let progress = pause_timer.0.percent();
+    for (rise_and_fade, mut location, mut sprite) in query.iter_mut() {
+        let top = rise_and_fade.from + Location::new(0.0, GHOST_EATEN_TEXT_RISE);
+        let risen = rise_and_fade.from.lerp(top, progress);
+        if *location != risen {
+            *location = risen;
+        }
+        sprite.color.set_a(1.0 - progress);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The way a frightened ghost turns at every open tile of the stock maze,
+    /// coming from each direction.
+    fn frightened_turns(rng: &mut GameRng) -> Vec<Option<Direction>> {
+        let map = Map::parse(include_str!("map")).unwrap();
+
+        let mut turns = vec![];
+        for x in 0..map.width() {
+            for y in 0..map.height() {
+                let tile = Location::new(x as f32, y as f32);
+                if map.is_blocked(tile) {
+                    continue;
+                }
+                for direction in Direction::all() {
+                    turns.push(ghost_path_finder(tile, None, &map, direction, false, rng));
+                }
+            }
+        }
+        turns
+    }
+
+    #[test]
+    fn same_seed_frightens_the_same_way() {
+        let turns = frightened_turns(&mut GameRng::with_seed(7));
+
+        assert_eq!(frightened_turns(&mut GameRng::with_seed(7)), turns);
+        assert_ne!(frightened_turns(&mut GameRng::with_seed(8)), turns);
+    }
+
+    #[test]
+    fn captured_ghosts_are_snapped_to_the_tile() {
+        let mut mode_timer = GlobalGhostModeTimer {
+            timer: Timer::from_seconds(20.0, TimerMode::Once),
+            duration_index: 2,
+        };
+        mode_timer.timer.tick(Duration::from_secs(5));
+
+        let between = Location::new(5.0, 7.0).lerp(Location::new(6.0, 7.0), 0.75);
+        let left = GhostDirections::new(Direction::Left);
+        let ghosts = [
+            (Ghost::Blinky, GhostMode::Frightened, between, left),
+            (Ghost::Pinky, GhostMode::Dead, between, left),
+            (Ghost::Inky, GhostMode::Home(true), between, left),
+        ];
+
+        let state = GhostState::capture(
+            GhostMode::Chase,
+            &mode_timer,
+            ghosts
+                .iter()
+                .map(|(ghost, mode, location, directions)| (ghost, mode, location, directions)),
+        );
+
+        assert_eq!(state.mode, GhostMode::Chase);
+        assert_eq!(state.mode_period, 2);
+        assert_eq!(state.mode_secs_left, Some(15.0));
+        assert_eq!(
+            state.ghosts,
+            vec![
+                (
+                    Ghost::Blinky,
+                    GhostPlace::Board {
+                        tile: Location::new(6.0, 7.0),
+                        direction: Direction::Left,
+                    },
+                ),
+                (Ghost::Pinky, GhostPlace::Home { leaving: true }),
+                (Ghost::Inky, GhostPlace::Home { leaving: false }),
+            ]
+        );
+    }
+
+    /// The times the ghosts turn white over a whole fright, stepping through it
+    /// at 60 frames per second.
+    fn count_flashes(levels: &Levels) -> usize {
+        let flash_window = frite_flash_window(levels);
+        let frames = levels.frite_duration() * 60;
+
+        let mut flashes = 0;
+        let mut was_white = false;
+        for frame in 0..=frames {
+            let remaining_secs = (frames - frame) as f32 / 60.0;
+            let white = frite_flash_white(remaining_secs, flash_window);
+            if white && !was_white {
+                flashes += 1;
+            }
+            was_white = white;
+        }
+
+        flashes
+    }
+
+    /// Eats pellets one at a time until `ghost` may leave, returning how many
+    /// it took. `pellets_eaten` is kept as the level's total.
+    fn pellets_until_released(
+        counter: &mut GhostPelletEatenCounter,
+        ghost: Ghost,
+        pellets_eaten: &mut usize,
+        levels: &Levels,
+    ) -> usize {
+        let mut eaten = 0;
+        let mut released = counter.limit_reached(ghost, 0, *pellets_eaten, levels);
+        while !released {
+            assert!(eaten < 1000, "{ghost:?} is never released");
+            *pellets_eaten += 1;
+            eaten += 1;
+            released = counter.limit_reached(ghost, 1, *pellets_eaten, levels);
+        }
+        eaten
+    }
+
+    #[test]
+    fn ghosts_leave_at_their_own_limits() {
+        let mut levels = Levels::default();
+        for level in [1, 2, 3] {
+            levels.select(level);
+            let mut counter = GhostPelletEatenCounter::default();
+            let mut pellets_eaten = 0;
+
+            for ghost in [Ghost::Pinky, Ghost::Inky, Ghost::Clyde] {
+                assert_eq!(
+                    pellets_until_released(&mut counter, ghost, &mut pellets_eaten, &levels),
+                    levels.home_exit_dots(ghost),
+                    "{ghost:?} on level {level}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn losing_a_life_switches_to_the_shared_count() {
+        let levels = Levels::default();
+        let mut counter = GhostPelletEatenCounter::default();
+        let mut pellets_eaten = 100;
+        counter.global_from = Some(pellets_eaten);
+
+        for (ghost, total) in [(Ghost::Pinky, 7), (Ghost::Inky, 17), (Ghost::Clyde, 32)] {
+            pellets_until_released(&mut counter, ghost, &mut pellets_eaten, &levels);
+            assert_eq!(pellets_eaten - 100, total, "{ghost:?}");
+        }
+    }
+
+    #[test]
+    fn own_counts_return_once_clyde_is_released() {
+        let levels = Levels::default();
+        let mut counter = GhostPelletEatenCounter::default();
+        counter.personal.insert(Ghost::Clyde, 10);
+        let mut pellets_eaten = 100;
+        counter.global_from = Some(pellets_eaten);
+
+        pellets_until_released(&mut counter, Ghost::Clyde, &mut pellets_eaten, &levels);
+        assert!(!counter.is_global());
+
+        // Clyde's own count carries on from where the life was lost.
+        counter.limit_reached(Ghost::Clyde, 5, pellets_eaten + 5, &levels);
+        assert_eq!(counter.personal[&Ghost::Clyde], 15);
+    }
+
+    /// Runs `detect_power_pellet` on its own, on the given level.
+    fn power_pellet_app(level: usize) -> App {
+        let mut levels = Levels::default();
+        levels.select(level);
+
+        let mut app = App::new();
+        app.add_event::<PelletEaten>()
+            .add_event::<GhostModeChanged>()
+            .insert_resource(FriteTimer(Timer::from_seconds(0.0, TimerMode::Once)))
+            .insert_resource(FriteChain::default())
+            .insert_resource(levels)
+            .add_systems(Update, detect_power_pellet);
+        app
+    }
+
+    fn eat_power_pellet(app: &mut App) {
+        app.world
+            .resource_mut::<Events<PelletEaten>>()
+            .send(PelletEaten { power: true });
+        app.update();
+    }
+
+    fn spawn_ghost(app: &mut App, ghost: Ghost, mode: GhostMode) -> Entity {
+        app.world
+            .spawn((ghost, mode, GhostDirections::new(Direction::Left)))
+            .id()
+    }
+
+    #[test]
+    fn second_power_pellet_extends_the_fright() {
+        let mut app = power_pellet_app(1);
+        let blinky = spawn_ghost(&mut app, Ghost::Blinky, GhostMode::Chase);
+        let pinky = spawn_ghost(&mut app, Ghost::Pinky, GhostMode::Scatter);
+
+        eat_power_pellet(&mut app);
+        for ghost in [blinky, pinky] {
+            assert_eq!(
+                app.world.get::<GhostMode>(ghost),
+                Some(&GhostMode::Frightened)
+            );
+            let mut directions = app.world.get_mut::<GhostDirections>(ghost).unwrap();
+            assert_eq!(directions.planned, Some(Direction::Right));
+            directions.advance();
+        }
+
+        app.world
+            .resource_mut::<FriteTimer>()
+            .0
+            .tick(Duration::from_secs(4));
+        app.world.resource_mut::<FriteChain>().0 = 1;
+        *app.world.get_mut::<GhostMode>(pinky).unwrap() = GhostMode::Dead;
+
+        eat_power_pellet(&mut app);
+
+        // Still frightened without turning around again, and the eaten ghost
+        // stays on its way home.
+        assert_eq!(
+            app.world.get::<GhostMode>(blinky),
+            Some(&GhostMode::Frightened)
+        );
+        assert_eq!(
+            app.world.get::<GhostDirections>(blinky).unwrap().planned,
+            None
+        );
+        assert_eq!(app.world.get::<GhostMode>(pinky), Some(&GhostMode::Dead));
+
+        let frite_timer = &app.world.resource::<FriteTimer>().0;
+        assert_eq!(frite_timer.elapsed(), Duration::ZERO);
+        assert_eq!(frite_timer.duration(), Duration::from_secs(6));
+        assert_eq!(app.world.resource::<FriteChain>().0, 0);
+    }
+
+    #[test]
+    fn swapping_sides_head_on_is_a_collision() {
+        let player = Location::new(10.0, 16.0);
+        let ghost = Location::new(10.125, 16.0);
+
+        // Moving towards each other they swap sides within one tick.
+        let player_after = player + Location::new(0.25, 0.0);
+        let ghost_after = ghost - Location::new(0.25, 0.0);
+        assert!(passed_through((ghost, player), (ghost_after, player_after)));
+        // Or one of them does while the other stands still.
+        assert!(passed_through((ghost, player), (ghost_after, player)));
+
+        // The same in a column.
+        let above = Location::new(6.0, 10.125);
+        let below = Location::new(6.0, 9.875);
+        assert!(passed_through((above, below), (below, above)));
+
+        // Following each other, moving apart, or on different rows isn't.
+        let ahead = ghost + Location::new(0.125, 0.0);
+        assert!(!passed_through((ghost, player), (ahead, ghost)));
+        assert!(!passed_through((ghost, player), (ahead, player)));
+        let aside = Location::new(10.0, 15.875);
+        assert!(!passed_through((ghost, aside), (aside, ghost)));
+    }
+
+    #[test]
+    fn power_pellet_without_fright_leaves_the_ghosts_alone() {
+        let mut app = power_pellet_app(17);
+        assert_eq!(app.world.resource::<Levels>().frite_duration(), 0);
+        let blinky = spawn_ghost(&mut app, Ghost::Blinky, GhostMode::Chase);
+        app.world.resource_mut::<FriteChain>().0 = 2;
+
+        eat_power_pellet(&mut app);
+
+        assert_eq!(app.world.get::<GhostMode>(blinky), Some(&GhostMode::Chase));
+        assert_eq!(
+            app.world.get::<GhostDirections>(blinky).unwrap().planned,
+            Some(Direction::Left)
+        );
+        assert_eq!(app.world.resource::<FriteChain>().0, 2);
+        assert!(app.world.resource::<Events<GhostModeChanged>>().is_empty());
+    }
+
+    #[test]
+    fn equidistant_turns_prefer_up_left_down_right() {
+        let map = Map::parse(include_str!("map")).unwrap();
+        let mut rng = GameRng::with_seed(0);
+        let mut turn = |junction: (f32, f32), current: Direction, target: (f32, f32)| {
+            ghost_path_finder(
+                Location::new(junction.0, junction.1),
+                Some(Location::new(target.0, target.1)),
+                &map,
+                current,
+                false,
+                &mut rng,
+            )
+        };
+
+        // Each target is as close to both of the turns it's between.
+        assert_eq!(
+            turn((6.0, 7.0), Direction::Up, (7.0, 8.0)),
+            Some(Direction::Up)
+        );
+        assert_eq!(
+            turn((6.0, 7.0), Direction::Left, (0.0, 7.0)),
+            Some(Direction::Up)
+        );
+        assert_eq!(
+            turn((6.0, 7.0), Direction::Down, (7.0, 6.0)),
+            Some(Direction::Down)
+        );
+        assert_eq!(
+            turn((3.0, 4.0), Direction::Left, (2.0, 5.0)),
+            Some(Direction::Up)
+        );
+        assert_eq!(
+            turn((3.0, 4.0), Direction::Up, (3.0, 3.0)),
+            Some(Direction::Left)
+        );
+    }
+
+    #[test]
+    fn blinky_speeds_up_at_the_elroy_thresholds() {
+        let levels = Levels::default();
+        let speed = |ghost, remaining_pellets| normal_speed(ghost, remaining_pellets, &levels);
+
+        assert_eq!(speed(Ghost::Blinky, 244), 0.75);
+        assert_eq!(speed(Ghost::Blinky, 21), 0.75);
+        assert_eq!(speed(Ghost::Blinky, 20), 0.8);
+        assert_eq!(speed(Ghost::Blinky, 11), 0.8);
+        assert_eq!(speed(Ghost::Blinky, 10), 0.85);
+        assert_eq!(speed(Ghost::Blinky, 1), 0.85);
+        for ghost in [Ghost::Pinky, Ghost::Inky, Ghost::Clyde] {
+            assert_eq!(speed(ghost, 5), 0.75);
+        }
+    }
+
+    #[test]
+    fn first_level_flashes_five_times_over_the_last_seconds() {
+        let levels = Levels::default();
+
+        assert_eq!(frite_flash_window(&levels), 2.5);
+        assert_eq!(count_flashes(&levels), 5);
+    }
+
+    #[test]
+    fn short_frights_only_flash_whole_times() {
+        let mut levels = Levels::default();
+        levels.select(5);
+
+        assert_eq!(levels.frite_duration(), 2);
+        assert_eq!(count_flashes(&levels), 4);
+    }
+}
@@ -0,0 +1,389 @@
+use std::time::Duration;
+
+use bevy::{asset::AssetPlugin, input::InputPlugin, prelude::*, time::TimeUpdateStrategy};
+use bevy_kira_audio::AudioPlugin;
+
+use crate::{
+    add_game,
+    bot::BotInterface,
+    common::{
+        app_state::{AppState, DeadState},
+        levels::Levels,
+        sets::Rendering,
+    },
+    ghosts::Ghost,
+    pellets::TotalPellets,
+    player::Player,
+    points::Points,
+    services::map::Location,
+    MAX_MOVE_SPEED,
+};
+
+const HEADLESS_ARG: &str = "--headless";
+const EXPECT_SCORE_ARG: &str = "--expect-score";
+const EXPECT_PLAYER_ARG: &str = "--expect-player";
+
+/// Reads the `--headless <ticks>` argument, which runs that many game loop
+/// ticks without a window and prints where the game ended up. Combined with
+/// `--seed` or `--replay` this checks game logic from scripts and CI, and with
+/// `--bot` it shows how far the built in bot gets.
+pub fn ticks_from_args() -> Option<u64> {
+    let value = std::env::args()
+        .skip_while(|arg| arg != HEADLESS_ARG)
+        .nth(1)?;
+
+    value
+        .parse()
+        .map_err(|_| eprintln!("Ignoring invalid {} value {:?}", HEADLESS_ARG, value))
+        .ok()
+}
+
+/// Where a headless run has to end up, from `--expect-score <score>` and
+/// `--expect-player <x>,<y>`, the latter being the tile the player is in.
+/// Together with `--seed` and `--replay` this pins a scripted game frame by
+/// frame, so a change to movement, eating or the ghosts that alters it fails
+/// the run.
+#[derive(Debug, PartialEq)]
+struct Expectations {
+    score: Option<u32>,
+    player: Option<Location>,
+}
+
+impl Expectations {
+    fn from_args() -> Result<Self, String> {
+        Self::parse(&std::env::args().collect::<Vec<_>>())
+    }
+
+    fn parse(args: &[String]) -> Result<Self, String> {
+        let value_of = |arg: &str| args.iter().skip_while(|other| *other != arg).nth(1);
+
+        let score = value_of(EXPECT_SCORE_ARG)
+            .map(|value| {
+                value
+                    .parse()
+                    .map_err(|_| format!("Invalid {} value {:?}", EXPECT_SCORE_ARG, value))
+            })
+            .transpose()?;
+
+        let player = value_of(EXPECT_PLAYER_ARG)
+            .map(|value| {
+                parse_tile(value).map_err(|error| format!("{}: {}", EXPECT_PLAYER_ARG, error))
+            })
+            .transpose()?;
+
+        Ok(Self { score, player })
+    }
+
+    /// Prints every expectation that wasn't met, returning whether all were.
+    fn check(&self, world: &mut World) -> bool {
+        let mut met = true;
+
+        let score = world.resource::<Points>().score;
+        if let Some(expected) = self.score.filter(|expected| *expected != score) {
+            eprintln!("Expected a score of {}, got {}", expected, score);
+            met = false;
+        }
+
+        let mut player_query = world.query_filtered::<&Location, With<Player>>();
+        let player = player_query.iter(world).next().copied();
+        let in_tile = |expected: &Location| player.is_some_and(|player| player.tile_eq(*expected));
+        if let Some(expected) = self.player.filter(|expected| !in_tile(expected)) {
+            let player = player.map_or("nowhere".to_string(), |player| {
+                format!("({}, {})", player.x(), player.y())
+            });
+            eprintln!(
+                "Expected the player in tile ({}, {}), got {}",
+                expected.x(),
+                expected.y(),
+                player
+            );
+            met = false;
+        }
+
+        met
+    }
+}
+
+/// Parses a tile given as `<x>,<y>`. Only whole numbers name a tile, anything
+/// else is refused rather than guessed at.
+fn parse_tile(value: &str) -> Result<Location, String> {
+    let (x, y) = value
+        .split_once(',')
+        .ok_or_else(|| format!("Expected <x>,<y>, got {:?}", value))?;
+    let coordinate = |coordinate: &str| {
+        coordinate
+            .trim()
+            .parse::<i16>()
+            .map_err(|_| format!("{:?} is not a tile coordinate", coordinate))
+    };
+
+    Ok(Location::new(coordinate(x)?.into(), coordinate(y)?.into()))
+}
+
+/// Plays a game with no window, rendering or audio output, advancing time by
+/// exactly one game loop tick per update. Stops early once the game is over,
+/// and exits with an error if the expected end wasn't reached.
+pub fn run(ticks: u64) {
+    let expectations = match Expectations::from_args() {
+        Ok(expectations) => expectations,
+        Err(error) => {
+            eprintln!("{}", error);
+            std::process::exit(2);
+        }
+    };
+
+    let mut app = headless_app();
+    app.finish();
+    app.cleanup();
+
+    for _ in 0..ticks {
+        app.update();
+
+        if *app.world.resource::<State<DeadState>>().get() == DeadState::GameOver {
+            break;
+        }
+    }
+
+    report(&mut app.world);
+
+    if !expectations.check(&mut app.world) {
+        std::process::exit(1);
+    }
+}
+
+/// The whole game without its window, started straight into the first level.
+/// Left unfinished, so more plugins can still be added: call `App::finish` and
+/// `App::cleanup` before updating it. Each update is one game loop tick.
+pub fn headless_app() -> App {
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        AssetPlugin::default(),
+        InputPlugin,
+        AudioPlugin,
+    ))
+    .init_asset::<Image>()
+    .init_asset::<TextureAtlas>()
+    .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f64(
+        1.0 / MAX_MOVE_SPEED,
+    )))
+    .configure_sets(Update, Rendering.run_if(|| false))
+    .add_systems(Update, start_game.run_if(in_state(AppState::MainMenu)));
+
+    add_game(&mut app);
+    app
+}
+
+fn start_game(mut next_state: ResMut<NextState<AppState>>) {
+    next_state.set(AppState::LevelStart);
+}
+
+fn report(world: &mut World) {
+    println!("State: {:?}", world.resource::<State<AppState>>().get());
+    println!("Level: {}", world.resource::<Levels>().current());
+    println!("Score: {}", world.resource::<Points>().score);
+    println!("Total pellets: {}", world.resource::<TotalPellets>().0);
+
+    let mut player_query = world.query_filtered::<&Location, With<Player>>();
+    for location in player_query.iter(world) {
+        println!("Player: ({}, {})", location.x(), location.y());
+    }
+
+    let mut ghost_query = world.query::<(&Ghost, &Location)>();
+    for (ghost, location) in ghost_query.iter(world) {
+        println!("{:?}: ({}, {})", ghost, location.x(), location.y());
+    }
+
+    if let Some(snapshot) = world
+        .get_resource::<BotInterface>()
+        .and_then(|bot| bot.snapshot())
+    {
+        let power_pellets = snapshot
+            .pellets
+            .iter()
+            .filter(|pellet| pellet.power)
+            .count();
+        println!(
+            "Bot snapshot on tick {}: {} pellets left, {} of them power pellets",
+            snapshot.tick,
+            snapshot.pellets.len(),
+            power_pellets
+        );
+        for ghost in &snapshot.ghosts {
+            println!("{:?} mode: {:?}", ghost.ghost, ghost.mode);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+    use crate::{
+        background_sound::{siren, BackgroundSoundPlugin, BackgroundSounds},
+        bot::drive_greedy_bot,
+        common::sets::GameLoop,
+        ghosts::{Ghost, GhostMode, GhostPelletEatenCounter, GhostPlace, GhostState, SavedGhosts},
+        pellets::PelletsEaten,
+        points::BonusSymbolsSpawned,
+        services::{
+            map::{Direction, Location},
+            rng::GameRng,
+        },
+    };
+
+    /// The ghost house counter, the siren and the bonus symbols all follow
+    /// `PelletsEaten`, checked while the built in bot plays most of a level.
+    #[test]
+    fn pellet_consumers_agree_on_pellets_eaten() {
+        let mut app = headless_app();
+        app.insert_resource(GameRng::with_seed(1))
+            .insert_resource(BotInterface::default())
+            .add_plugins(BackgroundSoundPlugin)
+            .add_systems(FixedUpdate, drive_greedy_bot.before(GameLoop::Planning));
+        app.finish();
+        app.cleanup();
+
+        let mut last_pellets_eaten = None;
+        let mut most_pellets_checked = 0;
+        let mut sirens_checked = HashSet::new();
+        for _ in 0..3000 {
+            app.update();
+
+            let world = &app.world;
+            if *world.resource::<State<AppState>>().get() != AppState::MainGame {
+                last_pellets_eaten = None;
+                continue;
+            }
+
+            // They read it at different points of a tick, so they're compared
+            // once a whole tick went by without eating.
+            let pellets_eaten = world.resource::<PelletsEaten>().0;
+            if last_pellets_eaten.replace(pellets_eaten) != Some(pellets_eaten) {
+                continue;
+            }
+
+            assert_eq!(
+                world.resource::<GhostPelletEatenCounter>().counted_to,
+                pellets_eaten
+            );
+
+            let thresholds_crossed = world
+                .resource::<Levels>()
+                .bonus_symbol_dots()
+                .iter()
+                .filter(|dots| pellets_eaten >= **dots)
+                .count();
+            assert_eq!(
+                world.resource::<BonusSymbolsSpawned>().0,
+                thresholds_crossed,
+                "{pellets_eaten} pellets eaten"
+            );
+
+            if let Some(playing) = world.resource::<BackgroundSounds>().playing_siren() {
+                assert_eq!(
+                    playing,
+                    siren(pellets_eaten),
+                    "{pellets_eaten} pellets eaten"
+                );
+                sirens_checked.insert(playing);
+            }
+
+            most_pellets_checked = most_pellets_checked.max(pellets_eaten);
+        }
+
+        // Past both bonus symbols and into the faster sirens.
+        assert!(most_pellets_checked >= 170, "{most_pellets_checked}");
+        assert!(sirens_checked.len() >= 2, "{sirens_checked:?}");
+    }
+
+    fn parse(args: &[&str]) -> Result<Expectations, String> {
+        Expectations::parse(&args.iter().map(|arg| arg.to_string()).collect::<Vec<_>>())
+    }
+
+    #[test]
+    fn expectations_are_parsed() {
+        assert_eq!(
+            parse(&[
+                "pacman",
+                EXPECT_SCORE_ARG,
+                "190",
+                EXPECT_PLAYER_ARG,
+                "6, 19"
+            ]),
+            Ok(Expectations {
+                score: Some(190),
+                player: Some(Location::new(6.0, 19.0)),
+            })
+        );
+        assert_eq!(
+            parse(&["pacman"]),
+            Ok(Expectations {
+                score: None,
+                player: None,
+            })
+        );
+    }
+
+    #[test]
+    fn saved_ghosts_are_put_back() {
+        let blinky_tile = Location::new(6.0, 19.0);
+        let mut app = headless_app();
+        app.insert_resource(SavedGhosts(Some(GhostState {
+            mode: GhostMode::Chase,
+            mode_period: 7,
+            mode_secs_left: None,
+            ghosts: vec![
+                (
+                    Ghost::Blinky,
+                    GhostPlace::Board {
+                        tile: blinky_tile,
+                        direction: Direction::Down,
+                    },
+                ),
+                (Ghost::Pinky, GhostPlace::Home { leaving: false }),
+            ],
+        })));
+        app.finish();
+        app.cleanup();
+
+        while *app.world.resource::<State<AppState>>().get() != AppState::LevelStart {
+            app.update();
+        }
+
+        assert!(app.world.resource::<SavedGhosts>().0.is_none());
+        let mut ghosts = app.world.query::<(&Ghost, &GhostMode, &Location)>();
+        for (ghost, mode, location) in ghosts.iter(&app.world) {
+            match ghost {
+                Ghost::Blinky => {
+                    assert_eq!(*mode, GhostMode::Chase);
+                    assert_eq!(*location, blinky_tile);
+                }
+                Ghost::Pinky => assert_eq!(*mode, GhostMode::Home(false)),
+                // Not saved, so as at the start of a level.
+                Ghost::Inky | Ghost::Clyde => assert_eq!(*mode, GhostMode::Home(false)),
+            }
+        }
+
+        // The last period goes on for the rest of the level.
+        while *app.world.resource::<State<AppState>>().get() != AppState::MainGame {
+            app.update();
+        }
+        for _ in 0..60 {
+            app.update();
+            assert_eq!(*app.world.resource::<GhostMode>(), GhostMode::Chase);
+        }
+    }
+
+    #[test]
+    fn off_grid_players_are_refused() {
+        for value in ["6.3,19", "6.5,19", "6", "6,", "x,19", "NaN,19", "100000,19"] {
+            assert!(
+                parse(&["pacman", EXPECT_PLAYER_ARG, value]).is_err(),
+                "{value:?}"
+            );
+        }
+        assert!(parse(&["pacman", EXPECT_SCORE_ARG, "-1"]).is_err());
+    }
+}
@@ -0,0 +1,239 @@
+use std::f32::consts::TAU;
+
+use bevy::prelude::*;
+
+use crate::{
+    common::{
+        app_state::{AppState, PauseState, StateTimer},
+        layers::Layers,
+    },
+    map_render::NoMapWrap,
+    services::{
+        input::GamepadInput,
+        map::{Direction, Location},
+    },
+};
+
+/// The row the cutscene plays on, the middle of the screen.
+const ROW: f32 = 16.0;
+
+/// A character of the cutscene, crossing the screen in a straight line.
+#[derive(Component)]
+struct Actor {
+    kind: ActorKind,
+    from: Location,
+    to: Location,
+    /// Seconds into the intermission the actor enters and leaves the screen.
+    start: f32,
+    end: f32,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum ActorKind {
+    PacMan,
+    Blinky,
+    FrightenedBlinky,
+}
+
+impl Actor {
+    fn location(&self, secs: f32) -> Option<Location> {
+        if secs < self.start || secs > self.end {
+            return None;
+        }
+        let t = (secs - self.start) / (self.end - self.start);
+        Some(self.from.lerp(self.to, t))
+    }
+
+    fn direction(&self) -> Direction {
+        if self.to.x() < self.from.x() {
+            Direction::Left
+        } else {
+            Direction::Right
+        }
+    }
+}
+
+/// The arcade's first intermission, played after the levels in
+/// `Levels::intermission_follows`: Blinky chases Pac-Man off the left of the
+/// screen, then a giant Pac-Man chases a frightened Blinky back across. Any key
+/// but Escape, which pauses, skips it.
+pub struct IntermissionPlugin;
+
+impl Plugin for IntermissionPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(AppState::Intermission), spawn_actors);
+        app.add_systems(OnExit(AppState::Intermission), despawn_actors);
+        app.add_systems(
+            Update,
+            (skip_intermission, move_actors)
+                .run_if(in_state(AppState::Intermission).and_then(in_state(PauseState::Running))),
+        );
+    }
+}
+
+fn spawn_actors(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut texture_atlases: ResMut<Assets<TextureAtlas>>,
+) {
+    let mut atlas = |path: &'static str, size: f32, frames: usize| {
+        let texture_handle = asset_server.load(path);
+        let texture_atlas =
+            TextureAtlas::from_grid(texture_handle, Vec2::splat(size), frames, 1, None, None);
+        texture_atlases.add(texture_atlas)
+    };
+    let pacman = atlas("pacman.png", 15.0, 3);
+    let blinky_body = atlas("blinky_body.png", 16.0, 2);
+    let blinky_eyes = atlas("ghost_eyes.png", 16.0, 4);
+    let frightened = atlas("ghosts_frite.png", 16.0, 4);
+
+    let actors = [
+        (
+            Actor {
+                kind: ActorKind::PacMan,
+                from: Location::new(29.0, ROW),
+                to: Location::new(-5.0, ROW),
+                start: 0.0,
+                end: 3.0,
+            },
+            1.0,
+        ),
+        (
+            Actor {
+                kind: ActorKind::Blinky,
+                from: Location::new(31.5, ROW),
+                to: Location::new(-2.5, ROW),
+                start: 0.0,
+                end: 3.0,
+            },
+            1.0,
+        ),
+        (
+            Actor {
+                kind: ActorKind::FrightenedBlinky,
+                from: Location::new(-2.5, ROW),
+                to: Location::new(39.5, ROW),
+                start: 3.5,
+                end: 7.0,
+            },
+            1.0,
+        ),
+        (
+            Actor {
+                kind: ActorKind::PacMan,
+                from: Location::new(-9.0, ROW + 1.75),
+                to: Location::new(33.0, ROW + 1.75),
+                start: 3.5,
+                end: 7.0,
+            },
+            3.0,
+        ),
+    ];
+
+    for (actor, scale) in actors {
+        let kind = actor.kind;
+        let mut entity = commands.spawn((
+            actor,
+            NoMapWrap,
+            Location::new(-10.0, ROW),
+            SpatialBundle {
+                transform: Transform::from_xyz(0.0, 0.0, Layers::HUD.as_f32())
+                    .with_scale(Vec3::splat(scale)),
+                visibility: Visibility::Hidden,
+                ..default()
+            },
+        ));
+
+        entity.with_children(|parent| {
+            let mut sprite = |texture_atlas: Handle<TextureAtlas>, z: f32| {
+                parent.spawn(SpriteSheetBundle {
+                    texture_atlas,
+                    transform: Transform::from_xyz(0.0, 0.0, z),
+                    ..default()
+                });
+            };
+            match kind {
+                ActorKind::PacMan => sprite(pacman.clone(), 0.0),
+                ActorKind::Blinky => {
+                    sprite(blinky_body.clone(), 0.0);
+                    sprite(blinky_eyes.clone(), 0.1);
+                }
+                ActorKind::FrightenedBlinky => sprite(frightened.clone(), 0.0),
+            }
+        });
+    }
+}
+
+/// Places the actors along their paths by the time spent in the intermission,
+/// so they stop along with it while the game is paused.
+fn move_actors(
+    mut actors: Query<(
+        &Actor,
+        &mut Location,
+        &mut Visibility,
+        &mut Transform,
+        &Children,
+    )>,
+    mut sprites: Query<&mut TextureAtlasSprite>,
+    timer: Res<StateTimer>,
+) {
+    let secs = timer.0.elapsed_secs();
+    for (actor, mut location, mut visibility, mut transform, children) in actors.iter_mut() {
+        let Some(new_location) = actor.location(secs) else {
+            *visibility = Visibility::Hidden;
+            continue;
+        };
+        *visibility = Visibility::Inherited;
+        *location = new_location;
+
+        let direction = actor.direction();
+        // A tile's worth of steps opens and closes the mouth once, and swaps
+        // the ghosts' skirts twice.
+        let quarter = (location.x().rem_euclid(1.0) * 4.0) as usize;
+        let mut frames = sprites.iter_many_mut(children);
+        match actor.kind {
+            ActorKind::PacMan => {
+                transform.rotation = Quat::from_rotation_z(TAU * direction.rotation());
+                if let Some(mut sprite) = frames.fetch_next() {
+                    sprite.index = [1, 2, 1, 0][quarter];
+                }
+            }
+            ActorKind::Blinky => {
+                if let Some(mut body) = frames.fetch_next() {
+                    body.index = quarter / 2;
+                }
+                if let Some(mut eyes) = frames.fetch_next() {
+                    eyes.index = (direction.rotation() * 4.0) as usize;
+                }
+            }
+            ActorKind::FrightenedBlinky => {
+                if let Some(mut sprite) = frames.fetch_next() {
+                    sprite.index = quarter / 2;
+                }
+            }
+        }
+    }
+}
+
+fn skip_intermission(
+    key: Res<Input<KeyCode>>,
+    mut gamepad: GamepadInput,
+    mut next_state: ResMut<NextState<AppState>>,
+) {
+    let key_pressed = key
+        .get_just_pressed()
+        .any(|key| !matches!(key, KeyCode::Escape | KeyCode::Back));
+    let button_pressed = gamepad
+        .just_pressed_keys()
+        .iter()
+        .any(|key| !matches!(key, KeyCode::Escape | KeyCode::Back));
+    if key_pressed || button_pressed {
+        next_state.set(AppState::LevelStart);
+    }
+}
+
+fn despawn_actors(mut commands: Commands, query: Query<Entity, With<Actor>>) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
@@ -1,12 +1,39 @@
-use std::{fmt::Display, fs::OpenOptions, io::BufRead, io::BufReader};
+use std::fmt::Display;
 
-use bevy::{input::keyboard::KeyboardInput, prelude::*};
+use bevy::{
+    input::{keyboard::KeyboardInput, mouse::MouseWheel},
+    prelude::*,
+};
 
 use crate::{
-    common::app_state::AppState,
-    services::{map::Location, text::TextProvider},
+    common::{app_state::AppState, game_config::GameConfig, layers::Layers},
+    points::Points,
+    run_timer::format_time,
+    services::{
+        input::{GamepadInput, KeyBindings},
+        map::{Direction, Location},
+        scores::{self, ScoreEntry},
+        text::TextProvider,
+    },
 };
 
+/// How long Up or Down has to be held before the list starts scrolling by
+/// itself, and how often it moves after that.
+const SCROLL_REPEAT_DELAY_SECS: f32 = 0.4;
+const SCROLL_REPEAT_SECS: f32 = 0.1;
+/// Entries skipped by Page Up and Page Down, a full screen.
+const PAGE_ENTRIES: isize = 10;
+const CLEAR_KEY: KeyCode = KeyCode::Delete;
+const CLEAR_PROMPT_TEXT: &str = "CLEAR SCORES? Y/N";
+
+/// Whether the prompt to clear every score, opened with Delete, is shown. The
+/// list ignores input while it's open and on the frame it closes.
+#[derive(Resource, Default)]
+struct ClearPrompt(bool);
+
+#[derive(Component)]
+struct ClearPromptSign;
+
 #[derive(Component)]
 struct Entry {
     index: usize,
@@ -15,7 +42,20 @@ struct Entry {
 #[derive(Resource)]
 struct LeaderboardState {
     top_entry_index: usize,
-    entries: Vec<(String, u32)>,
+    entries: Vec<ScoreEntry>,
+    /// The step of the Up or Down key being held, and when it's repeated next.
+    held_step: Option<isize>,
+    repeat_timer: Timer,
+}
+
+impl LeaderboardState {
+    fn scroll(&mut self, step: isize) {
+        let last_index = self.entries.len().saturating_sub(1);
+        self.top_entry_index = self
+            .top_entry_index
+            .saturating_add_signed(step)
+            .min(last_index);
+    }
 }
 
 #[derive(Component, Clone, Copy)]
@@ -23,6 +63,7 @@ enum EntryPart {
     Index,
     Name,
     Score,
+    Time,
 }
 
 pub struct LeaderboardPlugin;
@@ -30,12 +71,20 @@ pub struct LeaderboardPlugin;
 impl Plugin for LeaderboardPlugin {
     fn build(&self, app: &mut App) {
         app.add_systems(OnEnter(AppState::Leaderboard), setup);
-        app.add_systems(Update, update.run_if(in_state(AppState::Leaderboard)));
+        app.add_systems(
+            Update,
+            (answer_clear_prompt, draw_clear_prompt, update)
+                .chain()
+                .run_if(in_state(AppState::Leaderboard)),
+        );
         app.add_systems(OnExit(AppState::Leaderboard), despawn);
         app.insert_resource(LeaderboardState {
             top_entry_index: 0,
             entries: vec![],
+            held_step: None,
+            repeat_timer: Timer::from_seconds(SCROLL_REPEAT_DELAY_SECS, TimerMode::Once),
         });
+        app.insert_resource(ClearPrompt::default());
     }
 }
 
@@ -44,27 +93,13 @@ fn setup(
     mut text_provider: ResMut<TextProvider>,
     asset_server: Res<AssetServer>,
     mut leaderboard_state: ResMut<LeaderboardState>,
+    mut clear_prompt: ResMut<ClearPrompt>,
+    game_config: Res<GameConfig>,
 ) {
+    clear_prompt.0 = false;
     leaderboard_state.top_entry_index = 0;
-    leaderboard_state.entries.clear();
-
-    let scores = OpenOptions::new().read(true).open("scores");
-    if let Ok(scores) = scores {
-        let scores = BufReader::new(scores);
-
-        leaderboard_state.entries.extend(scores.lines().map(|line| {
-            line.expect("Failed to open scores file")
-                .split_once(':')
-                .map(|(name, score)| {
-                    (
-                        name.to_string(),
-                        score.parse::<u32>().expect("Scores file is corrupted"),
-                    )
-                })
-                .expect("Scores file is corrupted")
-        }));
-    }
-    leaderboard_state.entries.sort_by(|(_, a), (_, b)| b.cmp(a));
+    leaderboard_state.held_step = None;
+    leaderboard_state.entries = scores::load_best_first(game_config.strict_scores());
 
     commands.spawn((
         Location::new(13.5, 27.0),
@@ -94,8 +129,23 @@ fn setup(
                 &mut text_provider,
                 &asset_server,
             ));
+
+            parent.spawn(get_entry_part(
+                EntryPart::Time,
+                &"Time",
+                &mut text_provider,
+                &asset_server,
+            ));
         });
 
+    commands.spawn((
+        Location::new(13.5, 1.0),
+        SpriteBundle {
+            texture: text_provider.get_image("Del: clear scores", Color::WHITE, &asset_server),
+            ..default()
+        },
+    ));
+
     for i in 0..10 {
         commands
             .spawn((
@@ -124,6 +174,13 @@ fn setup(
                     &mut text_provider,
                     &asset_server,
                 ));
+
+                parent.spawn(get_entry_part(
+                    EntryPart::Time,
+                    &".",
+                    &mut text_provider,
+                    &asset_server,
+                ));
             });
     }
 }
@@ -151,12 +208,13 @@ fn get_part_location<T: Display>(
     text_provider: &mut TextProvider,
     text: &T,
 ) -> f32 {
-    const DISPLACEMENT: f32 = 10.0;
+    const DISPLACEMENT: f32 = 12.0;
 
     let displacement = match entry_part {
         EntryPart::Index => 0.0,
         EntryPart::Name => 2.5,
-        EntryPart::Score => 13.5,
+        EntryPart::Score => 13.0,
+        EntryPart::Time => 21.0,
     };
     text_provider.get_size(text).x / 2.0
         * if let EntryPart::Index = entry_part {
@@ -167,31 +225,151 @@ fn get_part_location<T: Display>(
         + (displacement - DISPLACEMENT) * 8.0
 }
 
+/// Yes empties the scores file and forgets the high score, no keeps them.
+fn answer_clear_prompt(
+    mut clear_prompt: ResMut<ClearPrompt>,
+    mut leaderboard_state: ResMut<LeaderboardState>,
+    mut points: ResMut<Points>,
+    key: Res<Input<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    gamepad: GamepadInput,
+) {
+    if !clear_prompt.0 {
+        if key.just_pressed(CLEAR_KEY) {
+            clear_prompt.0 = true;
+        }
+        return;
+    }
+
+    let yes =
+        key.any_just_pressed([KeyCode::Y, bindings.confirm]) || gamepad.confirm_just_pressed();
+    let no = key.just_pressed(KeyCode::N) || gamepad.cancel_just_pressed();
+    if !yes && !no {
+        return;
+    }
+
+    clear_prompt.0 = false;
+    if yes {
+        scores::clear();
+        points.clear_high_score();
+        leaderboard_state.entries.clear();
+        leaderboard_state.top_entry_index = 0;
+    }
+}
+
+fn draw_clear_prompt(
+    mut commands: Commands,
+    clear_prompt: Res<ClearPrompt>,
+    query: Query<Entity, With<ClearPromptSign>>,
+    mut text_provider: ResMut<TextProvider>,
+    asset_server: Res<AssetServer>,
+) {
+    if !clear_prompt.is_changed() {
+        return;
+    }
+
+    for entity in query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+
+    if !clear_prompt.0 {
+        return;
+    }
+
+    commands
+        .spawn((
+            ClearPromptSign,
+            Location::new(13.5, 13.0),
+            SpriteBundle {
+                sprite: Sprite {
+                    color: Color::BLACK,
+                    custom_size: Some(text_provider.get_size(CLEAR_PROMPT_TEXT) + Vec2::splat(8.0)),
+                    ..default()
+                },
+                transform: Transform::from_xyz(0.0, 0.0, Layers::HUD.as_f32() + 1.0),
+                ..default()
+            },
+        ))
+        .with_children(|parent| {
+            parent.spawn(SpriteBundle {
+                texture: text_provider.get_image(CLEAR_PROMPT_TEXT, Color::YELLOW, &asset_server),
+                transform: Transform::from_xyz(0.0, 0.0, 0.5),
+                ..default()
+            });
+        });
+}
+
+#[allow(clippy::too_many_arguments)]
 fn update(
     mut leaderboard_state: ResMut<LeaderboardState>,
+    clear_prompt: Res<ClearPrompt>,
     mut entry_query: Query<(&mut Entry, &mut Visibility, &Children), Without<EntryPart>>,
     mut entry_part_query: Query<(&EntryPart, &mut Transform, &mut Handle<Image>)>,
     mut keyboard_events: EventReader<KeyboardInput>,
+    mut gamepad: GamepadInput,
+    mut mouse_wheel_events: EventReader<MouseWheel>,
+    bindings: Res<KeyBindings>,
+    key: Res<Input<KeyCode>>,
+    time: Res<Time>,
     mut text_provider: ResMut<TextProvider>,
     asset_server: Res<AssetServer>,
 ) {
-    for event in keyboard_events.read() {
-        if !event.state.is_pressed() {
-            continue;
+    let mut pressed_keys = keyboard_events
+        .read()
+        .filter(|event| event.state.is_pressed())
+        .filter_map(|event| event.key_code)
+        .collect::<Vec<_>>();
+    pressed_keys.extend(gamepad.just_pressed_keys());
+    pressed_keys.extend(mouse_wheel_events.read().filter_map(|event| {
+        if event.y > 0.0 {
+            Some(bindings.up)
+        } else if event.y < 0.0 {
+            Some(bindings.down)
+        } else {
+            None
         }
+    }));
 
-        match event.key_code {
-            Some(KeyCode::Up) => {
-                if leaderboard_state.top_entry_index > 0 {
-                    leaderboard_state.top_entry_index -= 1;
-                }
-            }
-            Some(KeyCode::Down) => {
-                if leaderboard_state.top_entry_index < leaderboard_state.entries.len() - 1 {
-                    leaderboard_state.top_entry_index += 1;
-                }
-            }
+    let prompt_answered = clear_prompt.0 || clear_prompt.is_changed();
+    if prompt_answered {
+        pressed_keys.clear();
+    }
+
+    for key_code in pressed_keys {
+        let step = match key_code {
+            key if key == bindings.up => -1,
+            key if key == bindings.down => 1,
+            KeyCode::PageUp => -PAGE_ENTRIES,
+            KeyCode::PageDown => PAGE_ENTRIES,
             _ => continue,
+        };
+        leaderboard_state.scroll(step);
+    }
+
+    // The press itself already scrolled once above, so holding only takes over
+    // after a delay.
+    let held_step = if prompt_answered {
+        None
+    } else if key.pressed(bindings.up) || gamepad.pressed(Direction::Up) {
+        Some(-1)
+    } else if key.pressed(bindings.down) || gamepad.pressed(Direction::Down) {
+        Some(1)
+    } else {
+        None
+    };
+    if held_step != leaderboard_state.held_step {
+        leaderboard_state.held_step = held_step;
+        leaderboard_state.repeat_timer =
+            Timer::from_seconds(SCROLL_REPEAT_DELAY_SECS, TimerMode::Once);
+    } else if let Some(step) = held_step {
+        if leaderboard_state
+            .repeat_timer
+            .tick(time.delta())
+            .just_finished()
+        {
+            leaderboard_state.scroll(step);
+            leaderboard_state.repeat_timer =
+                Timer::from_seconds(SCROLL_REPEAT_SECS, TimerMode::Once);
         }
     }
 
@@ -207,7 +385,7 @@ fn update(
             *visibility = Visibility::Inherited;
         }
 
-        let (name, score) = leaderboard_entry.unwrap();
+        let (name, score, time) = leaderboard_entry.unwrap();
 
         for child in children.iter() {
             let (entry_part, mut transform, mut handle) = entry_part_query.get_mut(*child).unwrap();
@@ -218,6 +396,7 @@ fn update(
                 }
                 EntryPart::Name => name.clone(),
                 EntryPart::Score => score.to_string(),
+                EntryPart::Time => time.map_or_else(|| "--:--".to_string(), format_time),
             };
             *handle = text_provider.get_image(&text, Color::WHITE, &asset_server);
 
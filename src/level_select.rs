@@ -0,0 +1,168 @@
+use bevy::{input::keyboard::KeyboardInput, prelude::*};
+use bevy_kira_audio::prelude::*;
+
+#[cfg(debug_assertions)]
+use crate::ghosts::SpawnedGhosts;
+use crate::{
+    common::{app_state::AppState, levels::Levels},
+    services::{
+        input::{GamepadInput, KeyBindings},
+        map::Location,
+        text::TextProvider,
+    },
+    StartGameSound,
+};
+
+#[derive(Component, Clone, Copy)]
+enum LevelInfo {
+    Level,
+    PlayerSpeed,
+    GhostSpeed,
+    FriteDuration,
+    /// Which ghosts spawn, changed with left and right in debug builds.
+    #[cfg(debug_assertions)]
+    Ghosts,
+}
+
+#[derive(Component)]
+struct LevelSymbol;
+
+pub struct LevelSelectPlugin;
+
+impl Plugin for LevelSelectPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(AppState::LevelSelect), setup);
+        app.add_systems(
+            Update,
+            (update, draw_level_info)
+                .chain()
+                .run_if(in_state(AppState::LevelSelect)),
+        );
+        app.add_systems(OnExit(AppState::LevelSelect), despawn);
+    }
+}
+
+fn setup(
+    mut commands: Commands,
+    mut text_provider: ResMut<TextProvider>,
+    asset_server: Res<AssetServer>,
+    mut levels: ResMut<Levels>,
+) {
+    levels.select(1);
+
+    commands.spawn((
+        Location::new(13.5, 27.0),
+        SpriteBundle {
+            texture: text_provider.get_image("Level select", Color::YELLOW, &asset_server),
+            sprite: Sprite {
+                custom_size: Some(text_provider.get_size("Level select") * 1.5),
+                ..default()
+            },
+            ..default()
+        },
+    ));
+
+    commands.spawn((
+        Location::new(13.5, 19.0),
+        SpriteBundle::default(),
+        LevelSymbol,
+    ));
+
+    for (i, info) in [
+        LevelInfo::Level,
+        LevelInfo::PlayerSpeed,
+        LevelInfo::GhostSpeed,
+        LevelInfo::FriteDuration,
+        #[cfg(debug_assertions)]
+        LevelInfo::Ghosts,
+    ]
+    .into_iter()
+    .enumerate()
+    {
+        let y = if let LevelInfo::Level = info {
+            22.0
+        } else {
+            17.0 - (2 * i) as f32
+        };
+        commands.spawn((Location::new(13.5, y), SpriteBundle::default(), info));
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn update(
+    mut levels: ResMut<Levels>,
+    mut next_state: ResMut<NextState<AppState>>,
+    mut keyboard_events: EventReader<KeyboardInput>,
+    mut gamepad: GamepadInput,
+    bindings: Res<KeyBindings>,
+    asset_server: Res<AssetServer>,
+    audio: Res<Audio>,
+    mut start_game_sound: ResMut<StartGameSound>,
+    #[cfg(debug_assertions)] mut spawned_ghosts: ResMut<SpawnedGhosts>,
+) {
+    let mut pressed_keys = keyboard_events
+        .read()
+        .filter(|event| event.state.is_pressed())
+        .filter_map(|event| event.key_code)
+        .collect::<Vec<_>>();
+    pressed_keys.extend(gamepad.just_pressed_keys());
+
+    for key_code in pressed_keys {
+        match key_code {
+            key if key == bindings.up => {
+                let level = levels.current() + 1;
+                levels.select(level);
+            }
+            key if key == bindings.down => {
+                let level = levels.current().saturating_sub(1);
+                levels.select(level);
+            }
+            #[cfg(debug_assertions)]
+            key if key == bindings.left || key == bindings.right => {
+                spawned_ghosts.cycle(if key == bindings.right { 1 } else { -1 });
+            }
+            key if key == bindings.confirm => {
+                next_state.set(AppState::LevelStart);
+                start_game_sound.0 = audio
+                    .play(asset_server.load("sounds/game_start.wav"))
+                    .handle();
+            }
+            _ => continue,
+        }
+    }
+}
+
+fn draw_level_info(
+    mut info_query: Query<(&LevelInfo, &mut Handle<Image>), Without<LevelSymbol>>,
+    mut symbol_query: Query<&mut Handle<Image>, With<LevelSymbol>>,
+    levels: Res<Levels>,
+    mut text_provider: ResMut<TextProvider>,
+    asset_server: Res<AssetServer>,
+    #[cfg(debug_assertions)] spawned_ghosts: Res<SpawnedGhosts>,
+) {
+    for (info, mut image) in info_query.iter_mut() {
+        let text = match info {
+            LevelInfo::Level => format!("Level {}", levels.current()),
+            LevelInfo::PlayerSpeed => {
+                format!("Pacman speed {:.0}%", levels.player_speed() * 100.0)
+            }
+            LevelInfo::GhostSpeed => {
+                format!("Ghost speed {:.0}%", levels.ghost_normal_speed() * 100.0)
+            }
+            LevelInfo::FriteDuration => format!("Fright time {}s", levels.frite_duration()),
+            #[cfg(debug_assertions)]
+            LevelInfo::Ghosts => format!("Ghosts {}", spawned_ghosts.describe()),
+        };
+        *image = text_provider.get_image(text, Color::WHITE, &asset_server);
+    }
+
+    for mut image in symbol_query.iter_mut() {
+        *image = asset_server.load(levels.bonus_symbol().asset());
+    }
+}
+
+fn despawn(mut commands: Commands, query: Query<Entity, With<Location>>) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
@@ -0,0 +1,137 @@
+use bevy::{ecs::system::SystemParam, prelude::*};
+
+use crate::{
+    background_sound::LEVEL_COMPLETE_JINGLE_SECS,
+    common::{
+        app_state::{AppState, NewRun, StateTimer},
+        layers::Layers,
+    },
+    services::{map::Location, text::TextProvider},
+};
+
+const LINE_HEIGHT: f32 = 12.0;
+
+/// What the player collected, counted as it happens.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Stats {
+    pub pellets: u32,
+    pub ghosts: u32,
+    pub fruit: u32,
+    /// Points from fruit and from eating all sixteen ghosts of a level.
+    pub bonus_points: u32,
+}
+
+impl Stats {
+    /// The stats as lines of text, in the order they're shown.
+    pub fn lines(&self) -> [String; 4] {
+        [
+            format!("Pellets: {}", self.pellets),
+            format!("Ghosts: {}", self.ghosts),
+            format!("Fruit: {}", self.fruit),
+            format!("Bonus: {}", self.bonus_points),
+        ]
+    }
+}
+
+/// Stats of the current level, shown when it's complete.
+#[derive(Resource, Default)]
+pub struct LevelStats(pub Stats);
+
+/// Stats of the whole run, shown on the game over screen.
+#[derive(Resource, Default)]
+pub struct GameStats(pub Stats);
+
+/// Counts into the level's and the run's stats at once.
+#[derive(SystemParam)]
+pub struct StatsRecorder<'w> {
+    level: ResMut<'w, LevelStats>,
+    game: ResMut<'w, GameStats>,
+}
+
+impl StatsRecorder<'_> {
+    pub fn record(&mut self, update: impl Fn(&mut Stats)) {
+        update(&mut self.level.0);
+        update(&mut self.game.0);
+    }
+}
+
+#[derive(Component)]
+struct LevelStatsSign;
+
+pub struct LevelStatsPlugin;
+
+impl Plugin for LevelStatsPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(LevelStats::default());
+        app.insert_resource(GameStats::default());
+        app.add_systems(OnEnter(AppState::LevelStart), reset_level_stats);
+        app.add_systems(NewRun, reset_game_stats);
+        app.add_systems(OnEnter(AppState::LevelComplete), spawn_level_stats);
+        app.add_systems(
+            Update,
+            despawn_level_stats.run_if(in_state(AppState::LevelComplete).and_then(jingle_over)),
+        );
+        app.add_systems(OnExit(AppState::LevelComplete), despawn_level_stats);
+    }
+}
+
+fn reset_level_stats(mut level_stats: ResMut<LevelStats>) {
+    level_stats.0 = Stats::default();
+}
+
+fn reset_game_stats(mut game_stats: ResMut<GameStats>) {
+    game_stats.0 = Stats::default();
+}
+
+/// Shown over the ghost house while the jingle plays, and gone once the maze
+/// starts flashing.
+fn spawn_level_stats(
+    mut commands: Commands,
+    level_stats: Res<LevelStats>,
+    mut text_provider: ResMut<TextProvider>,
+    asset_server: Res<AssetServer>,
+) {
+    let lines = level_stats.0.lines();
+    let widest_line = lines
+        .iter()
+        .map(|line| text_provider.get_size(line).x)
+        .fold(0.0, f32::max);
+
+    commands
+        .spawn((
+            LevelStatsSign,
+            Location::new(13.5, 16.0),
+            SpriteBundle {
+                sprite: Sprite {
+                    color: Color::BLACK,
+                    custom_size: Some(Vec2::new(
+                        widest_line + 16.0,
+                        LINE_HEIGHT * lines.len() as f32 + 8.0,
+                    )),
+                    ..default()
+                },
+                transform: Transform::from_xyz(0.0, 0.0, Layers::HUD.as_f32()),
+                ..default()
+            },
+        ))
+        .with_children(|parent| {
+            for (i, line) in lines.iter().enumerate() {
+                let y = LINE_HEIGHT * ((lines.len() - 1) as f32 / 2.0 - i as f32);
+                parent.spawn(SpriteBundle {
+                    texture: text_provider.get_image(line, Color::WHITE, &asset_server),
+                    transform: Transform::from_xyz(0.0, y, 0.5),
+                    ..default()
+                });
+            }
+        });
+}
+
+fn jingle_over(timer: Res<StateTimer>) -> bool {
+    timer.0.elapsed_secs() >= LEVEL_COMPLETE_JINGLE_SECS
+}
+
+fn despawn_level_stats(mut commands: Commands, query: Query<Entity, With<LevelStatsSign>>) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
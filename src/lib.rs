@@ -0,0 +1,455 @@
+//! The game, as played by the `pacman` binary through `run`. A program driving
+//! it instead, like a bot, builds the game with `headless_app` and plays it
+//! through the `BotInterface` resource, which hands out a `GameSnapshot` of the
+//! board every tick and takes a `Direction` to steer the player.
+
+use std::time::Duration;
+
+use bevy::{
+    ecs::{schedule::apply_state_transition, system::SystemParam},
+    input::{keyboard::KeyboardInput, ButtonState},
+    prelude::*,
+};
+use bevy_kira_audio::prelude::*;
+
+use common::{
+    app_state::{AppState, DeadState, NewRun, PauseState, StateDwellTimes, StateTimer},
+    events::{
+        CollisionPauseTimer, GetExtraLife, GhostEaten, GhostModeChanged, LevelComplete,
+        PelletEaten, PlayerAt,
+    },
+    game_config::GameConfig,
+    levels::Levels,
+    sets::GameLoop,
+};
+use quit_prompt::{answer_quit_prompt, QuitPrompt};
+use services::{
+    audio::AudioSettingsPlugin, input::KeyBindings, map::Location, rng::GameRng,
+    text::TextProviderPlugin,
+};
+
+use bevy::winit::WinitWindows;
+use winit::window::Icon;
+
+mod background_sound;
+mod bot;
+mod camera;
+mod career_stats;
+mod common;
+mod fps_overlay;
+mod game_over;
+mod ghost_strategy;
+mod ghosts;
+mod headless;
+mod intermission;
+mod leaderboard;
+mod level_select;
+mod level_stats;
+mod map_render;
+mod menu;
+mod missing_assets;
+mod pause;
+mod pellets;
+mod player;
+mod points;
+mod quit_prompt;
+mod replay;
+mod run_timer;
+mod savegame;
+mod services;
+mod settings;
+mod settings_menu;
+
+pub use bot::{BotInterface, GameSnapshot, GhostSnapshot, PelletSnapshot, PlayerSnapshot};
+pub use ghosts::{Ghost, GhostMode};
+pub use headless::headless_app;
+pub use services::map::Direction;
+
+pub const MAX_MOVE_SPEED: f64 = 78.0; // In pixel per second
+
+#[derive(Resource, Default)]
+pub struct StartGameSound(Handle<AudioInstance>);
+
+/// Debug build controls for slowing down the game loop. F5 cycles the fixed
+/// timestep through `DEBUG_SPEEDS` and F6 runs a single tick while paused.
+#[cfg(debug_assertions)]
+#[derive(Resource, Default)]
+pub struct DebugTimeControls {
+    speed_index: usize,
+    step: bool,
+}
+
+/// Lets the player move through walls and ghosts, for checking a maze's walls
+/// and pellets. Toggled with F10.
+#[cfg(debug_assertions)]
+#[derive(Resource, Default)]
+pub struct NoClip(pub bool);
+
+#[cfg(debug_assertions)]
+const DEBUG_SPEEDS: [f64; 3] = [1.0, 0.5, 0.25];
+
+/// Runs the game in a window, or headless when asked to on the command line.
+pub fn run() {
+    if let Some(ticks) = headless::ticks_from_args() {
+        headless::run(ticks);
+        return;
+    }
+
+    let mut app = App::new();
+    app.insert_resource(ClearColor(Color::rgb(0.0, 0.0, 0.0)))
+        .add_plugins(
+            DefaultPlugins
+                .set(ImagePlugin::default_nearest())
+                .set(WindowPlugin {
+                    primary_window: Some(Window {
+                        present_mode: bevy::window::PresentMode::AutoNoVsync,
+                        ..default()
+                    }),
+                    ..default()
+                }),
+        )
+        .add_plugins(AudioPlugin)
+        .add_plugins(bevy_framepace::FramepacePlugin)
+        .add_plugins(AudioSettingsPlugin)
+        .add_plugins(points::HighScorePlugin)
+        .insert_resource(StartGameSound::default())
+        .add_plugins((
+            menu::MenuPlugin,
+            game_over::GameOverPlugin,
+            leaderboard::LeaderboardPlugin,
+            career_stats::CareerStatsPlugin,
+            level_select::LevelSelectPlugin,
+            background_sound::BackgroundSoundPlugin,
+            pause::PausePlugin,
+            camera::CameraPlugin,
+            quit_prompt::QuitPromptPlugin,
+            settings::SettingsPlugin,
+            fps_overlay::FpsOverlayPlugin,
+            savegame::SaveGamePlugin,
+            settings_menu::SettingsMenuPlugin,
+            intermission::IntermissionPlugin,
+            missing_assets::MissingAssetsPlugin,
+        ))
+        .add_systems(Startup, frame_rate_limiter)
+        .add_systems(Update, escape_press.after(answer_quit_prompt))
+        .add_systems(Startup, set_window_icon);
+
+    add_game(&mut app);
+
+    app.run();
+}
+
+/// Adds the game itself: the maze, its characters and the game loop that moves
+/// them. Screens outside of play, like the menu, are added by `main`, so this is
+/// also all the headless mode runs.
+pub fn add_game(app: &mut App) {
+    app.insert_resource(Time::<Fixed>::from_hz(MAX_MOVE_SPEED))
+        .add_plugins(TextProviderPlugin)
+        .insert_resource(StateTimer(
+            Timer::from_seconds(0.0, TimerMode::Once)
+                .tick(Duration::from_secs(1))
+                .clone(),
+        ))
+        .insert_resource(StateDwellTimes::default())
+        .insert_resource(CollisionPauseTimer(Timer::from_seconds(
+            0.0,
+            TimerMode::Once,
+        )))
+        .insert_resource(Levels::default())
+        .insert_resource(GameConfig::default())
+        .insert_resource(KeyBindings::load())
+        .insert_resource(GameRng::load())
+        .add_event::<PlayerAt>()
+        .add_event::<PelletEaten>()
+        .add_event::<GetExtraLife>()
+        .add_event::<GhostEaten>()
+        .add_event::<GhostModeChanged>()
+        .add_event::<LevelComplete>()
+        .add_state::<AppState>()
+        .add_state::<DeadState>()
+        .add_state::<PauseState>()
+        .configure_sets(
+            FixedUpdate,
+            (GameLoop::Planning, GameLoop::Movement, GameLoop::Collisions)
+                .chain()
+                .run_if(in_state(AppState::MainGame).and_then(game_loop_running)),
+        )
+        .add_plugins((
+            map_render::MapRenderPlugin,
+            pellets::PelletsPlugin,
+            player::PlayerPlugin,
+            ghosts::GhostPlugin,
+            points::PointsPlugin,
+            replay::ReplayPlugin,
+            run_timer::RunTimerPlugin,
+            bot::BotPlugin,
+            level_stats::LevelStatsPlugin,
+        ))
+        .add_systems(
+            StateTransition,
+            start_state_timer.after(apply_state_transition::<AppState>),
+        )
+        .add_systems(
+            PostUpdate,
+            (timed_state_transition, update_entities_location),
+        )
+        .add_systems(OnEnter(AppState::LevelStart), advance_level)
+        .add_systems(OnEnter(AppState::MainMenu), init)
+        .init_schedule(NewRun)
+        .add_systems(OnExit(AppState::MainMenu), start_new_run)
+        .add_systems(
+            OnTransition {
+                from: AppState::GameOver,
+                to: AppState::LevelStart,
+            },
+            (init, start_new_run),
+        );
+
+    #[cfg(debug_assertions)]
+    app.insert_resource(DebugTimeControls::default())
+        .insert_resource(NoClip::default())
+        .add_systems(Update, (debug_time_controls, toggle_no_clip))
+        .add_systems(
+            PostUpdate,
+            assert_board_static
+                .before(update_entities_location)
+                .run_if(in_state(AppState::LevelStart)),
+        )
+        .add_systems(FixedUpdate, end_frame_step.after(GameLoop::Collisions));
+}
+
+pub fn init(mut collision_timer: ResMut<CollisionPauseTimer>, mut levels: ResMut<Levels>) {
+    collision_timer.0.set_duration(Duration::from_secs(0));
+    collision_timer.0.reset();
+
+    levels.reset();
+}
+
+fn start_new_run(world: &mut World) {
+    world.run_schedule(NewRun);
+}
+
+fn frame_rate_limiter(mut settings: ResMut<bevy_framepace::FramepaceSettings>) {
+    settings.limiter = bevy_framepace::Limiter::from_framerate(MAX_MOVE_SPEED);
+}
+
+/// Restarts the state timer right as a state is entered, before anything reads
+/// it this frame, with the dwell time from `StateDwellTimes`.
+fn start_state_timer(
+    state: Res<State<AppState>>,
+    mut timer: ResMut<StateTimer>,
+    dwell_times: Res<StateDwellTimes>,
+    mut previous_state: Local<AppState>,
+) {
+    if *previous_state == *state.get() {
+        return;
+    }
+
+    match dwell_times.get(state.get(), &previous_state) {
+        Some(duration) => {
+            timer.0.set_duration(duration);
+            timer.0.reset();
+            timer.0.unpause();
+        }
+        None => timer.0.pause(),
+    }
+    *previous_state = state.get().clone();
+}
+
+fn timed_state_transition(
+    state: Res<State<AppState>>,
+    mut next_state: ResMut<NextState<AppState>>,
+    mut timer: ResMut<StateTimer>,
+    time: Res<Time>,
+    levels: Res<Levels>,
+) {
+    if timer.0.tick(time.delta()).just_finished() {
+        match state.get() {
+            AppState::LevelStart => next_state.set(AppState::MainGame),
+            AppState::LevelComplete if levels.is_last() => next_state.set(AppState::GameOver),
+            AppState::LevelComplete if levels.intermission_follows() => {
+                next_state.set(AppState::Intermission)
+            }
+            AppState::LevelComplete | AppState::Intermission => {
+                next_state.set(AppState::LevelStart)
+            }
+            _ => (),
+        };
+    }
+}
+
+/// The game loop runs while the game isn't paused, plus the single ticks
+/// requested by the debug frame step.
+pub fn game_loop_running(
+    pause_state: Res<State<PauseState>>,
+    #[cfg(debug_assertions)] time_controls: Res<DebugTimeControls>,
+) -> bool {
+    #[cfg(debug_assertions)]
+    if time_controls.step {
+        return true;
+    }
+
+    *pause_state.get() == PauseState::Running
+}
+
+#[cfg(debug_assertions)]
+fn debug_time_controls(
+    key: Res<Input<KeyCode>>,
+    mut time_controls: ResMut<DebugTimeControls>,
+    mut fixed_time: ResMut<Time<Fixed>>,
+    pause_state: Res<State<PauseState>>,
+) {
+    if key.just_pressed(KeyCode::F5) {
+        time_controls.speed_index = (time_controls.speed_index + 1) % DEBUG_SPEEDS.len();
+        let speed = DEBUG_SPEEDS[time_controls.speed_index];
+        fixed_time.set_timestep_hz(MAX_MOVE_SPEED * speed);
+        info!("Game loop running at {}x speed", speed);
+    }
+
+    if key.just_pressed(KeyCode::F6) && *pause_state.get() == PauseState::Paused {
+        time_controls.step = true;
+    }
+}
+
+#[cfg(debug_assertions)]
+fn toggle_no_clip(key: Res<Input<KeyCode>>, mut no_clip: ResMut<NoClip>) {
+    if key.just_pressed(KeyCode::F10) {
+        no_clip.0 = !no_clip.0;
+        info!("No clip {}", if no_clip.0 { "on" } else { "off" });
+    }
+}
+
+/// Nothing on the board may move before the game loop starts, while READY! is
+/// shown. Spawning the characters counts as a change, so only later ones are
+/// caught.
+#[cfg(debug_assertions)]
+#[allow(clippy::type_complexity)]
+fn assert_board_static(
+    query: Query<Ref<Location>, Or<(With<ghosts::Ghost>, With<player::Player>)>>,
+) {
+    for location in query.iter() {
+        assert!(
+            !location.is_changed() || location.is_added(),
+            "A character moved during the READY! pause"
+        );
+    }
+}
+
+#[cfg(debug_assertions)]
+fn end_frame_step(mut time_controls: ResMut<DebugTimeControls>) {
+    time_controls.step = false;
+}
+
+pub fn advance_level(mut levels: ResMut<Levels>) {
+    levels.next();
+}
+
+fn update_entities_location(mut query: Query<(&mut Transform, &Location), Changed<Location>>) {
+    query.par_iter_mut().for_each(|(mut transform, location)| {
+        let world = location.world_position();
+        transform.translation.x = world.x;
+        transform.translation.y = world.y;
+    });
+}
+
+/// Leaving the current screen early, with Escape or from the pause overlay.
+#[derive(SystemParam)]
+pub struct LeaveScreen<'w> {
+    next_state: ResMut<'w, NextState<AppState>>,
+    next_dead_state: ResMut<'w, NextState<DeadState>>,
+    state_timer: ResMut<'w, StateTimer>,
+    game_start_sound: ResMut<'w, StartGameSound>,
+    audio_instances: ResMut<'w, Assets<AudioInstance>>,
+}
+
+impl LeaveScreen<'_> {
+    pub fn go_to(&mut self, state: AppState) {
+        self.next_state.set(state);
+        self.state_timer.0.pause();
+
+        self.next_dead_state.set(DeadState::default());
+
+        if let Some(audio_instance) = self.audio_instances.get_mut(&self.game_start_sound.0) {
+            audio_instance.stop(AudioTween::default());
+            self.game_start_sound.0 = Handle::default();
+        }
+    }
+
+    /// Holds the current screen in place, like while the game is paused.
+    pub fn hold(&mut self) {
+        self.state_timer.0.pause();
+    }
+
+    pub fn resume(&mut self) {
+        self.state_timer.0.unpause();
+    }
+}
+
+/// Screens outside of play go back to the menu. During a game Escape pauses
+/// instead, so only picking quit from the pause overlay ends the run.
+fn escape_press(
+    state: Res<State<AppState>>,
+    mut leave_screen: LeaveScreen,
+    mut keyboard_events: EventReader<KeyboardInput>,
+    quit_prompt: Res<QuitPrompt>,
+    pause_state: Res<State<PauseState>>,
+    mut next_pause_state: ResMut<NextState<PauseState>>,
+) {
+    if quit_prompt.is_open() || quit_prompt.is_changed() {
+        keyboard_events.clear();
+        return;
+    }
+
+    for event in keyboard_events.read() {
+        if let KeyboardInput {
+            state: ButtonState::Pressed,
+            key_code: Some(KeyCode::Escape | KeyCode::Back),
+            ..
+        } = event
+        {
+            match state.get() {
+                AppState::MainMenu
+                | AppState::GameOver
+                | AppState::Leaderboard
+                | AppState::Stats
+                | AppState::LevelSelect
+                | AppState::BadMap => leave_screen.go_to(AppState::MainMenu),
+                // During a game Escape toggles the pause overlay, which is
+                // where the run can be abandoned.
+                _ if *pause_state.get() == PauseState::Paused => {
+                    leave_screen.resume();
+                    next_pause_state.set(PauseState::Running);
+                }
+                _ if *pause_state.get() == PauseState::Options => {
+                    next_pause_state.set(PauseState::Paused);
+                }
+                _ => {
+                    leave_screen.hold();
+                    next_pause_state.set(PauseState::Paused);
+                }
+            }
+        }
+    }
+}
+
+fn set_window_icon(
+    // we have to use `NonSend` here
+    windows: NonSend<WinitWindows>,
+) {
+    // here we use the `image` crate to load our icon data from a png file
+    // this is not a very bevy-native solution, but it will do
+    let (icon_rgba, icon_width, icon_height) = {
+        let image = image::open("assets/icon.png")
+            .expect("Failed to open icon path")
+            .into_rgba8();
+        let (width, height) = image.dimensions();
+        let rgba = image.into_raw();
+        (rgba, width, height)
+    };
+    let icon = Icon::from_rgba(icon_rgba, icon_width, icon_height).unwrap();
+
+    // do it for all windows
+    for window in windows.windows.values() {
+        window.set_window_icon(Some(icon.clone()));
+    }
+}
@@ -1,30 +1,73 @@
 use bevy::prelude::*;
 
+use crate::advance_level;
+use crate::background_sound::LEVEL_COMPLETE_JINGLE_SECS;
 use crate::common::app_state::{AppState, DeadState, StateTimer};
 use crate::common::layers::Layers;
-use crate::common::sets::GameLoop;
-use crate::services::map::{Location, Map};
+use crate::common::levels::Levels;
+use crate::common::sets::{GameLoop, Rendering};
+use crate::services::input::{GamepadInput, KeyBindings};
+use crate::services::map::{Location, Map, MapParseError};
 use crate::services::text::TextProvider;
 
+const MAP_FILE: &str = "map";
+const DEFAULT_MAP_TEXT: &str = include_str!("map");
+const BAD_MAP_LINE_LENGTH: usize = 26;
+
 #[derive(Component)]
 struct MapComponent;
 
 #[derive(Component)]
 struct ReadySign;
 
+/// Seconds left until play starts, shown next to READY! at the start of a
+/// level.
+#[derive(Component)]
+struct Countdown;
+
 #[derive(Component)]
 pub struct NoMapWrap;
 
+#[derive(Resource)]
+struct MapError(MapParseError);
+
+#[derive(Component)]
+struct BadMapScreen;
+
 pub struct MapRenderPlugin;
 
 impl Plugin for MapRenderPlugin {
     fn build(&self, app: &mut App) {
-        const MAP_TEXT: &str = include_str!("map");
+        let map = match load_map() {
+            Ok(map) => map,
+            Err(error) => {
+                warn!(
+                    "Failed to load the map file, using the default map: {}",
+                    error
+                );
+                app.insert_resource(MapError(error));
+                app.add_systems(Startup, show_bad_map);
+                Map::parse(DEFAULT_MAP_TEXT).expect("Error parsing the default map")
+            }
+        };
 
-        app.insert_resource(Map::parse(MAP_TEXT));
-        app.add_systems(OnEnter(AppState::LevelStart), (render_map, spawn_ready));
+        app.insert_resource(map);
+        app.add_systems(OnEnter(AppState::BadMap), spawn_bad_map_screen);
+        app.add_systems(OnExit(AppState::BadMap), despawn_bad_map_screen);
+        app.add_systems(Update, leave_bad_map.run_if(in_state(AppState::BadMap)));
+        app.add_systems(
+            OnEnter(AppState::LevelStart),
+            (
+                render_map.after(advance_level),
+                spawn_ready,
+                spawn_countdown,
+            ),
+        );
         app.add_systems(OnEnter(DeadState::Restart), spawn_ready);
-        app.add_systems(OnExit(AppState::LevelStart), remove_ready);
+        app.add_systems(
+            OnExit(AppState::LevelStart),
+            (remove_ready, remove_countdown),
+        );
         app.add_systems(OnExit(DeadState::Restart), remove_ready);
         app.add_systems(
             FixedUpdate,
@@ -34,18 +77,112 @@ impl Plugin for MapRenderPlugin {
                 .run_if(in_state(AppState::MainGame)),
         );
 
-        app.add_systems(Update, flash_map.run_if(in_state(AppState::LevelComplete)));
+        app.add_systems(
+            Update,
+            flash_map
+                .in_set(Rendering)
+                .run_if(in_state(AppState::LevelComplete)),
+        );
+        app.add_systems(
+            Update,
+            draw_countdown
+                .in_set(Rendering)
+                .run_if(in_state(AppState::LevelStart)),
+        );
         app.add_systems(OnExit(AppState::LevelComplete), despawn);
         app.add_systems(OnEnter(AppState::GameOver), despawn);
     }
 }
 
+/// Uses a `map` file next to the executable when there is one, and the
+/// embedded maze otherwise.
+fn load_map() -> Result<Map, MapParseError> {
+    let map_text = std::env::current_exe()
+        .ok()
+        .and_then(|exe| Some(exe.parent()?.join(MAP_FILE)))
+        .and_then(|path| std::fs::read_to_string(path).ok());
+
+    Map::parse(map_text.as_deref().unwrap_or(DEFAULT_MAP_TEXT))
+}
+
+fn show_bad_map(mut next_state: ResMut<NextState<AppState>>) {
+    next_state.set(AppState::BadMap);
+}
+
+fn spawn_bad_map_screen(
+    mut commands: Commands,
+    mut text_provider: ResMut<TextProvider>,
+    asset_server: Res<AssetServer>,
+    error: Res<MapError>,
+) {
+    commands.spawn((
+        BadMapScreen,
+        Location::new(13.5, 23.0),
+        SpriteBundle {
+            texture: text_provider.get_image("BAD MAP", Color::RED, &asset_server),
+            ..default()
+        },
+    ));
+
+    let mut lines = vec![String::new()];
+    for word in error.0.to_string().split_whitespace() {
+        let line = lines.last_mut().unwrap();
+        if !line.is_empty() && line.len() + word.len() + 1 > BAD_MAP_LINE_LENGTH {
+            lines.push(word.to_string());
+        } else {
+            if !line.is_empty() {
+                line.push(' ');
+            }
+            line.push_str(word);
+        }
+    }
+
+    for (i, line) in lines.iter().enumerate() {
+        commands.spawn((
+            BadMapScreen,
+            Location::new(13.5, 19.0 - (2 * i) as f32),
+            SpriteBundle {
+                texture: text_provider.get_image(line, Color::WHITE, &asset_server),
+                ..default()
+            },
+        ));
+    }
+
+    commands.spawn((
+        BadMapScreen,
+        Location::new(13.5, 5.0),
+        SpriteBundle {
+            texture: text_provider.get_image("Press enter", Color::YELLOW, &asset_server),
+            ..default()
+        },
+    ));
+}
+
+fn leave_bad_map(
+    key: Res<Input<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    gamepad: GamepadInput,
+    mut next_state: ResMut<NextState<AppState>>,
+) {
+    if key.just_pressed(bindings.confirm) || gamepad.confirm_just_pressed() {
+        next_state.set(AppState::MainMenu);
+    }
+}
+
+fn despawn_bad_map_screen(mut commands: Commands, query: Query<Entity, With<BadMapScreen>>) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn();
+    }
+}
+
 fn render_map(
     mut commands: Commands,
     asset_server: Res<AssetServer>,
     mut texture_atlases: ResMut<Assets<TextureAtlas>>,
+    levels: Res<Levels>,
+    map: Res<Map>,
 ) {
-    let map_center = Location::new(13.5, 15.0);
+    let map_center = map.center();
     let map_texture = asset_server.load("map.png");
     let texture_atlas =
         TextureAtlas::from_grid(map_texture, Vec2::new(226.0, 248.0), 28, 36, None, None);
@@ -56,7 +193,10 @@ fn render_map(
         map_center,
         SpriteSheetBundle {
             texture_atlas: texture_atlas_handle,
-            sprite: TextureAtlasSprite::new(0),
+            sprite: TextureAtlasSprite {
+                color: levels.maze_color(),
+                ..TextureAtlasSprite::new(0)
+            },
             transform: Transform::from_xyz(0.0, 0.0, Layers::Map.as_f32()),
             ..default()
         },
@@ -77,10 +217,11 @@ fn spawn_ready(
     mut commands: Commands,
     mut text_provider: ResMut<TextProvider>,
     asset_server: Res<AssetServer>,
+    map: Res<Map>,
 ) {
     commands.spawn((
         ReadySign,
-        Location::new(13.5, 13.0),
+        map.markers().bonus_symbol,
         SpriteBundle {
             texture: text_provider.get_image("READY!", Color::YELLOW, &asset_server),
             transform: Transform::from_xyz(0.0, 0.0, Layers::Map.as_f32() + 1.0),
@@ -96,31 +237,68 @@ fn remove_ready(mut commands: Commands, query: Query<Entity, With<ReadySign>>) {
     commands.entity(query.single()).despawn();
 }
 
+/// The image is drawn by `draw_countdown` once the state timer has started,
+/// to the right of READY!.
+fn spawn_countdown(mut commands: Commands, map: Res<Map>) {
+    commands.spawn((
+        Countdown,
+        map.markers().bonus_symbol + Location::new(4.0, 0.0),
+        SpriteBundle {
+            transform: Transform::from_xyz(0.0, 0.0, Layers::Map.as_f32() + 1.0),
+            ..default()
+        },
+    ));
+}
+
+fn draw_countdown(
+    mut query: Query<&mut Handle<Image>, With<Countdown>>,
+    timer: Res<StateTimer>,
+    mut text_provider: ResMut<TextProvider>,
+    asset_server: Res<AssetServer>,
+) {
+    let secs_left = (timer.0.remaining_secs().ceil() as u32).max(1);
+    for mut image in query.iter_mut() {
+        *image = text_provider.get_image(secs_left.to_string(), Color::YELLOW, &asset_server);
+    }
+}
+
+fn remove_countdown(mut commands: Commands, query: Query<Entity, With<Countdown>>) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn();
+    }
+}
+
 fn map_wrap(mut query: Query<&mut Location, Without<NoMapWrap>>, map: Res<Map>) {
-    query.par_iter_mut().for_each(|mut location| {
-        if location.x <= -2.0 {
-            let dif = location.x + 2.0;
-            location.x = map.width() as f32 + 1.0 + dif;
-        } else if location.x >= (map.width() as f32 + 1.0) {
-            let dif = location.x - (map.width() as f32 + 1.0);
-            location.x = -2.0 + dif;
-        }
+    query
+        .par_iter_mut()
+        .for_each(|mut location| wrap_around(&mut location, &map));
+}
 
-        if location.y <= -2.0 {
-            let dif = location.y + 2.0;
-            location.y = map.height() as f32 + 1.0 + dif;
-        } else if location.y == (map.height() as f32 + 1.0) {
-            let dif = location.y - (map.height() as f32 + 1.0);
-            location.y = -2.0 + dif;
-        }
-    });
+/// Moves a location that went two tiles past an edge of the map to the same
+/// distance past the opposite edge, keeping any overshoot.
+fn wrap_around(location: &mut Location, map: &Map) {
+    if location.x() <= -2.0 {
+        let dif = location.x() + 2.0;
+        location.set_x(map.width() as f32 + 1.0 + dif);
+    } else if location.x() >= (map.width() as f32 + 1.0) {
+        let dif = location.x() - (map.width() as f32 + 1.0);
+        location.set_x(-2.0 + dif);
+    }
+
+    if location.y() <= -2.0 {
+        let dif = location.y() + 2.0;
+        location.set_y(map.height() as f32 + 1.0 + dif);
+    } else if location.y() >= (map.height() as f32 + 1.0) {
+        let dif = location.y() - (map.height() as f32 + 1.0);
+        location.set_y(-2.0 + dif);
+    }
 }
 
 fn flash_map(
     timer: Res<StateTimer>,
     mut query: Query<&mut TextureAtlasSprite, With<MapComponent>>,
 ) {
-    if timer.0.elapsed_secs() >= 3.0 {
+    if timer.0.elapsed_secs() >= LEVEL_COMPLETE_JINGLE_SECS {
         let first_half_of_second = timer.0.elapsed().as_secs_f32().fract() < 0.5;
 
         let mut sprite = query.single_mut();
@@ -133,3 +311,57 @@ fn despawn(mut commands: Commands, query: Query<Entity, With<MapComponent>>) {
         commands.entity(entity).despawn();
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::map::Direction;
+
+    #[test]
+    fn tunnels_wrap_around_every_edge() {
+        let map = Map::parse(DEFAULT_MAP_TEXT).unwrap();
+        let width = map.width() as f32;
+        let height = map.height() as f32;
+
+        for direction in Direction::all() {
+            let start = Location::new(13.0, 14.0);
+            let mut location = start;
+
+            // Across the map and the two tiles past each edge, back to the start.
+            let tiles = match direction {
+                Direction::Left | Direction::Right => width + 3.0,
+                Direction::Up | Direction::Down => height + 3.0,
+            };
+            for _ in 0..(tiles / Location::ADVANCEMENT_DELTA) as usize {
+                location.advance(direction);
+                wrap_around(&mut location, &map);
+
+                assert!((-2.0..=width + 1.0).contains(&location.x()), "{location:?}");
+                assert!(
+                    (-2.0..=height + 1.0).contains(&location.y()),
+                    "{location:?}"
+                );
+            }
+
+            assert_eq!(location, start, "Wrapping {direction:?}");
+        }
+    }
+
+    #[test]
+    fn locations_stay_on_the_grid_through_many_wraps() {
+        let map = Map::parse(DEFAULT_MAP_TEXT).unwrap();
+        let start = Location::new(13.0, 16.0);
+        let mut location = start;
+
+        for step in 1..=20_000 {
+            location.advance(Direction::Right);
+            wrap_around(&mut location, &map);
+
+            assert_eq!(Location::from_vec(location.to_vec()), location);
+            assert_eq!(location.is_tile_center(), step % 8 == 0, "{location:?}");
+        }
+
+        // 20,000 steps are 2,500 tiles, 80 times around plus 20 tiles.
+        assert_eq!(location, Location::new(2.0, 16.0));
+    }
+}
@@ -1,7 +1,7 @@
 use std::mem::discriminant;
 
 use bevy::{
-    app::AppExit,
+    ecs::system::SystemParam,
     input::{keyboard::KeyboardInput, ButtonState},
     prelude::*,
 };
@@ -9,18 +9,42 @@ use bevy_kira_audio::prelude::*;
 use strum::{Display, EnumCount, EnumIter, IntoEnumIterator};
 
 use crate::{
-    common::{app_state::AppState, levels::Levels},
+    common::{app_state::AppState, game_config::GameConfig, levels::Levels},
+    ghost_strategy::GhostStrategies,
     init,
-    services::{map::Location, text::TextProvider},
+    quit_prompt::{answer_quit_prompt, QuitPrompt},
+    savegame::ContinueGame,
+    services::{
+        input::{GamepadInput, KeyBindings, PointerInput},
+        map::Location,
+        scores,
+        text::TextProvider,
+    },
+    settings_menu::{
+        draw_setting_values, spawn_setting_item, spawn_toggle, toggle_width, SettingItem,
+        SettingValues, ToggleState,
+    },
     StartGameSound,
 };
 
 #[derive(Component, Debug, EnumCount, EnumIter, Display, Clone, Copy)]
 #[allow(non_camel_case_types)]
 enum Menu {
+    Continue,
     Play,
+    Level_Select,
     Hard_Mode(bool),
+    Endless(bool),
+    Random_Timing(bool),
+    Nightmare(bool),
+    Lives,
+    Colorblind,
+    Pixel_Perfect,
+    Turn_Indicator,
+    Practice_Mode,
+    Volume,
     LeaderBoard,
+    Stats,
     Exit,
 }
 
@@ -28,6 +52,33 @@ enum Menu {
 struct MenuState {
     current: usize,
     options: [Menu; Menu::COUNT],
+    /// Whether there's a saved game, without which "Continue" is hidden.
+    can_continue: bool,
+}
+
+impl Menu {
+    /// The state of the menu's own toggles. The settings shared with the pause
+    /// screen are shown through `setting` instead.
+    fn toggle_state(&self) -> Option<bool> {
+        match self {
+            Menu::Hard_Mode(state)
+            | Menu::Endless(state)
+            | Menu::Random_Timing(state)
+            | Menu::Nightmare(state) => Some(*state),
+            _ => None,
+        }
+    }
+
+    fn setting(&self) -> Option<SettingItem> {
+        match self {
+            Menu::Colorblind => Some(SettingItem::Colorblind),
+            Menu::Pixel_Perfect => Some(SettingItem::Pixel_Perfect),
+            Menu::Turn_Indicator => Some(SettingItem::Turn_Indicator),
+            Menu::Practice_Mode => Some(SettingItem::Practice_Mode),
+            Menu::Volume => Some(SettingItem::Volume),
+            _ => None,
+        }
+    }
 }
 
 impl MenuState {
@@ -35,6 +86,21 @@ impl MenuState {
         self.options[self.current]
     }
 
+    fn is_shown(&self, item: Menu) -> bool {
+        !matches!(item, Menu::Continue) || self.can_continue
+    }
+
+    /// Moves the selection up or down, skipping hidden items.
+    fn step(&mut self, delta: i32) {
+        let len = self.options.len() as i32;
+        loop {
+            self.current = (self.current as i32 + delta).rem_euclid(len) as usize;
+            if self.is_shown(self.current()) {
+                break;
+            }
+        }
+    }
+
     fn set_current(&mut self, item: Menu) {
         let current = &mut self.options[self.current];
         assert!(discriminant(current) == discriminant(&item));
@@ -45,51 +111,126 @@ impl MenuState {
 #[derive(Component)]
 struct Arrow;
 
-#[derive(Component, Clone, Debug, Copy, Default, PartialEq)]
-enum Toggle {
-    On,
-    #[default]
-    Off,
+#[derive(Component)]
+struct TitleLogo;
+
+/// The best scores, shown in place of the logo every other turn while the menu
+/// is left alone, like the cabinet's attract loop.
+#[derive(Component)]
+struct HighScoreSnippet;
+
+/// Rows between menu items, in tiles.
+const ITEM_SPACING: f32 = 1.5;
+
+const SNIPPET_ENTRIES: usize = 5;
+/// Half the width of the snippet, in pixels, with names aligned to its left
+/// edge and scores to its right.
+const SNIPPET_HALF_WIDTH: f32 = 64.0;
+
+#[derive(Resource)]
+struct TitleCycle {
+    timer: Timer,
+    showing_scores: bool,
+}
+
+#[derive(Component)]
+struct LivesCount;
+
+/// The settings the menu changes, and whether the game it starts continues the
+/// saved one.
+#[derive(SystemParam)]
+struct MenuSettings<'w> {
+    levels: ResMut<'w, Levels>,
+    strategies: ResMut<'w, GhostStrategies>,
+    game_config: ResMut<'w, GameConfig>,
+    values: SettingValues<'w>,
+    continue_game: ResMut<'w, ContinueGame>,
 }
 
 #[derive(Resource)]
 struct InputDelayTimer(Timer);
 
+/// Set when the current item was clicked or tapped, to be handled like the
+/// confirm key.
+#[derive(Resource, Default)]
+struct PointerConfirm(bool);
+
 pub struct MenuPlugin;
 
 impl Plugin for MenuPlugin {
     fn build(&self, app: &mut App) {
         app.add_systems(OnEnter(AppState::MainMenu), setup_menu.after(init));
         app.add_systems(OnExit(AppState::MainMenu), despawn_menu);
-        app.add_systems(Update, update_menu.run_if(in_state(AppState::MainMenu)));
+        app.add_systems(
+            Update,
+            (pointer_input, update_menu, draw_lives_count, cycle_title)
+                .chain()
+                .after(answer_quit_prompt)
+                .before(draw_setting_values)
+                .run_if(in_state(AppState::MainMenu)),
+        );
         app.insert_resource(MenuState {
             current: 0,
             options: [
+                Menu::Continue,
                 Menu::Play,
+                Menu::Level_Select,
                 Menu::Hard_Mode(false),
+                Menu::Endless(false),
+                Menu::Random_Timing(false),
+                Menu::Nightmare(false),
+                Menu::Lives,
+                Menu::Colorblind,
+                Menu::Pixel_Perfect,
+                Menu::Turn_Indicator,
+                Menu::Practice_Mode,
+                Menu::Volume,
                 Menu::LeaderBoard,
+                Menu::Stats,
                 Menu::Exit,
             ],
+            can_continue: false,
+        });
+        app.insert_resource(PointerConfirm::default());
+        app.insert_resource(TitleCycle {
+            timer: Timer::from_seconds(5.0, TimerMode::Repeating),
+            showing_scores: false,
         });
         app.insert_resource(InputDelayTimer(Timer::from_seconds(0.1, TimerMode::Once)));
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn setup_menu(
     mut commands: Commands,
     mut text_provider: ResMut<TextProvider>,
     asset_server: Res<AssetServer>,
     mut selected_option: ResMut<MenuState>,
     levels: Res<Levels>,
+    strategies: Res<GhostStrategies>,
     mut input_delay_timer: ResMut<InputDelayTimer>,
+    mut title_cycle: ResMut<TitleCycle>,
+    game_config: Res<GameConfig>,
 ) {
-    selected_option.current = 0;
-    selected_option.options[1] = Menu::Hard_Mode(levels.hard_mode);
+    selected_option.can_continue = ContinueGame::available();
+    selected_option.current = if selected_option.can_continue { 0 } else { 1 };
+    for option in selected_option.options.iter_mut() {
+        match option {
+            Menu::Hard_Mode(state) => *state = levels.hard_mode,
+            Menu::Endless(state) => *state = levels.endless,
+            Menu::Random_Timing(state) => *state = levels.random_mode_timing,
+            Menu::Nightmare(state) => *state = strategies.nightmare(),
+            _ => (),
+        }
+    }
 
     input_delay_timer.0.reset();
+    title_cycle.timer.reset();
+    title_cycle.showing_scores = false;
 
     commands.spawn((
         Location::new(13.5, 23.0),
+        TitleLogo,
         SpriteBundle {
             texture: text_provider.get_image("PACMAN", Color::YELLOW, &asset_server),
             sprite: Sprite {
@@ -100,107 +241,274 @@ fn setup_menu(
         },
     ));
 
-    for (i, option) in Menu::iter().enumerate() {
+    let best_scores = scores::load_best_first(game_config.strict_scores());
+    if !best_scores.is_empty() {
+        spawn_high_score_snippet(
+            &mut commands,
+            &best_scores[..best_scores.len().min(SNIPPET_ENTRIES)],
+            &mut text_provider,
+            &asset_server,
+        );
+    }
+
+    // With "Continue" shown the items start a row higher to fit.
+    let top = if selected_option.can_continue {
+        21.0
+    } else {
+        19.0
+    };
+    let shown_options = Menu::iter().filter(|option| selected_option.is_shown(*option));
+    for (i, option) in shown_options.enumerate() {
         let option_name = option.to_string().replace("_", " ").to_uppercase();
-        commands
-            .spawn((
-                Location::new(13.5, 17.0 - (2 * i) as f32),
-                SpatialBundle::default(),
-                option,
-            ))
-            .with_children(|parent| {
-                let arrow_location = Vec2::new(-3.0 - 8.0 * (option_name.len() as f32 / 2.0), 0.0);
+        let mut item_entity = commands.spawn((
+            Location::new(13.5, top - ITEM_SPACING * i as f32),
+            SpatialBundle::default(),
+            option,
+        ));
+
+        if let Some(setting) = option.setting() {
+            spawn_setting_item(&mut item_entity, setting, &mut text_provider, &asset_server);
+        } else if option.toggle_state().is_some() {
+            item_entity.insert(ToggleState::default());
+        }
+
+        item_entity.with_children(|parent| {
+            let arrow_location = Vec2::new(-3.0 - 8.0 * (option_name.len() as f32 / 2.0), 0.0);
+            parent.spawn((
+                Arrow,
+                SpriteBundle {
+                    texture: asset_server.load("select_arrow.png"),
+                    transform: Transform::from_translation(arrow_location.extend(0.0)),
+                    ..default()
+                },
+            ));
+
+            if option.setting().is_some() {
+                return;
+            }
+
+            parent.spawn(SpriteBundle {
+                texture: text_provider.get_image(&option_name, Color::WHITE, &asset_server),
+                ..default()
+            });
+
+            if option.toggle_state().is_some() {
+                spawn_toggle(parent, &option_name, &mut text_provider, &asset_server);
+            }
+
+            if let Menu::Lives = option {
+                let count_location = Vec2::new(8.0 * ((option_name.len() + 2) as f32 / 2.0), 0.0);
                 parent.spawn((
-                    Arrow,
+                    LivesCount,
                     SpriteBundle {
-                        texture: asset_server.load("select_arrow.png"),
-                        transform: Transform::from_translation(arrow_location.extend(0.0)),
+                        transform: Transform::from_translation(count_location.extend(0.0)),
                         ..default()
                     },
                 ));
+            }
+        });
+    }
+}
+
+fn spawn_high_score_snippet(
+    commands: &mut Commands,
+    entries: &[scores::ScoreEntry],
+    text_provider: &mut TextProvider,
+    asset_server: &AssetServer,
+) {
+    commands
+        .spawn((
+            Location::new(13.5, 31.0),
+            HighScoreSnippet,
+            SpatialBundle {
+                visibility: Visibility::Hidden,
+                ..default()
+            },
+        ))
+        .with_children(|parent| {
+            parent.spawn(SpriteBundle {
+                texture: text_provider.get_image("High Scores", Color::YELLOW, asset_server),
+                ..default()
+            });
+
+            for (i, (name, score, _)) in entries.iter().enumerate() {
+                let y = -12.0 * (i + 1) as f32 - 4.0;
 
+                let name = format!("{}. {}", i + 1, name);
+                let name_x = -SNIPPET_HALF_WIDTH + text_provider.get_size(&name).x / 2.0;
                 parent.spawn(SpriteBundle {
-                    texture: text_provider.get_image(&option_name, Color::WHITE, &asset_server),
+                    texture: text_provider.get_image(&name, Color::WHITE, asset_server),
+                    transform: Transform::from_xyz(name_x, y, 0.0),
                     ..default()
                 });
 
-                if let Menu::Hard_Mode(_) = option {
-                    let on_location = Vec2::new(8.0 * ((option_name.len() + 4) as f32 / 2.0), 0.0);
-                    parent.spawn((
-                        Toggle::On,
-                        SpriteBundle {
-                            texture: text_provider.get_image("ON", Color::GREEN, &asset_server),
-                            transform: Transform::from_translation(on_location.extend(0.0)),
-                            ..default()
-                        },
-                    ));
-
-                    let off_location =
-                        Vec2::new(8.0 * ((option_name.len() + 5) as f32 / 2.0) - 0.5, 0.0);
-                    parent.spawn((
-                        Toggle::Off,
-                        SpriteBundle {
-                            texture: text_provider.get_image("OFF", Color::RED, &asset_server),
-                            transform: Transform::from_translation(off_location.extend(0.0)),
-                            ..default()
-                        },
-                    ));
-                }
-            });
-    }
+                let score_x = SNIPPET_HALF_WIDTH - text_provider.get_size(score).x / 2.0;
+                parent.spawn(SpriteBundle {
+                    texture: text_provider.get_image(score, Color::WHITE, asset_server),
+                    transform: Transform::from_xyz(score_x, y, 0.0),
+                    ..default()
+                });
+            }
+        });
+}
+
+/// Moving the mouse over an item selects it, and clicking or tapping it also
+/// confirms it.
+fn pointer_input(
+    mut menu_state: ResMut<MenuState>,
+    mut pointer_confirm: ResMut<PointerConfirm>,
+    mut pointer: PointerInput,
+    query: Query<(&Menu, &Location)>,
+    text_provider: Res<TextProvider>,
+) {
+    let pressed = pointer.just_pressed();
+    let Some(position) = pressed.or(pointer.moved()) else {
+        return;
+    };
+
+    let Some((menu, _)) = query
+        .iter()
+        .find(|(menu, location)| item_bounds(**menu, location, &text_provider).contains(position))
+    else {
+        return;
+    };
+
+    menu_state.current = menu_state
+        .options
+        .iter()
+        .position(|option| discriminant(option) == discriminant(menu))
+        .expect("Menu item not found");
+    pointer_confirm.0 = pressed.is_some();
+}
+
+/// The item's name along with the arrow to its left and the setting shown to
+/// its right.
+fn item_bounds(menu: Menu, location: &Location, text_provider: &TextProvider) -> Rect {
+    let option_name = menu.to_string().replace("_", " ").to_uppercase();
+    let name_size = text_provider.get_size(&option_name);
+
+    let setting_width = match (menu, menu.setting()) {
+        (_, Some(setting)) => setting.value_width(text_provider),
+        _ if menu.toggle_state().is_some() => toggle_width(text_provider),
+        (Menu::Lives, _) => text_provider.get_size("0").x + 8.0,
+        _ => 0.0,
+    };
+
+    let center = location.world_position();
+    Rect::new(
+        center.x - name_size.x / 2.0 - 8.0,
+        center.y - ITEM_SPACING * 4.0,
+        center.x + name_size.x / 2.0 + setting_width,
+        center.y + ITEM_SPACING * 4.0,
+    )
 }
 
+#[allow(clippy::too_many_arguments)]
 fn update_menu(
     mut menu_state: ResMut<MenuState>,
     mut next_state: ResMut<NextState<AppState>>,
-    mut levels: ResMut<Levels>,
+    mut settings: MenuSettings,
     mut key_event: EventReader<KeyboardInput>,
-    query: Query<(&Menu, &Children)>,
+    mut gamepad: GamepadInput,
+    bindings: Res<KeyBindings>,
+    mut query: Query<(&Menu, &Children, Option<&mut ToggleState>)>,
     mut query_arrow: Query<&mut Visibility, With<Arrow>>,
-    mut query_toggle: Query<(&Toggle, &mut Visibility), Without<Arrow>>,
-    mut exit_event: EventWriter<AppExit>,
+    mut quit_prompt: ResMut<QuitPrompt>,
     mut input_delay_timer: ResMut<InputDelayTimer>,
     time: Res<Time>,
     asset_server: Res<AssetServer>,
     audio: Res<Audio>,
     mut start_game_sound: ResMut<StartGameSound>,
+    mut pointer_confirm: ResMut<PointerConfirm>,
 ) {
-    if !input_delay_timer.0.tick(time.delta()).finished() {
-        key_event.clear();
+    let mut pressed_keys = key_event
+        .read()
+        .filter(|event| event.state == ButtonState::Pressed)
+        .filter_map(|event| event.key_code)
+        .collect::<Vec<_>>();
+    pressed_keys.extend(gamepad.just_pressed_keys());
+
+    if pointer_confirm.0 {
+        pressed_keys.push(bindings.confirm);
+        pointer_confirm.0 = false;
     }
 
-    for event in key_event.read() {
-        if event.state != ButtonState::Pressed {
-            continue;
-        }
+    let input_delay_finished = input_delay_timer.0.tick(time.delta()).finished();
+    if !input_delay_finished || quit_prompt.is_open() || quit_prompt.is_changed() {
+        pressed_keys.clear();
+    }
 
-        match event.key_code {
-            Some(KeyCode::Up) => {
-                menu_state.current = (menu_state.current as i32 - 1)
-                    .rem_euclid(menu_state.options.len() as i32)
-                    as usize;
-            }
-            Some(KeyCode::Down) => {
-                menu_state.current = (menu_state.current as i32 + 1)
-                    .rem_euclid(menu_state.options.len() as i32)
-                    as usize;
-            }
-            Some(KeyCode::Return) => match menu_state.current() {
+    for key_code in pressed_keys {
+        match key_code {
+            key if key == bindings.up => menu_state.step(-1),
+            key if key == bindings.down => menu_state.step(1),
+            key if key == bindings.left || key == bindings.right => match menu_state.current() {
+                Menu::Lives => {
+                    let lives = settings.game_config.starting_lives();
+                    let lives = if key == bindings.left {
+                        lives.saturating_sub(1)
+                    } else {
+                        lives + 1
+                    };
+                    settings.game_config.set_starting_lives(lives);
+                }
+                item => {
+                    if let Some(setting) = item.setting() {
+                        settings.values.adjust(setting, key == bindings.right);
+                    }
+                }
+            },
+            key if key == bindings.confirm => match menu_state.current() {
+                Menu::Continue => {
+                    settings.continue_game.0 = true;
+                    next_state.set(AppState::LevelStart);
+                    start_game_sound.0 = audio
+                        .play(asset_server.load("sounds/game_start.wav"))
+                        .handle();
+                }
                 Menu::Play => {
                     next_state.set(AppState::LevelStart);
                     start_game_sound.0 = audio
                         .play(asset_server.load("sounds/game_start.wav"))
                         .handle();
                 }
+                Menu::Level_Select => {
+                    next_state.set(AppState::LevelSelect);
+                }
                 Menu::Hard_Mode(state) => {
                     menu_state.set_current(Menu::Hard_Mode(!state));
-                    levels.hard_mode = !state;
+                    settings.levels.hard_mode = !state;
+                }
+                Menu::Endless(state) => {
+                    menu_state.set_current(Menu::Endless(!state));
+                    settings.levels.endless = !state;
+                }
+                Menu::Random_Timing(state) => {
+                    menu_state.set_current(Menu::Random_Timing(!state));
+                    settings.levels.random_mode_timing = !state;
+                }
+                Menu::Nightmare(state) => {
+                    menu_state.set_current(Menu::Nightmare(!state));
+                    settings.strategies.set_nightmare(!state);
+                }
+                Menu::Lives => {}
+                item @ (Menu::Colorblind
+                | Menu::Pixel_Perfect
+                | Menu::Turn_Indicator
+                | Menu::Practice_Mode
+                | Menu::Volume) => {
+                    settings
+                        .values
+                        .confirm(item.setting().expect("Setting item without a setting"));
                 }
                 Menu::LeaderBoard => {
                     next_state.set(AppState::Leaderboard);
                 }
+                Menu::Stats => {
+                    next_state.set(AppState::Stats);
+                }
                 Menu::Exit => {
-                    exit_event.send(AppExit);
+                    quit_prompt.open();
                 }
             },
             _ => {}
@@ -208,31 +516,74 @@ fn update_menu(
     }
 
     for (i, option) in menu_state.options.iter().enumerate() {
-        let (_, children) = query
-            .iter()
-            .find(|(menu, _)| discriminant(*menu) == discriminant(option))
+        if !menu_state.is_shown(*option) {
+            continue;
+        }
+
+        let (_, children, toggle_state) = query
+            .iter_mut()
+            .find(|(menu, _, _)| discriminant(*menu) == discriminant(option))
             .expect("Menu item not found");
 
-        for child in children.iter() {
-            if let Ok(mut visibility) = query_arrow.get_mut(*child) {
-                *visibility = if i == menu_state.current {
-                    Visibility::Visible
-                } else {
-                    Visibility::Hidden
-                };
-            } else if let Ok((toggle, mut visibility)) = query_toggle.get_mut(*child) {
-                let item_state = if let Menu::Hard_Mode(state) = option {
-                    state
-                } else {
-                    unreachable!();
-                };
-
-                *visibility = match (toggle, item_state) {
-                    (Toggle::On, true) | (Toggle::Off, false) => Visibility::Visible,
-                    _ => Visibility::Hidden,
-                };
-            }
+        if let (Some(mut toggle_state), Some(state)) = (toggle_state, option.toggle_state()) {
+            toggle_state.0 = state;
         }
+
+        let mut arrows = query_arrow.iter_many_mut(children);
+        while let Some(mut visibility) = arrows.fetch_next() {
+            *visibility = if i == menu_state.current {
+                Visibility::Visible
+            } else {
+                Visibility::Hidden
+            };
+        }
+    }
+}
+
+fn draw_lives_count(
+    mut query: Query<&mut Handle<Image>, With<LivesCount>>,
+    game_config: Res<GameConfig>,
+    mut text_provider: ResMut<TextProvider>,
+    asset_server: Res<AssetServer>,
+) {
+    for mut image in query.iter_mut() {
+        *image = text_provider.get_image(game_config.starting_lives(), Color::WHITE, &asset_server);
+    }
+}
+
+/// Swaps the logo and the best scores on a timer, going back to the logo on any
+/// key, button or click.
+fn cycle_title(
+    mut title_cycle: ResMut<TitleCycle>,
+    mut logo_query: Query<&mut Visibility, (With<TitleLogo>, Without<HighScoreSnippet>)>,
+    mut snippet_query: Query<&mut Visibility, With<HighScoreSnippet>>,
+    keys: Res<Input<KeyCode>>,
+    gamepad_buttons: Res<Input<GamepadButton>>,
+    mouse_buttons: Res<Input<MouseButton>>,
+    time: Res<Time>,
+) {
+    let Ok(mut snippet_visibility) = snippet_query.get_single_mut() else {
+        return;
+    };
+
+    let any_input = keys.get_just_pressed().next().is_some()
+        || gamepad_buttons.get_just_pressed().next().is_some()
+        || mouse_buttons.get_just_pressed().next().is_some();
+    if any_input {
+        title_cycle.timer.reset();
+        title_cycle.showing_scores = false;
+    } else if title_cycle.timer.tick(time.delta()).just_finished() {
+        title_cycle.showing_scores = !title_cycle.showing_scores;
+    }
+
+    let (logo, snippet) = if title_cycle.showing_scores {
+        (Visibility::Hidden, Visibility::Inherited)
+    } else {
+        (Visibility::Inherited, Visibility::Hidden)
+    };
+    *snippet_visibility = snippet;
+    for mut visibility in logo_query.iter_mut() {
+        *visibility = logo;
     }
 }
 
@@ -0,0 +1,111 @@
+use bevy::prelude::*;
+
+use crate::{
+    common::{layers::Layers, levels::Levels},
+    services::{files::asset_path, map::Map, text::TextProvider},
+};
+
+const MISSING_ASSETS_TEXT: &str = "MISSING ASSETS";
+
+/// Every file in `assets` the game loads by name, other than the bonus symbols
+/// which come from their table. A missing image loads as an invisible sprite and
+/// a missing sound as silence, so they're checked once at startup instead.
+const REQUIRED_ASSETS: &[&str] = &[
+    "map.png",
+    "map_outer_mask.png",
+    "icon.png",
+    "pacman.png",
+    "death_animation.png",
+    "select_arrow.png",
+    "pellet.png",
+    "power_pellet.png",
+    "blinky_body.png",
+    "pinky_body.png",
+    "inky_body.png",
+    "clyde_body.png",
+    "ghost_eyes.png",
+    "ghosts_frite.png",
+    "ghosts_death_points_200.png",
+    "ghosts_death_points_400.png",
+    "ghosts_death_points_800.png",
+    "ghosts_death_points_1600.png",
+    "sounds/game_start.wav",
+    "sounds/munch_1.wav",
+    "sounds/munch_2.wav",
+    "sounds/siren_1.wav",
+    "sounds/siren_2.wav",
+    "sounds/siren_3.wav",
+    "sounds/siren_4.wav",
+    "sounds/siren_5.wav",
+    "sounds/ghost_going_home.wav",
+    "sounds/ghosts_frite.wav",
+    "sounds/level_complete.wav",
+    "sounds/eat_ghost.wav",
+    "sounds/eat_fruit.wav",
+    "sounds/gain_life.wav",
+    "sounds/death_1.wav",
+    "sounds/death_2.wav",
+];
+
+/// The required assets that weren't found at startup.
+#[derive(Resource, Default)]
+struct MissingAssets(Vec<String>);
+
+#[derive(Component)]
+struct MissingAssetsSign;
+
+pub struct MissingAssetsPlugin;
+
+impl Plugin for MissingAssetsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<MissingAssets>();
+        app.add_systems(Startup, check_assets);
+        app.add_systems(
+            Update,
+            draw_missing_assets.run_if(|missing: Res<MissingAssets>| !missing.0.is_empty()),
+        );
+    }
+}
+
+fn check_assets(mut missing: ResMut<MissingAssets>, levels: Res<Levels>) {
+    let bonus_symbol_assets = levels.bonus_symbol_assets();
+    missing.0 = REQUIRED_ASSETS
+        .iter()
+        .copied()
+        .chain(bonus_symbol_assets.iter().map(String::as_str))
+        .filter(|asset| !asset_path(asset).exists())
+        .map(str::to_string)
+        .collect();
+
+    if !missing.0.is_empty() {
+        error!(
+            "Missing assets in {}: {}",
+            asset_path("").display(),
+            missing.0.join(", ")
+        );
+    }
+}
+
+/// Shown over the middle of the maze. Screens despawn everything with a
+/// location when they close, so the sign is put back whenever it's gone.
+fn draw_missing_assets(
+    mut commands: Commands,
+    query: Query<(), With<MissingAssetsSign>>,
+    map: Res<Map>,
+    mut text_provider: ResMut<TextProvider>,
+    asset_server: Res<AssetServer>,
+) {
+    if !query.is_empty() {
+        return;
+    }
+
+    commands.spawn((
+        MissingAssetsSign,
+        map.center(),
+        SpriteBundle {
+            texture: text_provider.get_image(MISSING_ASSETS_TEXT, Color::RED, &asset_server),
+            transform: Transform::from_xyz(0.0, 0.0, Layers::HUD.as_f32() + 2.0),
+            ..default()
+        },
+    ));
+}
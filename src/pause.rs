@@ -0,0 +1,437 @@
+use bevy::prelude::*;
+use bevy_kira_audio::prelude::*;
+use strum::{EnumCount, IntoEnumIterator};
+
+use crate::common::{
+    app_state::{AppState, PauseState},
+    layers::Layers,
+};
+use crate::services::{
+    input::{GamepadInput, KeyBindings, PointerInput},
+    map::Location,
+    text::TextProvider,
+};
+use crate::settings_menu::{draw_setting_values, spawn_setting_item, SettingItem, SettingValues};
+use crate::LeaveScreen;
+
+const PAUSE_ITEMS: [PauseItem; 3] = [PauseItem::Resume, PauseItem::Options, PauseItem::QuitToMenu];
+/// Distance between the lines of the pause overlay, in pixels.
+const LINE_HEIGHT: f32 = 12.0;
+
+#[derive(Component)]
+struct PauseSign;
+
+#[derive(Component, Clone, Copy, PartialEq)]
+enum PauseItem {
+    Resume,
+    Options,
+    QuitToMenu,
+}
+
+impl PauseItem {
+    fn text(&self) -> &'static str {
+        match self {
+            PauseItem::Resume => "RESUME",
+            PauseItem::Options => "OPTIONS",
+            PauseItem::QuitToMenu => "QUIT TO MENU",
+        }
+    }
+
+    /// Position relative to the overlay's center, the title taking the line
+    /// above the first item.
+    fn offset(&self) -> Vec2 {
+        let index = PAUSE_ITEMS.iter().position(|item| item == self).unwrap();
+        line_offset(index + 1, PAUSE_ITEMS.len() + 1)
+    }
+}
+
+/// Position of `line` on a sign of `lines` lines, relative to its center.
+fn line_offset(line: usize, lines: usize) -> Vec2 {
+    Vec2::new(0.0, LINE_HEIGHT * ((lines - 1) as f32 / 2.0 - line as f32))
+}
+
+#[derive(Component)]
+struct PauseArrow;
+
+#[derive(Resource, Default)]
+struct PauseSelection(usize);
+
+/// The settings shared with the main menu, followed by a line to go back to
+/// the pause overlay.
+#[derive(Component)]
+struct OptionsSign;
+
+#[derive(Component)]
+struct OptionsArrow;
+
+#[derive(Resource, Default)]
+struct OptionsSelection(usize);
+
+/// Where the options line `index` is, relative to the options sign. The title
+/// comes before the first line, and the last line, after the settings, is
+/// "BACK".
+fn options_line_offset(index: usize) -> Vec2 {
+    line_offset(index + 1, SettingItem::COUNT + 2)
+}
+
+/// The settings' names are centered a bit to the left, leaving room for their
+/// values.
+fn setting_offset(item: SettingItem, text_provider: &TextProvider) -> Vec2 {
+    let index = SettingItem::iter().position(|other| other == item).unwrap();
+    options_line_offset(index) - Vec2::new(item.value_width(text_provider) / 2.0, 0.0)
+}
+
+pub struct PausePlugin;
+
+impl Plugin for PausePlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(PauseSelection::default());
+        app.insert_resource(OptionsSelection::default());
+        app.add_systems(Update, toggle_pause.run_if(in_state(AppState::MainGame)));
+        app.add_systems(Update, pause_menu.run_if(in_state(PauseState::Paused)));
+        app.add_systems(
+            Update,
+            options_menu
+                .before(draw_setting_values)
+                .run_if(in_state(PauseState::Options)),
+        );
+        app.add_systems(OnEnter(PauseState::Paused), spawn_pause_sign);
+        app.add_systems(OnExit(PauseState::Paused), despawn_pause_sign);
+        app.add_systems(OnEnter(PauseState::Options), spawn_options_sign);
+        app.add_systems(OnExit(PauseState::Options), despawn_options_sign);
+        app.add_systems(
+            OnTransition {
+                from: PauseState::Running,
+                to: PauseState::Paused,
+            },
+            reset_pause_selection,
+        );
+        app.add_systems(OnExit(PauseState::Running), pause_audio);
+        app.add_systems(OnEnter(PauseState::Running), resume_audio);
+        app.add_systems(OnExit(AppState::MainGame), unpause);
+        app.add_systems(OnEnter(AppState::GameOver), unpause);
+        app.add_systems(OnEnter(AppState::MainMenu), unpause);
+    }
+}
+
+fn toggle_pause(
+    key: Res<Input<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    state: Res<State<PauseState>>,
+    mut next_state: ResMut<NextState<PauseState>>,
+    mut leave_screen: LeaveScreen,
+) {
+    if !key.any_just_pressed([bindings.pause, KeyCode::Space]) {
+        return;
+    }
+
+    match state.get() {
+        PauseState::Running => next_state.set(PauseState::Paused),
+        PauseState::Paused | PauseState::Options => {
+            leave_screen.resume();
+            next_state.set(PauseState::Running);
+        }
+    }
+}
+
+/// Up and down pick an item, confirm or a click runs it.
+#[allow(clippy::too_many_arguments)]
+fn pause_menu(
+    mut selection: ResMut<PauseSelection>,
+    key: Res<Input<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    mut gamepad: GamepadInput,
+    mut pointer: PointerInput,
+    mut next_state: ResMut<NextState<PauseState>>,
+    mut leave_screen: LeaveScreen,
+    sign_query: Query<&Location, With<PauseSign>>,
+    mut arrow_query: Query<&mut Transform, With<PauseArrow>>,
+    text_provider: Res<TextProvider>,
+) {
+    let Ok(sign_location) = sign_query.get_single() else {
+        return;
+    };
+
+    let mut pressed_keys = key.get_just_pressed().copied().collect::<Vec<_>>();
+    pressed_keys.extend(gamepad.just_pressed_keys());
+
+    let pressed = pointer.just_pressed();
+    if let Some(position) = pressed.or(pointer.moved()) {
+        let hovered = PAUSE_ITEMS.iter().position(|item| {
+            Rect::from_center_size(
+                sign_location.world_position() + item.offset(),
+                text_provider.get_size(item.text()) + Vec2::new(16.0, 4.0),
+            )
+            .contains(position)
+        });
+        if let Some(hovered) = hovered {
+            selection.0 = hovered;
+            if pressed.is_some() {
+                pressed_keys.push(bindings.confirm);
+            }
+        }
+    }
+
+    for key_code in pressed_keys {
+        match key_code {
+            key if key == bindings.up => {
+                selection.0 = (selection.0 + PAUSE_ITEMS.len() - 1) % PAUSE_ITEMS.len();
+            }
+            key if key == bindings.down => {
+                selection.0 = (selection.0 + 1) % PAUSE_ITEMS.len();
+            }
+            key if key == bindings.confirm => match PAUSE_ITEMS[selection.0] {
+                PauseItem::Resume => {
+                    leave_screen.resume();
+                    next_state.set(PauseState::Running);
+                }
+                PauseItem::Options => {
+                    next_state.set(PauseState::Options);
+                }
+                PauseItem::QuitToMenu => {
+                    leave_screen.go_to(AppState::MainMenu);
+                    next_state.set(PauseState::Running);
+                }
+            },
+            _ => {}
+        }
+    }
+
+    let item = PAUSE_ITEMS[selection.0];
+    for mut transform in arrow_query.iter_mut() {
+        let text_width = text_provider.get_size(item.text()).x;
+        let arrow_location = item.offset() + Vec2::new(-text_width / 2.0 - 6.0, 0.0);
+        transform.translation = arrow_location.extend(transform.translation.z);
+    }
+}
+
+/// Same controls as the pause overlay, plus left and right for the volume.
+#[allow(clippy::too_many_arguments)]
+fn options_menu(
+    mut selection: ResMut<OptionsSelection>,
+    key: Res<Input<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    mut gamepad: GamepadInput,
+    mut pointer: PointerInput,
+    mut next_state: ResMut<NextState<PauseState>>,
+    mut values: SettingValues,
+    sign_query: Query<&Location, With<OptionsSign>>,
+    mut arrow_query: Query<&mut Transform, With<OptionsArrow>>,
+    text_provider: Res<TextProvider>,
+) {
+    let Ok(sign_location) = sign_query.get_single() else {
+        return;
+    };
+
+    let lines = SettingItem::COUNT + 1;
+    let settings = SettingItem::iter().collect::<Vec<_>>();
+    // Each line's center and size, with the arrow and value included.
+    let line_bounds = |index: usize| match settings.get(index) {
+        Some(item) => {
+            let name_size = text_provider.get_size(item.name());
+            let width = name_size.x + item.value_width(&text_provider) + 16.0;
+            let center = setting_offset(*item, &text_provider)
+                + Vec2::new(item.value_width(&text_provider) / 2.0, 0.0);
+            (center, Vec2::new(width, name_size.y + 4.0))
+        }
+        None => (
+            options_line_offset(index),
+            text_provider.get_size("BACK") + Vec2::new(16.0, 4.0),
+        ),
+    };
+
+    let mut pressed_keys = key.get_just_pressed().copied().collect::<Vec<_>>();
+    pressed_keys.extend(gamepad.just_pressed_keys());
+
+    let pressed = pointer.just_pressed();
+    if let Some(position) = pressed.or(pointer.moved()) {
+        let hovered = (0..lines).position(|index| {
+            let (center, size) = line_bounds(index);
+            Rect::from_center_size(sign_location.world_position() + center, size).contains(position)
+        });
+        if let Some(hovered) = hovered {
+            selection.0 = hovered;
+            if pressed.is_some() {
+                pressed_keys.push(bindings.confirm);
+            }
+        }
+    }
+
+    for key_code in pressed_keys {
+        let current = settings.get(selection.0).copied();
+        match key_code {
+            key if key == bindings.up => {
+                selection.0 = (selection.0 + lines - 1) % lines;
+            }
+            key if key == bindings.down => {
+                selection.0 = (selection.0 + 1) % lines;
+            }
+            key if key == bindings.left || key == bindings.right => {
+                if let Some(item) = current {
+                    values.adjust(item, key == bindings.right);
+                }
+            }
+            key if key == bindings.confirm => match current {
+                Some(item) => values.confirm(item),
+                None => next_state.set(PauseState::Paused),
+            },
+            _ => {}
+        }
+    }
+
+    let (center, size) = line_bounds(selection.0);
+    for mut transform in arrow_query.iter_mut() {
+        let arrow_location = center + Vec2::new(-size.x / 2.0 + 2.0, 0.0);
+        transform.translation = arrow_location.extend(transform.translation.z);
+    }
+}
+
+fn unpause(mut next_state: ResMut<NextState<PauseState>>) {
+    next_state.set(PauseState::Running);
+}
+
+fn reset_pause_selection(mut selection: ResMut<PauseSelection>) {
+    selection.0 = 0;
+}
+
+fn spawn_pause_sign(
+    mut commands: Commands,
+    mut text_provider: ResMut<TextProvider>,
+    asset_server: Res<AssetServer>,
+) {
+    let widest_item = PAUSE_ITEMS
+        .iter()
+        .map(|item| text_provider.get_size(item.text()).x)
+        .fold(0.0, f32::max);
+    let lines = PAUSE_ITEMS.len() + 1;
+    let title_location = line_offset(0, lines);
+
+    commands
+        .spawn((
+            PauseSign,
+            Location::new(13.5, 12.0),
+            SpriteBundle {
+                sprite: Sprite {
+                    color: Color::BLACK,
+                    custom_size: Some(Vec2::new(
+                        widest_item + 24.0,
+                        LINE_HEIGHT * lines as f32 + 8.0,
+                    )),
+                    ..default()
+                },
+                transform: Transform::from_xyz(0.0, 0.0, Layers::HUD.as_f32()),
+                ..default()
+            },
+        ))
+        .with_children(|parent| {
+            parent.spawn(SpriteBundle {
+                texture: text_provider.get_image("PAUSED", Color::YELLOW, &asset_server),
+                transform: Transform::from_translation(title_location.extend(0.5)),
+                ..default()
+            });
+
+            for item in PAUSE_ITEMS {
+                parent.spawn((
+                    item,
+                    SpriteBundle {
+                        texture: text_provider.get_image(item.text(), Color::WHITE, &asset_server),
+                        transform: Transform::from_translation(item.offset().extend(0.5)),
+                        ..default()
+                    },
+                ));
+            }
+
+            parent.spawn((
+                PauseArrow,
+                SpriteBundle {
+                    texture: asset_server.load("select_arrow.png"),
+                    transform: Transform::from_xyz(0.0, 0.0, 0.5),
+                    ..default()
+                },
+            ));
+        });
+}
+
+fn despawn_pause_sign(mut commands: Commands, query: Query<Entity, With<PauseSign>>) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+fn spawn_options_sign(
+    mut commands: Commands,
+    mut text_provider: ResMut<TextProvider>,
+    asset_server: Res<AssetServer>,
+    mut selection: ResMut<OptionsSelection>,
+) {
+    selection.0 = 0;
+
+    let widest_item = SettingItem::iter()
+        .map(|item| text_provider.get_size(item.name()).x + item.value_width(&text_provider))
+        .fold(0.0, f32::max);
+    let lines = SettingItem::COUNT + 2;
+    let title_location = line_offset(0, lines);
+    let back_location = options_line_offset(SettingItem::COUNT);
+
+    commands
+        .spawn((
+            OptionsSign,
+            Location::new(13.5, 12.0),
+            SpriteBundle {
+                sprite: Sprite {
+                    color: Color::BLACK,
+                    custom_size: Some(Vec2::new(
+                        widest_item + 24.0,
+                        LINE_HEIGHT * lines as f32 + 8.0,
+                    )),
+                    ..default()
+                },
+                transform: Transform::from_xyz(0.0, 0.0, Layers::HUD.as_f32()),
+                ..default()
+            },
+        ))
+        .with_children(|parent| {
+            parent.spawn(SpriteBundle {
+                texture: text_provider.get_image("OPTIONS", Color::YELLOW, &asset_server),
+                transform: Transform::from_translation(title_location.extend(0.5)),
+                ..default()
+            });
+
+            for item in SettingItem::iter() {
+                let offset = setting_offset(item, &text_provider);
+                let mut item_entity = parent.spawn(SpatialBundle::from_transform(
+                    Transform::from_translation(offset.extend(0.5)),
+                ));
+                spawn_setting_item(&mut item_entity, item, &mut text_provider, &asset_server);
+            }
+
+            parent.spawn(SpriteBundle {
+                texture: text_provider.get_image("BACK", Color::WHITE, &asset_server),
+                transform: Transform::from_translation(back_location.extend(0.5)),
+                ..default()
+            });
+
+            parent.spawn((
+                OptionsArrow,
+                SpriteBundle {
+                    texture: asset_server.load("select_arrow.png"),
+                    transform: Transform::from_xyz(0.0, 0.0, 0.5),
+                    ..default()
+                },
+            ));
+        });
+}
+
+fn despawn_options_sign(mut commands: Commands, query: Query<Entity, With<OptionsSign>>) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+fn pause_audio(audio: Res<Audio>) {
+    audio.pause();
+}
+
+fn resume_audio(audio: Res<Audio>) {
+    audio.resume();
+}
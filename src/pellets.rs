@@ -1,14 +1,14 @@
 use bevy::prelude::*;
 use bevy_kira_audio::prelude::*;
 
-use crate::common::app_state::AppState;
-use crate::common::events::{PelletEaten, PlayerAt};
+use crate::common::app_state::{AppState, DeadState};
+use crate::common::events::{LevelComplete, PelletEaten, PlayerAt};
 use crate::common::layers::Layers;
-use crate::common::sets::GameLoop::Collisions;
-use crate::services::map::Location;
+use crate::common::sets::{GameLoop::Collisions, Rendering};
+use crate::services::map::{Location, Map};
 
 #[derive(Component, Copy, Clone)]
-enum PelletType {
+pub enum PelletType {
     Regular,
     Power,
 }
@@ -16,9 +16,24 @@ enum PelletType {
 #[derive(Resource, Default)]
 pub struct TotalPellets(pub usize);
 
+/// Pellets eaten so far in the current level. Updated once per tick by
+/// `remove_pellets`, everything else only reads it.
+#[derive(Resource, Default)]
+pub struct PelletsEaten(pub usize);
+
+/// The pellets left on the board in a game being continued. The next level
+/// start only spawns these, counting the others as already eaten.
+#[derive(Resource, Default)]
+pub struct SavedPellets(pub Option<Vec<Location>>);
+
 #[derive(Resource)]
 struct PowerPelletFlashTimer(Timer);
 
+/// More munches than this in a single tick would only play over each other.
+const MAX_MUNCHES_PER_TICK: usize = 2;
+
+/// The two munch sounds, played one after the other for each regular pellet.
+/// The alternation restarts from the first with every level and life.
 #[derive(Resource)]
 struct MunchSounds {
     audio_handles: [Handle<AudioSource>; 2],
@@ -30,8 +45,9 @@ pub struct PelletsPlugin;
 impl Plugin for PelletsPlugin {
     fn build(&self, app: &mut App) {
         app.add_systems(OnEnter(AppState::LevelStart), spawn_pellets);
+        app.add_systems(OnEnter(DeadState::Restart), reset_munch_sounds);
         app.add_systems(FixedUpdate, remove_pellets.in_set(Collisions));
-        app.add_systems(Update, flash_power_pellets);
+        app.add_systems(Update, flash_power_pellets.in_set(Rendering));
         app.add_systems(OnEnter(AppState::GameOver), despawn);
 
         app.insert_resource(PowerPelletFlashTimer(Timer::from_seconds(
@@ -39,8 +55,13 @@ impl Plugin for PelletsPlugin {
             TimerMode::Repeating,
         )));
         app.insert_resource(TotalPellets::default());
+        app.insert_resource(PelletsEaten::default());
+        app.insert_resource(SavedPellets::default());
 
         app.add_systems(Startup, load_sounds);
+
+        #[cfg(debug_assertions)]
+        app.add_systems(Update, complete_level.run_if(in_state(AppState::MainGame)));
     }
 }
 
@@ -58,33 +79,44 @@ fn spawn_pellets(
     mut commands: Commands,
     asset_server: Res<AssetServer>,
     mut total_pellets: ResMut<TotalPellets>,
+    mut pellets_eaten: ResMut<PelletsEaten>,
     mut munch_sounds: ResMut<MunchSounds>,
+    mut saved_pellets: ResMut<SavedPellets>,
+    map: Res<Map>,
 ) {
-    const PELLETS_TEXT: &str = include_str!("pellets");
-    const PARSING_ERROR: &str = "Error parsing pellets file";
-
-    let pellets_parser = PELLETS_TEXT
-        .lines()
-        .map(|line| {
-            let (coordinates_text, type_text) = line.split_once(' ')?;
-            let (x_text, y_text) = coordinates_text.split_once(',')?;
-
-            let x = x_text.parse::<f32>().ok()?;
-            let y = y_text.parse::<f32>().ok()?;
-            let pellet_type = match type_text {
-                "Regular" => PelletType::Regular,
-                "Power" => PelletType::Power,
-                _ => return None,
-            };
-
-            Some((x, y, pellet_type))
+    let pellets = if map.pellets().is_empty() {
+        parse_pellets_file(&map).unwrap_or_else(|error| {
+            error!("The map has no pellets of its own: {}", error);
+            Vec::new()
         })
-        .map(|option| option.expect(PARSING_ERROR));
+    } else {
+        map.pellets()
+            .iter()
+            .map(|pellet| {
+                let pellet_type = if pellet.power {
+                    PelletType::Power
+                } else {
+                    PelletType::Regular
+                };
+                (pellet.location, pellet_type)
+            })
+            .collect()
+    };
+
+    let saved_pellets = saved_pellets.0.take();
+    let mut spawned = 0;
+    for (location, pellet_type) in pellets.iter().copied() {
+        let eaten = saved_pellets
+            .as_ref()
+            .is_some_and(|saved| !saved.iter().any(|pellet| pellet.tile_eq(location)));
+        if eaten {
+            continue;
+        }
 
-    for (x, y, pellet_type) in pellets_parser {
+        spawned += 1;
         commands.spawn((
             pellet_type,
-            Location::new(x, y),
+            location,
             SpriteBundle {
                 texture: asset_server.load(match pellet_type {
                     PelletType::Regular => "pellet.png",
@@ -96,16 +128,64 @@ fn spawn_pellets(
         ));
     }
 
-    total_pellets.0 = PELLETS_TEXT.lines().count();
+    total_pellets.0 = pellets.len();
+    pellets_eaten.0 = pellets.len() - spawned;
 
     munch_sounds.current_index = 0;
 }
 
+fn reset_munch_sounds(mut munch_sounds: ResMut<MunchSounds>) {
+    munch_sounds.current_index = 0;
+}
+
+/// The pellets of the `pellets` file, for maps that don't mark their own. The
+/// file only fits the stock maze, so it's refused for a map where any of its
+/// pellets would sit in a wall.
+fn parse_pellets_file(map: &Map) -> Result<Vec<(Location, PelletType)>, String> {
+    const PELLETS_TEXT: &str = include_str!("pellets");
+
+    PELLETS_TEXT
+        .lines()
+        .enumerate()
+        .map(|(i, line)| {
+            let parse_line = || {
+                let (coordinates_text, type_text) = line.split_once(' ')?;
+                let (x_text, y_text) = coordinates_text.split_once(',')?;
+
+                let x = x_text.parse::<u16>().ok()?;
+                let y = y_text.parse::<u16>().ok()?;
+                let pellet_type = match type_text {
+                    "Regular" => PelletType::Regular,
+                    "Power" => PelletType::Power,
+                    _ => return None,
+                };
+
+                Some((Location::new(x as f32, y as f32), pellet_type))
+            };
+
+            let (location, pellet_type) = parse_line()
+                .ok_or_else(|| format!("bad pellets file line {}: {:?}", i + 1, line))?;
+            if !map.is_open(location) {
+                return Err(format!(
+                    "the pellets file doesn't fit the map, it has a pellet in the wall at {},{}",
+                    location.x(),
+                    location.y()
+                ));
+            }
+
+            Ok((location, pellet_type))
+        })
+        .collect()
+}
+
+#[allow(clippy::too_many_arguments)]
 fn remove_pellets(
     mut commands: Commands,
     query: Query<(Entity, &Location, &PelletType)>,
     mut player_at_events: EventReader<PlayerAt>,
     mut pellets_eaten_events: EventWriter<PelletEaten>,
+    mut level_complete_events: EventWriter<LevelComplete>,
+    mut pellets_eaten: ResMut<PelletsEaten>,
     mut next_game_state: ResMut<NextState<AppState>>,
     mut munch_sounds: ResMut<MunchSounds>,
     audio: Res<Audio>,
@@ -115,27 +195,67 @@ fn remove_pellets(
         .map(|event| event.location)
         .collect::<Vec<_>>();
 
-    let mut pellet_eaten = Option::<PelletType>::None;
+    let mut regular_eaten_count = 0;
+    let mut eaten_count = 0;
     for (entity, location, pellet_type) in query.iter() {
-        if player_locations.contains(location) {
-            pellet_eaten = Some(*pellet_type);
+        if player_locations
+            .iter()
+            .any(|player_location| player_location.tile_eq(*location))
+        {
+            if let PelletType::Regular = pellet_type {
+                regular_eaten_count += 1;
+            }
+            eaten_count += 1;
             pellets_eaten_events.send(PelletEaten {
                 power: matches!(pellet_type, PelletType::Power),
             });
             commands.entity(entity).despawn();
         }
     }
+    pellets_eaten.0 += eaten_count;
 
-    let pellets_left = query.iter().count();
+    // The eaten pellets are only despawned once the commands are applied.
+    let pellets_left = query.iter().count() - eaten_count;
     if pellets_left == 0 {
+        if eaten_count > 0 {
+            level_complete_events.send(LevelComplete);
+        }
         next_game_state.set(AppState::LevelComplete);
-    } else if let Some(PelletType::Regular) = pellet_eaten {
-        let audio_handle = munch_sounds.audio_handles[munch_sounds.current_index].clone();
-        audio.play(audio_handle);
-        munch_sounds.current_index = (munch_sounds.current_index + 1) % 2;
+    } else {
+        for _ in 0..regular_eaten_count.min(MAX_MUNCHES_PER_TICK) {
+            let audio_handle = munch_sounds.audio_handles[munch_sounds.current_index].clone();
+            audio.play(audio_handle);
+            munch_sounds.current_index = (munch_sounds.current_index + 1) % 2;
+        }
     }
 }
 
+/// Debug builds clear the level on F8, eating every pellet left at once, for
+/// getting to later levels quickly.
+#[cfg(debug_assertions)]
+fn complete_level(
+    mut commands: Commands,
+    key: Res<Input<KeyCode>>,
+    query: Query<Entity, With<PelletType>>,
+    total_pellets: Res<TotalPellets>,
+    mut pellets_eaten: ResMut<PelletsEaten>,
+    mut level_complete_events: EventWriter<LevelComplete>,
+    mut next_game_state: ResMut<NextState<AppState>>,
+) {
+    if !key.just_pressed(KeyCode::F8) {
+        return;
+    }
+
+    for entity in query.iter() {
+        commands.entity(entity).despawn();
+    }
+    pellets_eaten.0 = total_pellets.0;
+
+    info!("Level completed from the debug key");
+    level_complete_events.send(LevelComplete);
+    next_game_state.set(AppState::LevelComplete);
+}
+
 fn flash_power_pellets(
     mut query: Query<(&PelletType, &mut Visibility)>,
     mut timer: ResMut<PowerPelletFlashTimer>,
@@ -163,3 +283,24 @@ fn despawn(mut commands: Commands, query: Query<Entity, With<PelletType>>) {
         commands.entity(entity).despawn();
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pellets_file_fits_the_stock_maze() {
+        let map = Map::parse(include_str!("map")).unwrap();
+
+        let pellets = parse_pellets_file(&map).unwrap();
+
+        assert_eq!(pellets.len(), map.pellets().len());
+    }
+
+    #[test]
+    fn pellets_file_is_refused_for_another_maze() {
+        let map = Map::parse(&include_str!("map").replace(['.', 'o'], "W")).unwrap();
+
+        assert!(parse_pellets_file(&map).is_err());
+    }
+}
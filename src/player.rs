@@ -5,20 +5,59 @@ use bevy::prelude::*;
 use bevy_kira_audio::prelude::*;
 use strum::IntoEnumIterator;
 
-use crate::common::app_state::{AppState, DeadState};
+use crate::bot::BotInterface;
+use crate::common::app_state::{AppState, DeadState, NewRun};
 use crate::common::events::{CollisionPauseTimer, GetExtraLife, PelletEaten, PlayerAt};
+use crate::common::game_config::GameConfig;
 use crate::common::layers::Layers;
 use crate::common::levels::Levels;
-use crate::common::sets::GameLoop;
+use crate::common::sets::{GameLoop, Rendering};
 use crate::ghosts::FriteTimer;
+use crate::replay::{Recorder, Replay};
+use crate::services::input::{GamepadInput, KeyBindings};
 use crate::services::map::{Direction, Location, Map};
 use crate::services::speed::CharacterSpeed;
+use crate::services::text::TextProvider;
+#[cfg(debug_assertions)]
+use crate::NoClip;
+
+const INPUT_BUFFER_SECS: f32 = 0.2;
+const MAX_LIFE_ICONS: usize = 5;
 
 #[derive(Component)]
 pub struct Player {
     pub is_blocked: bool,
+    pub buffered_direction: Option<Direction>,
+    buffer_timer: Timer,
 }
 
+impl Player {
+    fn new() -> Self {
+        Self {
+            is_blocked: false,
+            buffered_direction: None,
+            buffer_timer: Timer::from_seconds(INPUT_BUFFER_SECS, TimerMode::Once),
+        }
+    }
+}
+
+/// Shows a faint arrow next to the player pointing at the turn that's queued,
+/// to help learn cornering.
+#[derive(Resource, Default)]
+pub struct TurnIndicator(pub bool);
+
+#[derive(Component)]
+struct TurnArrow;
+
+/// Cuts the pause, the death animation and the restart delay after losing a
+/// life down to almost nothing, for trying a tricky spot again and again.
+#[derive(Resource, Default)]
+pub struct PracticeMode(pub bool);
+
+/// How long the pause before the death animation and the delay before the next
+/// life take in practice mode.
+const PRACTICE_DEATH_DELAY: Duration = Duration::from_millis(100);
+
 #[derive(Bundle)]
 struct PlayerBundle {
     location: Location,
@@ -34,7 +73,7 @@ struct PelletEatenTimer(Timer);
 struct PlayerDeadTimer(Timer);
 
 #[derive(Resource, Default)]
-struct PlayerLives(usize);
+pub struct PlayerLives(pub usize);
 
 #[derive(Component)]
 struct PlayerLife;
@@ -43,9 +82,55 @@ struct PlayerLife;
 struct DeathAnimation {
     timer: Timer,
     playing_handle: Handle<AudioInstance>,
-    counter: usize,
+    step: usize,
+}
+
+/// When a step of the death animation ends and the next one starts.
+#[derive(Clone, Copy, Debug)]
+enum StepEnd {
+    AfterSecs(f32),
+    /// Once the sound played by the step is over.
+    SoundFinished,
 }
 
+/// One step of the death animation. The sprite frame is `None` once the player
+/// is gone, and the sound, if any, replaces the one playing.
+#[derive(Clone, Copy, Debug)]
+struct DeathStep {
+    frame: Option<usize>,
+    sound: Option<&'static str>,
+    end: StepEnd,
+}
+
+impl DeathStep {
+    const fn new(frame: Option<usize>, sound: Option<&'static str>, end: StepEnd) -> Self {
+        Self { frame, sound, end }
+    }
+
+    const fn frame(frame: usize, secs: f32) -> Self {
+        Self::new(Some(frame), None, StepEnd::AfterSecs(secs))
+    }
+}
+
+/// The player freezes for a moment, shrinks away to the first death sound, pops
+/// to the second one played twice, and the screen stays empty for a second
+/// before the next life or game over.
+const DEATH_ANIMATION: [DeathStep; 13] = [
+    DeathStep::frame(0, 0.5),
+    DeathStep::new(Some(1), Some("sounds/death_1.wav"), StepEnd::AfterSecs(0.1)),
+    DeathStep::frame(2, 0.1),
+    DeathStep::frame(3, 0.1),
+    DeathStep::frame(4, 0.1),
+    DeathStep::frame(5, 0.1),
+    DeathStep::frame(6, 0.1),
+    DeathStep::frame(7, 0.1),
+    DeathStep::frame(8, 0.1),
+    DeathStep::frame(9, 0.1),
+    DeathStep::new(Some(10), Some("sounds/death_2.wav"), StepEnd::SoundFinished),
+    DeathStep::new(Some(10), Some("sounds/death_2.wav"), StepEnd::SoundFinished),
+    DeathStep::new(None, None, StepEnd::AfterSecs(1.0)),
+];
+
 #[derive(Component)]
 struct DeathSprite;
 
@@ -57,6 +142,8 @@ impl Plugin for PlayerPlugin {
         app.insert_resource(PlayerDeadTimer(Timer::from_seconds(0.0, TimerMode::Once)));
         app.insert_resource(DeathAnimation::default());
         app.insert_resource(PlayerLives::default());
+        app.insert_resource(TurnIndicator::default());
+        app.insert_resource(PracticeMode::default());
 
         app.add_systems(
             OnEnter(AppState::LevelStart),
@@ -66,7 +153,7 @@ impl Plugin for PlayerPlugin {
             OnEnter(DeadState::Restart),
             (spawn_character, (despawn_lives, spawn_lives).chain()),
         );
-        app.add_systems(OnExit(AppState::MainMenu), reset_lives);
+        app.add_systems(NewRun, reset_lives);
         app.add_systems(
             FixedUpdate,
             (
@@ -77,7 +164,10 @@ impl Plugin for PlayerPlugin {
 
         app.add_systems(
             Update,
-            update_pacman_sprite.run_if(in_state(AppState::MainGame)),
+            (update_pacman_sprite, draw_turn_arrow)
+                .chain()
+                .in_set(Rendering)
+                .run_if(in_state(AppState::MainGame)),
         );
 
         app.add_systems(OnEnter(AppState::LevelComplete), level_complete_sprite);
@@ -110,65 +200,154 @@ impl Plugin for PlayerPlugin {
     }
 }
 
-fn reset_lives(mut player_lives: ResMut<PlayerLives>) {
-    player_lives.0 = 3;
+fn reset_lives(mut player_lives: ResMut<PlayerLives>, game_config: Res<GameConfig>) {
+    player_lives.0 = game_config.starting_lives();
 }
 
+#[allow(clippy::too_many_arguments)]
 fn spawn_character(
     mut commands: Commands,
     asset_server: Res<AssetServer>,
     mut texture_atlases: ResMut<Assets<TextureAtlas>>,
     level: Res<Levels>,
+    map: Res<Map>,
+    key: Res<Input<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    gamepad: GamepadInput,
+    replay: Option<Res<Replay>>,
+    bot: Option<Res<BotInterface>>,
 ) {
     let texture_handle = asset_server.load("pacman.png");
     let texture_atlas =
         TextureAtlas::from_grid(texture_handle, Vec2::new(15.0, 15.0), 3, 1, None, None);
     let texture_atlas_handle = texture_atlases.add(texture_atlas);
 
-    commands.spawn((
-        PlayerBundle {
-            location: Location::new(13.5, 7.0),
-            player: Player { is_blocked: false },
-            direction: Direction::Left,
-            speed: CharacterSpeed::new(level.player_speed()),
-        },
-        SpriteSheetBundle {
-            texture_atlas: texture_atlas_handle,
-            sprite: TextureAtlasSprite::new(0),
-            transform: Transform::from_xyz(0.0, 0.0, Layers::Player.as_f32()),
-            ..default()
-        },
-    ));
+    // Start out facing a direction that's already held, so the first frame of
+    // play doesn't draw the player facing left before the first tick turns it.
+    // `update_player` plans with the held key before that tick's move either
+    // way, so replays, which only read input from then on, play out the same.
+    let location = map.markers().player;
+    let held_direction = if replay.is_none() && bot.is_none() {
+        map.possible_directions(location)
+            .into_iter()
+            .find(|direction| {
+                key.pressed(bindings.direction(*direction)) || gamepad.pressed(*direction)
+            })
+    } else {
+        None
+    };
+
+    commands
+        .spawn((
+            PlayerBundle {
+                location,
+                player: Player::new(),
+                direction: held_direction.unwrap_or(Direction::Left),
+                speed: CharacterSpeed::new(level.player_speed()),
+            },
+            SpriteSheetBundle {
+                texture_atlas: texture_atlas_handle,
+                sprite: TextureAtlasSprite::new(0),
+                transform: Transform::from_xyz(0.0, 0.0, Layers::Player.as_f32()),
+                ..default()
+            },
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                TurnArrow,
+                SpriteBundle {
+                    texture: asset_server.load("select_arrow.png"),
+                    sprite: Sprite {
+                        color: Color::WHITE.with_a(0.5),
+                        ..default()
+                    },
+                    visibility: Visibility::Hidden,
+                    ..default()
+                },
+            ));
+        });
 }
 
+#[allow(clippy::too_many_arguments)]
 fn update_player(
-    mut query: Query<(&mut Direction, &Location, &Player)>,
+    mut query: Query<(&mut Direction, &Location, &mut Player)>,
     map: Res<Map>,
     key: Res<Input<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    gamepad: GamepadInput,
+    time: Res<Time>,
+    mut recorder: ResMut<Recorder>,
+    replay: Option<ResMut<Replay>>,
+    bot: Option<Res<BotInterface>>,
+    #[cfg(debug_assertions)] no_clip: Res<NoClip>,
 ) {
-    let (mut direction, location, player) = query.single_mut();
+    let (mut direction, location, mut player) = query.single_mut();
 
-    let possible_directions = if player.is_blocked {
-        Direction::iter().collect::<Vec<_>>()
+    #[cfg(debug_assertions)]
+    let free_to_turn = player.is_blocked || (no_clip.0 && location.is_tile_center());
+    #[cfg(not(debug_assertions))]
+    let free_to_turn = player.is_blocked;
+
+    let possible_directions = turn_options(&map, *location, *direction, free_to_turn);
+
+    let pressed_direction = if let Some(mut replay) = replay {
+        replay.direction(recorder.tick())
+    } else if let Some(bot) = bot {
+        bot.input()
     } else {
-        map.possible_directions(*location)
+        let pressed_directions = Direction::iter()
+            .filter(|direction| {
+                key.pressed(bindings.direction(*direction)) || gamepad.pressed(*direction)
+            })
+            .collect::<Vec<_>>();
+
+        pressed_directions
+            .iter()
+            .find(|direction| possible_directions.contains(direction))
+            .or(pressed_directions.first())
+            .copied()
     };
+    recorder.record(pressed_direction);
+
+    // Remember the last pressed direction for a short while, so a turn pressed
+    // slightly before the intersection is still taken once it opens up.
+    if let Some(pressed_direction) = pressed_direction {
+        player.buffered_direction = Some(pressed_direction);
+        player.buffer_timer.reset();
+    } else if player.buffer_timer.tick(time.delta()).finished() {
+        player.buffered_direction = None;
+    }
 
-    let new_direction = possible_directions
-        .iter()
-        .filter(|direction| match **direction {
-            Direction::Up => key.pressed(KeyCode::Up),
-            Direction::Down => key.pressed(KeyCode::Down),
-            Direction::Left => key.pressed(KeyCode::Left),
-            Direction::Right => key.pressed(KeyCode::Right),
-        })
-        .next();
+    if let Some(buffered_direction) = player.buffered_direction {
+        if possible_directions.contains(&buffered_direction) {
+            *direction = buffered_direction;
+            player.buffered_direction = None;
+        }
+    }
+}
 
-    if let Some(d) = new_direction {
-        *direction = *d;
+/// The directions the player may turn to. Like in the arcade, turning back is
+/// allowed anywhere, even between tiles. `move_player` still stops the player
+/// if that runs into a wall.
+fn turn_options(
+    map: &Map,
+    location: Location,
+    direction: Direction,
+    free_to_turn: bool,
+) -> Vec<Direction> {
+    let mut possible_directions = if free_to_turn {
+        Direction::iter().collect::<Vec<_>>()
+    } else {
+        map.possible_directions(location)
+    };
+    if !possible_directions.contains(&direction.opposite()) {
+        possible_directions.push(direction.opposite());
     }
+
+    possible_directions
 }
 
+#[allow(clippy::too_many_arguments)]
 fn move_player(
     mut query: Query<(&mut Location, &Direction, &mut CharacterSpeed, &mut Player)>,
     mut player_at_events: EventWriter<PlayerAt>,
@@ -180,6 +359,7 @@ fn move_player(
     pause_timer: Res<CollisionPauseTimer>,
     time: Res<Time>,
     next_game_state: Res<NextState<AppState>>,
+    #[cfg(debug_assertions)] no_clip: Res<NoClip>,
 ) {
     const PELLET_STOP_TIME: f32 = 1.0 / 60.0;
     for event in pellets_eaten_events.read() {
@@ -195,7 +375,12 @@ fn move_player(
 
     let (mut location, direction, mut speed, mut player) = query.single_mut();
 
-    if frite_timer.0.finished() {
+    let tunnel_speed = levels
+        .player_tunnel_speed()
+        .filter(|_| map.is_tunnel(*location));
+    if let Some(tunnel_speed) = tunnel_speed {
+        speed.set_speed(tunnel_speed);
+    } else if frite_timer.0.finished() {
         speed.set_speed(levels.player_speed());
     } else {
         speed.set_speed(levels.player_frite_speed());
@@ -208,6 +393,10 @@ fn move_player(
 
     player.is_blocked = *location == location.get_tile(*direction)
         && map.is_blocked(location.next_tile(*direction));
+    #[cfg(debug_assertions)]
+    if no_clip.0 {
+        player.is_blocked = false;
+    }
 
     if player.is_blocked {
         return;
@@ -217,10 +406,12 @@ fn move_player(
 
     match *direction {
         Direction::Up | Direction::Down => {
-            location.x = bring_towards_center(location.x);
+            let x = bring_towards_center(location.x());
+            location.set_x(x);
         }
         Direction::Left | Direction::Right => {
-            location.y = bring_towards_center(location.y);
+            let y = bring_towards_center(location.y());
+            location.set_y(y);
         }
     };
 
@@ -229,6 +420,7 @@ fn move_player(
     });
 }
 
+#[allow(clippy::let_and_return)]
 fn bring_towards_center(location: f32) -> f32 {
     if location.fract() == 0.0 {
         return location;
@@ -258,7 +450,7 @@ fn update_pacman_sprite(
     let index = if player.is_blocked {
         1
     } else {
-        let masked_location = *location * *direction.get_vec();
+        let masked_location = location.to_vec() * direction.get_vec().to_vec();
         let value_in_direction = if masked_location.x.fract() == 0.0 {
             masked_location.y
         } else {
@@ -290,6 +482,34 @@ fn update_pacman_sprite(
     }
 }
 
+/// The arrow is a child of the player, so it's turned back against the player's
+/// rotation to point the queued way.
+fn draw_turn_arrow(
+    player_query: Query<(&Player, &Direction)>,
+    mut arrow_query: Query<(&mut Transform, &mut Visibility), With<TurnArrow>>,
+    turn_indicator: Res<TurnIndicator>,
+) {
+    let (player, direction) = player_query.single();
+    let queued_direction = player
+        .buffered_direction
+        .filter(|queued| turn_indicator.0 && queued != direction);
+
+    for (mut transform, mut visibility) in arrow_query.iter_mut() {
+        let Some(queued_direction) = queued_direction else {
+            *visibility = Visibility::Hidden;
+            continue;
+        };
+        *visibility = Visibility::Inherited;
+
+        let player_rotation = Quat::from_rotation_z(-TAU * direction.rotation());
+        let offset = queued_direction.get_vec().to_vec() * 10.0;
+        transform.translation = player_rotation * offset.extend(0.0);
+        // The arrow image points right, the opposite of a rotation of zero.
+        transform.rotation =
+            player_rotation * Quat::from_rotation_z(TAU * (queued_direction.rotation() + 0.5));
+    }
+}
+
 fn level_complete_sprite(mut query: Query<&mut TextureAtlasSprite, With<Player>>) {
     let mut sprite = query.single_mut();
     sprite.index = 0;
@@ -303,8 +523,12 @@ fn despawn(mut commands: Commands, query: Query<Entity, With<Player>>) {
     commands.entity(entity).despawn_recursive();
 }
 
-fn reset_dead_timer(mut timer: ResMut<PlayerDeadTimer>) {
-    timer.0.set_duration(Duration::from_secs(1));
+fn reset_dead_timer(mut timer: ResMut<PlayerDeadTimer>, practice_mode: Res<PracticeMode>) {
+    timer.0.set_duration(if practice_mode.0 {
+        PRACTICE_DEATH_DELAY
+    } else {
+        Duration::from_secs(1)
+    });
     timer.0.reset();
 }
 
@@ -325,12 +549,15 @@ fn setup_death_animation(
     asset_server: Res<AssetServer>,
     mut texture_atlases: ResMut<Assets<TextureAtlas>>,
 ) {
-    death_animation
-        .timer
-        .set_duration(Duration::from_secs_f32(0.5));
-    death_animation.timer.set_mode(TimerMode::Repeating);
+    let first_step = DEATH_ANIMATION[0];
+    death_animation.step = 0;
+    death_animation.timer.set_mode(TimerMode::Once);
+    if let StepEnd::AfterSecs(secs) = first_step.end {
+        death_animation
+            .timer
+            .set_duration(Duration::from_secs_f32(secs));
+    }
     death_animation.timer.reset();
-    death_animation.counter = 0;
 
     let texture_handle = asset_server.load("death_animation.png");
     let texture_atlas =
@@ -339,11 +566,11 @@ fn setup_death_animation(
 
     let (entity, location) = query.single();
     commands.spawn((
-        location.clone(),
+        *location,
         DeathSprite,
         SpriteSheetBundle {
             texture_atlas: texture_atlas_handle,
-            sprite: TextureAtlasSprite::new(0),
+            sprite: TextureAtlasSprite::new(first_step.frame.unwrap_or_default()),
             transform: Transform::from_xyz(0.0, 0.0, Layers::Player.as_f32()),
             ..default()
         },
@@ -352,6 +579,9 @@ fn setup_death_animation(
     commands.entity(entity).despawn_recursive();
 }
 
+/// Moves through `DEATH_ANIMATION` one step at a time, and on to the next life
+/// or game over after the last one. Practice mode skips straight to the end.
+#[allow(clippy::too_many_arguments)]
 fn death_animation(
     mut query: Query<(Entity, &mut TextureAtlasSprite), With<DeathSprite>>,
     mut commands: Commands,
@@ -362,73 +592,62 @@ fn death_animation(
     audio: Res<Audio>,
     mut audio_instances: ResMut<Assets<AudioInstance>>,
     mut player_lives: ResMut<PlayerLives>,
+    practice_mode: Res<PracticeMode>,
 ) {
-    if !death_animation.timer.tick(time.delta()).just_finished() {
+    let Some(step) = DEATH_ANIMATION.get(death_animation.step) else {
+        return;
+    };
+    let step_ended = practice_mode.0
+        || match step.end {
+            StepEnd::AfterSecs(_) => death_animation.timer.tick(time.delta()).finished(),
+            StepEnd::SoundFinished => {
+                audio.state(&death_animation.playing_handle) == PlaybackState::Stopped
+            }
+        };
+    if !step_ended {
         return;
     }
 
-    if !query.is_empty() {
-        let (_, mut sprite) = query.single_mut();
-
-        if sprite.index == 0 {
-            death_animation.playing_handle =
-                audio.play(asset_server.load("sounds/death_1.wav")).handle();
-            death_animation
-                .timer
-                .set_duration(Duration::from_secs_f32(0.1));
-            sprite.index = 1;
-            return;
-        } else if sprite.index < 9 {
-            sprite.index += 1;
-            return;
+    death_animation.step = if practice_mode.0 {
+        DEATH_ANIMATION.len()
+    } else {
+        death_animation.step + 1
+    };
+    let Some(next_step) = DEATH_ANIMATION.get(death_animation.step) else {
+        if player_lives.0 == 1 {
+            next_dead_state.set(DeadState::GameOver);
+        } else {
+            player_lives.0 -= 1;
+            next_dead_state.set(DeadState::Restart);
         }
+        return;
+    };
 
-        sprite.index = 10;
-    }
-
-    match death_animation.counter {
-        0 => {
-            if let Some(audio_instance) = audio_instances.get_mut(&death_animation.playing_handle) {
-                audio_instance.stop(AudioTween::default());
-            }
-
-            death_animation.playing_handle =
-                audio.play(asset_server.load("sounds/death_2.wav")).handle();
-            death_animation
-                .timer
-                .set_duration(Duration::from_secs_f32(0.0));
-            death_animation.counter += 1;
-        }
-        1 => {
-            let audio_state = audio.state(&death_animation.playing_handle);
-            if PlaybackState::Stopped == audio_state {
-                death_animation.playing_handle =
-                    audio.play(asset_server.load("sounds/death_2.wav")).handle();
-                death_animation.counter += 1;
+    match next_step.frame {
+        Some(frame) => {
+            for (_, mut sprite) in query.iter_mut() {
+                sprite.index = frame;
             }
         }
-        2 => {
-            let audio_state = audio.state(&death_animation.playing_handle);
-            if PlaybackState::Stopped == audio_state {
-                death_animation
-                    .timer
-                    .set_duration(Duration::from_secs_f32(1.0));
-                death_animation.timer.reset();
-                death_animation.counter += 1;
-
-                let (entity, _) = query.single_mut();
+        None => {
+            for (entity, _) in query.iter() {
                 commands.entity(entity).despawn_recursive();
             }
         }
-        3 => {
-            if player_lives.0 == 1 {
-                next_dead_state.set(DeadState::GameOver);
-            } else {
-                player_lives.0 -= 1;
-                next_dead_state.set(DeadState::Restart);
-            }
+    }
+
+    if let Some(sound) = next_step.sound {
+        if let Some(audio_instance) = audio_instances.get_mut(&death_animation.playing_handle) {
+            audio_instance.stop(AudioTween::default());
         }
-        _ => unreachable!(),
+        death_animation.playing_handle = audio.play(asset_server.load(sound)).handle();
+    }
+
+    if let StepEnd::AfterSecs(secs) = next_step.end {
+        death_animation
+            .timer
+            .set_duration(Duration::from_secs_f32(secs));
+        death_animation.timer.reset();
     }
 }
 
@@ -446,8 +665,12 @@ fn despawn_death_animation(
     }
 }
 
-fn reset_restart_timer(mut timer: ResMut<PlayerDeadTimer>) {
-    timer.0.set_duration(Duration::from_secs(2));
+fn reset_restart_timer(mut timer: ResMut<PlayerDeadTimer>, practice_mode: Res<PracticeMode>) {
+    timer.0.set_duration(if practice_mode.0 {
+        PRACTICE_DEATH_DELAY
+    } else {
+        Duration::from_secs(2)
+    });
     timer.0.reset();
 }
 
@@ -468,13 +691,36 @@ fn spawn_lives(
     asset_server: Res<AssetServer>,
     mut texture_atlases: ResMut<Assets<TextureAtlas>>,
     player_lives: Res<PlayerLives>,
+    mut text_provider: ResMut<TextProvider>,
 ) {
     let texture_handle = asset_server.load("pacman.png");
     let texture_atlas =
         TextureAtlas::from_grid(texture_handle, Vec2::new(15.0, 15.0), 3, 1, None, None);
     let texture_atlas_handle = texture_atlases.add(texture_atlas);
 
-    for i in 1..player_lives.0 {
+    // Only room for a few icons before the level counter, so more spare lives
+    // are drawn as a single icon with a count.
+    let spare_lives = player_lives.0.saturating_sub(1);
+    let icons = if spare_lives > MAX_LIFE_ICONS {
+        commands.spawn((
+            PlayerLife,
+            Location::new(5.0, -1.5),
+            SpriteBundle {
+                texture: text_provider.get_image(
+                    format!("x{}", spare_lives),
+                    Color::WHITE,
+                    &asset_server,
+                ),
+                transform: Transform::from_xyz(0.0, 0.0, Layers::HUD.as_f32()),
+                ..default()
+            },
+        ));
+        1
+    } else {
+        spare_lives
+    };
+
+    for i in 1..=icons {
         commands.spawn((
             PlayerLife,
             Location::new(0.5 + 2.0 * i as f32, -1.5),
@@ -497,3 +743,72 @@ fn despawn_lives(mut commands: Commands, query: Query<Entity, With<PlayerLife>>)
 fn add_life(mut player_lives: ResMut<PlayerLives>) {
     player_lives.0 += 1;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn death_animation_steps() {
+        let frames = DEATH_ANIMATION
+            .iter()
+            .map(|step| step.frame)
+            .collect::<Vec<_>>();
+        let expected_frames = (0..=10)
+            .map(Some)
+            .chain([Some(10), None])
+            .collect::<Vec<_>>();
+        assert_eq!(frames, expected_frames);
+
+        let sounds = DEATH_ANIMATION
+            .iter()
+            .enumerate()
+            .filter_map(|(i, step)| step.sound.map(|sound| (i, sound)))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            sounds,
+            vec![
+                (1, "sounds/death_1.wav"),
+                (10, "sounds/death_2.wav"),
+                (11, "sounds/death_2.wav"),
+            ]
+        );
+
+        // The steps waiting on a sound are the ones playing death_2, the rest
+        // add up to 2.4 seconds.
+        let secs = DEATH_ANIMATION
+            .iter()
+            .filter_map(|step| match step.end {
+                StepEnd::AfterSecs(secs) => Some(secs),
+                StepEnd::SoundFinished => {
+                    assert_eq!(step.sound, Some("sounds/death_2.wav"));
+                    None
+                }
+            })
+            .sum::<f32>();
+        assert!((secs - 2.4).abs() < 1e-5, "{secs}");
+    }
+
+    #[test]
+    fn player_can_reverse_between_tiles() {
+        let map = Map::parse(include_str!("map")).unwrap();
+
+        // Leaving the bottom corners, the tile behind the player is a wall, so
+        // only the special case lets it turn back.
+        for (start, direction) in [
+            (Location::new(1.0, 1.0), Direction::Right),
+            (Location::new(26.0, 1.0), Direction::Left),
+        ] {
+            let mut location = start;
+            for _ in 0..7 {
+                location.advance(direction);
+                let options = turn_options(&map, location, direction, false);
+
+                assert!(
+                    options.contains(&direction.opposite()),
+                    "Can't reverse at {location:?} moving {direction:?}"
+                );
+            }
+        }
+    }
+}
@@ -1,78 +1,103 @@
-use std::{fs::OpenOptions, io::BufRead, io::BufReader};
-
 use bevy::prelude::*;
 use bevy_kira_audio::prelude::*;
+use serde::Deserialize;
 
 use crate::{
     advance_level,
     common::{
-        app_state::AppState,
+        app_state::{AppState, NewRun},
         events::{CollisionPauseTimer, GetExtraLife, GhostEaten, PelletEaten},
+        game_config::GameConfig,
         layers::Layers,
         levels::Levels,
-        sets::GameLoop,
+        sets::{GameLoop, Rendering},
     },
+    level_stats::StatsRecorder,
     map_render::NoMapWrap,
+    pellets::PelletsEaten,
     player::Player,
-    services::{map::Location, text::TextProvider},
+    services::{
+        files::write_atomically,
+        map::{Direction, Location, Map},
+        rng::GameRng,
+        scores,
+        speed::CharacterSpeed,
+        text::TextProvider,
+    },
 };
 
-#[derive(Component, Clone, Copy, Debug)]
-pub enum BonusSymbol {
-    Cherries,
-    Strawberry,
-    Peach,
-    Apple,
-    Grapes,
-    Galaxian,
-    Bell,
-    Key,
+const HIGH_SCORE_FILE: &str = "highscore";
+const SCORE_COUNT_UP_SECS: f32 = 0.3;
+const WANDERING_SYMBOL_SPEED: f32 = 0.5;
+const ALL_GHOSTS_BONUS: u32 = 12000;
+
+/// One entry of the bonus symbol table in `bonus_symbols.ron`.
+#[derive(Deserialize, Clone, Debug)]
+pub struct BonusSymbolDef {
+    points: u32,
+    asset: String,
+    eaten_asset: String,
+    levels: usize,
+}
+
+impl BonusSymbolDef {
+    pub fn parse_table(text: &str) -> Result<Vec<Self>, String> {
+        let table: Vec<Self> = ron::from_str(text).map_err(|error| error.to_string())?;
+
+        if table.is_empty() {
+            return Err("Bonus symbol table has no symbols".to_string());
+        }
+        if let Some(i) = table.iter().position(|symbol| symbol.levels == 0) {
+            return Err(format!("Bonus symbol {} is used for 0 levels", i + 1));
+        }
+
+        Ok(table)
+    }
+
+    /// How many levels in a row this symbol is used for.
+    pub fn levels(&self) -> usize {
+        self.levels
+    }
+
+    pub fn assets(&self) -> [&str; 2] {
+        [&self.asset, &self.eaten_asset]
+    }
 }
 
+#[derive(Component, Clone, Debug)]
+pub struct BonusSymbol(BonusSymbolDef);
+
 impl BonusSymbol {
+    pub fn new(def: &BonusSymbolDef) -> Self {
+        Self(def.clone())
+    }
+
     fn points(&self) -> u32 {
-        match self {
-            BonusSymbol::Cherries => 100,
-            BonusSymbol::Strawberry => 300,
-            BonusSymbol::Peach => 500,
-            BonusSymbol::Apple => 700,
-            BonusSymbol::Grapes => 1000,
-            BonusSymbol::Galaxian => 2000,
-            BonusSymbol::Bell => 3000,
-            BonusSymbol::Key => 5000,
-        }
+        self.0.points
     }
 
-    fn asset(&self) -> &'static str {
-        match self {
-            BonusSymbol::Cherries => "cherries.png",
-            BonusSymbol::Strawberry => "strawberry.png",
-            BonusSymbol::Peach => "peach.png",
-            BonusSymbol::Apple => "apple.png",
-            BonusSymbol::Grapes => "grapes.png",
-            BonusSymbol::Galaxian => "galaxian.png",
-            BonusSymbol::Bell => "bell.png",
-            BonusSymbol::Key => "key.png",
-        }
+    pub fn asset(&self) -> String {
+        self.0.asset.clone()
     }
 
-    fn eaten_asset(&self) -> &'static str {
-        match self {
-            BonusSymbol::Cherries => "bonus_points_100.png",
-            BonusSymbol::Strawberry => "bonus_points_300.png",
-            BonusSymbol::Peach => "bonus_points_500.png",
-            BonusSymbol::Apple => "bonus_points_700.png",
-            BonusSymbol::Grapes => "bonus_points_1000.png",
-            BonusSymbol::Galaxian => "bonus_points_2000.png",
-            BonusSymbol::Bell => "bonus_points_3000.png",
-            BonusSymbol::Key => "bonus_points_5000.png",
-        }
+    fn eaten_asset(&self) -> String {
+        self.0.eaten_asset.clone()
     }
 }
 
 #[derive(Component)]
 struct SymbolTimer(Timer);
 
+/// A bonus symbol that enters through a tunnel, heads for the middle of the
+/// maze and leaves through the tunnel on the other side, like in the later
+/// arcade games. Used instead of the stationary symbol in hard and endless
+/// modes.
+#[derive(Component)]
+struct WanderingSymbol {
+    exit: Location,
+    reached_center: bool,
+}
+
 #[derive(Resource)]
 struct BonusTextTimer(Timer);
 
@@ -82,6 +107,32 @@ pub struct Points {
     pub high_score: u32,
 }
 
+impl Points {
+    /// Forgets the high score, deleting the `highscore` file along with it.
+    pub fn clear_high_score(&mut self) {
+        self.high_score = 0;
+
+        match std::fs::remove_file(HIGH_SCORE_FILE) {
+            Err(error) if error.kind() != std::io::ErrorKind::NotFound => {
+                warn!("Failed to delete high score file: {}", error)
+            }
+            _ => (),
+        }
+    }
+}
+
+/// The score shown on screen, which counts up to `Points::score` instead of
+/// jumping to it.
+#[derive(Resource, Default)]
+pub struct DisplayedScore(pub u32);
+
+/// Score at which the player is awarded a single extra life per game.
+#[derive(Resource)]
+pub struct ExtraLife {
+    pub threshold: u32,
+    pub awarded: bool,
+}
+
 #[derive(Component, Debug)]
 enum PointsText {
     Still,
@@ -89,33 +140,72 @@ enum PointsText {
     HighScore,
 }
 
+/// The "1UP" label over the score, which flashes while playing.
+#[derive(Component)]
+struct OneUpSign;
+
+#[derive(Resource)]
+struct FlashTimer(Timer);
+
 #[derive(Component)]
 struct Digit {
     digit: u8,
+    rendered: Option<char>,
 }
 
-#[derive(Resource)]
-struct PelletEatenCounter(usize);
+#[derive(Resource, Default)]
+pub struct BonusSymbolsSpawned(pub usize);
+
+impl BonusSymbolsSpawned {
+    /// Whether the next symbol should appear, counting it as spawned if so.
+    /// Compared with `>=` so a threshold can't be skipped over.
+    fn spawn_next(&mut self, pellets_eaten: usize, thresholds: &[usize]) -> bool {
+        let due = thresholds
+            .get(self.0)
+            .is_some_and(|dots| pellets_eaten >= *dots);
+        if due {
+            self.0 += 1;
+        }
+
+        due
+    }
+}
 
+/// Ghosts eaten with each of the level's first four power pellets, and the
+/// index of the latest power pellet. Eating all four ghosts with each of them
+/// is worth `ALL_GHOSTS_BONUS`. Maps with more power pellets don't count the
+/// ghosts eaten after the fourth.
 #[derive(Resource)]
 struct GhostsEatenCounter([Option<u8>; 4], Option<usize>);
 
 impl GhostsEatenCounter {
-    fn ghost_eaten(&mut self) {
-        assert!(self.1.is_some());
-        assert!(self.0[self.1.unwrap()].is_some());
+    /// Returns whether this was the last of the sixteen ghosts. A ghost eaten
+    /// before any power pellet, which only a custom map without them could
+    /// allow, isn't part of a chain and only gets its own points.
+    fn ghost_eaten(&mut self) -> bool {
+        let Some(index) = self.1 else {
+            warn!("Ghost eaten before any power pellet");
+            return false;
+        };
+
+        let Some(eaten) = self.0.get_mut(index) else {
+            return false;
+        };
+        *eaten = Some(eaten.unwrap_or(0) + 1);
 
-        let currently_eaten = self.0[self.1.unwrap()].unwrap();
-        self.0[self.1.unwrap()] = Some(currently_eaten + 1);
+        self.total() == 4 * 4
     }
 
     fn power_pellet_eaten(&mut self) {
-        self.1 = if let Some(i) = self.1 {
-            Some(i + 1)
-        } else {
-            Some(0)
-        };
-        self.0[self.1.unwrap()] = Some(0);
+        let index = self.1.map_or(0, |i| i + 1);
+        self.1 = Some(index);
+        if let Some(eaten) = self.0.get_mut(index) {
+            *eaten = Some(0);
+        }
+    }
+
+    fn total(&self) -> u8 {
+        self.0.iter().map(|eaten| eaten.unwrap_or(0)).sum()
     }
 }
 
@@ -127,10 +217,17 @@ impl Plugin for PointsPlugin {
             score: 0,
             high_score: 0,
         });
+        app.insert_resource(DisplayedScore::default());
+        app.insert_resource(ExtraLife {
+            threshold: 10000,
+            awarded: false,
+        });
         app.insert_resource(GhostsEatenCounter([None; 4], None));
-        app.insert_resource(PelletEatenCounter(0));
+        app.insert_resource(BonusSymbolsSpawned::default());
         app.insert_resource(BonusTextTimer(Timer::from_seconds(3.0, TimerMode::Once)));
+        app.insert_resource(FlashTimer(Timer::from_seconds(0.25, TimerMode::Repeating)));
         app.add_systems(OnEnter(AppState::LevelStart), setup.after(advance_level));
+        app.add_systems(NewRun, reset_score);
         app.add_systems(OnExit(AppState::LevelComplete), despawn);
         app.add_systems(OnEnter(AppState::GameOver), despawn);
         app.add_systems(
@@ -144,14 +241,42 @@ impl Plugin for PointsPlugin {
                 .in_set(GameLoop::Collisions)
                 .run_if(in_state(AppState::MainGame)),
         );
-        app.add_systems(OnExit(AppState::MainGame), despawn_symbol);
         app.add_systems(
             FixedUpdate,
-            update_points
+            move_wandering_symbol.in_set(GameLoop::Movement),
+        );
+        app.add_systems(OnExit(AppState::MainGame), (despawn_symbol, show_one_up));
+        app.add_systems(
+            FixedUpdate,
+            (update_points, award_extra_life)
+                .chain()
                 .run_if(in_state(AppState::MainGame))
                 .after(GameLoop::Collisions),
         );
-        app.add_systems(Update, draw_points.run_if(in_state(AppState::MainGame)));
+        app.add_systems(
+            Update,
+            (count_up_score, draw_points)
+                .chain()
+                .in_set(Rendering)
+                .run_if(in_state(AppState::MainGame).or_else(in_state(AppState::LevelComplete))),
+        );
+        app.add_systems(
+            Update,
+            flash_one_up
+                .in_set(Rendering)
+                .run_if(in_state(AppState::MainGame)),
+        );
+    }
+}
+
+/// Keeps the high score in the `highscore` file. Headless runs go without it,
+/// so they neither depend on the player's best nor overwrite it.
+pub struct HighScorePlugin;
+
+impl Plugin for HighScorePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, load_high_score);
+        app.add_systems(Update, save_high_score.run_if(resource_changed::<Points>()));
     }
 }
 
@@ -161,37 +286,51 @@ fn setup(
     asset_server: Res<AssetServer>,
     mut ghost_eaten_counter: ResMut<GhostsEatenCounter>,
     levels: Res<Levels>,
-    mut pellets_eaten_counter: ResMut<PelletEatenCounter>,
-    mut points: ResMut<Points>,
+    mut bonus_symbols_spawned: ResMut<BonusSymbolsSpawned>,
 ) {
-    pellets_eaten_counter.0 = 0;
+    bonus_symbols_spawned.0 = 0;
     *ghost_eaten_counter = GhostsEatenCounter([None; 4], None);
 
     spawn_points(&mut commands, text_provider.into_inner(), &asset_server);
 
     spawn_level_counter(&mut commands, &levels, &asset_server);
+}
 
-    let scores = OpenOptions::new().read(true).open("scores");
-
-    if let Ok(scores) = scores {
-        let reader = BufReader::new(scores);
-        points.high_score = reader
-            .lines()
-            .map(|line| {
-                line.expect("Error reading scores file")
-                    .split_once(':')
-                    .expect("Scores file is corrupt")
-                    .1
-                    .parse::<u32>()
-                    .expect("Scores file is corrupt")
-            })
-            .max()
-            .unwrap_or(0);
-    } else {
-        points.high_score = 0;
+/// Reads the `highscore` file, also considering the `scores` leaderboard so a
+/// best score saved before the file existed isn't lost. Anything missing or
+/// malformed counts as 0.
+fn load_high_score(mut points: ResMut<Points>, game_config: Res<GameConfig>) {
+    let high_score = std::fs::read_to_string(HIGH_SCORE_FILE)
+        .ok()
+        .and_then(|text| {
+            let high_score = text.trim().parse::<u32>();
+            if high_score.is_err() {
+                warn!("Ignoring malformed high score file");
+            }
+            high_score.ok()
+        })
+        .unwrap_or(0);
+
+    let best_score = scores::best_score(&scores::load(game_config.strict_scores()));
+
+    points.high_score = high_score.max(best_score);
+}
+
+/// Writes each new high score as it's set, so it's on disk however the game
+/// ends. The one read at startup and a cleared one aren't written.
+fn save_high_score(points: Res<Points>, mut saved: Local<Option<u32>>) {
+    let is_new = saved.is_some_and(|saved| saved != points.high_score);
+    *saved = Some(points.high_score);
+    if !is_new || points.high_score == 0 {
+        return;
+    }
+
+    if let Err(error) = write_atomically(HIGH_SCORE_FILE, points.high_score.to_string()) {
+        warn!("Failed to write high score file: {}", error);
     }
 }
 
+#[allow(clippy::type_complexity)]
 fn despawn(
     mut commands: Commands,
     query: Query<
@@ -232,6 +371,17 @@ fn spawn_points(
     text_provider: &mut TextProvider,
     asset_server: &AssetServer,
 ) {
+    commands.spawn((
+        NoMapWrap,
+        PointsText::Still,
+        OneUpSign,
+        Location::new(4.0, 33.0),
+        SpriteBundle {
+            texture: text_provider.get_image("1UP", Color::WHITE, asset_server),
+            transform: Transform::from_xyz(0.0, 0.0, Layers::HUD.as_f32()),
+            ..default()
+        },
+    ));
     commands.spawn((
         NoMapWrap,
         PointsText::Still,
@@ -255,7 +405,10 @@ fn spawn_points(
         .with_children(|parent| {
             for i in 0..=8 {
                 parent.spawn((
-                    Digit { digit: i },
+                    Digit {
+                        digit: i,
+                        rendered: None,
+                    },
                     SpriteBundle {
                         transform: Transform::from_xyz(
                             -((i * 8) as f32),
@@ -281,7 +434,10 @@ fn spawn_points(
         .with_children(|parent| {
             for i in 0..=8 {
                 parent.spawn((
-                    Digit { digit: i },
+                    Digit {
+                        digit: i,
+                        rendered: (i < 2).then_some('0'),
+                    },
                     SpriteBundle {
                         texture: text_provider.get_image("0", Color::WHITE, asset_server),
                         transform: Transform::from_xyz(
@@ -301,25 +457,50 @@ fn spawn_points(
         });
 }
 
+/// Closes the gap to the real score a bit every frame, so big gains roll up
+/// over about `SCORE_COUNT_UP_SECS`. A lower score, as when a new game starts,
+/// is shown right away.
+fn count_up_score(
+    mut displayed_score: ResMut<DisplayedScore>,
+    points: Res<Points>,
+    time: Res<Time>,
+) {
+    if points.score <= displayed_score.0 {
+        displayed_score.0 = points.score;
+        return;
+    }
+
+    let step_fraction = (time.delta_seconds() / (SCORE_COUNT_UP_SECS / 4.0)).min(1.0);
+    let step = ((points.score - displayed_score.0) as f32 * step_fraction).ceil() as u32;
+    displayed_score.0 = (displayed_score.0 + step).min(points.score);
+}
+
 fn draw_points(
     query: Query<(&Children, &PointsText)>,
-    mut decimal_query: Query<(&mut Handle<Image>, &mut Visibility, &Digit)>,
+    mut decimal_query: Query<(&mut Handle<Image>, &mut Visibility, &mut Digit)>,
     points: Res<Points>,
+    displayed_score: Res<DisplayedScore>,
     mut text_provider: ResMut<TextProvider>,
     asset_server: Res<AssetServer>,
 ) {
     for (children, points_text) in query.iter() {
         let text = match points_text {
             PointsText::Still => continue,
-            PointsText::Score => points.score.to_string(),
+            PointsText::Score => displayed_score.0.to_string(),
             PointsText::HighScore => points.high_score.to_string(),
         };
 
         let chars = text.chars().rev().collect::<Vec<_>>();
         for child in children.iter() {
-            let (mut image, mut visibility, digit) = decimal_query.get_mut(*child).unwrap();
+            let (mut image, mut visibility, mut digit) = decimal_query.get_mut(*child).unwrap();
+
+            let c = chars.get(digit.digit as usize).copied();
+            if c == digit.rendered {
+                continue;
+            }
+            digit.rendered = c;
 
-            if let Some(c) = chars.get(digit.digit as usize) {
+            if let Some(c) = c {
                 *image = text_provider.get_image(c, Color::WHITE, &asset_server);
                 *visibility = Visibility::Inherited;
             } else {
@@ -329,16 +510,21 @@ fn draw_points(
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn update_points(
+    mut commands: Commands,
     mut points: ResMut<Points>,
     mut pellet_eaten_events: EventReader<PelletEaten>,
     mut ghost_eaten_events: EventReader<GhostEaten>,
     mut ghosts_eaten_counter: ResMut<GhostsEatenCounter>,
-    mut extra_life_events: EventWriter<GetExtraLife>,
+    mut text_provider: ResMut<TextProvider>,
+    asset_server: Res<AssetServer>,
+    mut text_timer: ResMut<BonusTextTimer>,
+    mut stats: StatsRecorder,
+    map: Res<Map>,
 ) {
-    let points_before = points.score;
-
     for pellet_eaten in pellet_eaten_events.read() {
+        stats.record(|stats| stats.pellets += 1);
         if pellet_eaten.power {
             points.score += 50;
             ghosts_eaten_counter.power_pellet_eaten();
@@ -350,58 +536,195 @@ fn update_points(
     for event in ghost_eaten_events.read() {
         let ghosts_eaten = event.eaten_ghosts;
         points.score += 100 * (2_u32.pow(ghosts_eaten as u32 + 1));
+        stats.record(|stats| stats.ghosts += 1);
 
-        ghosts_eaten_counter.ghost_eaten();
-        let total_ghosts_eaten = ghosts_eaten_counter
-            .0
-            .iter()
-            .fold(0, |acc, x| acc + x.unwrap_or(0));
-        if total_ghosts_eaten == 4 * 4 {
-            points.score += 12000;
+        if ghosts_eaten_counter.ghost_eaten() {
+            points.score += ALL_GHOSTS_BONUS;
+            stats.record(|stats| stats.bonus_points += ALL_GHOSTS_BONUS);
+
+            commands.spawn((
+                BonusText,
+                NoMapWrap,
+                map.markers().bonus_symbol,
+                SpriteBundle {
+                    texture: text_provider.get_image(
+                        ALL_GHOSTS_BONUS.to_string(),
+                        Color::CYAN,
+                        &asset_server,
+                    ),
+                    transform: Transform::from_xyz(0.0, 0.0, Layers::OnMapText.as_f32()),
+                    ..default()
+                },
+            ));
+            text_timer.0.reset();
         }
     }
 
     if points.score > points.high_score {
         points.high_score = points.score;
     }
+}
+
+fn flash_one_up(
+    mut query: Query<&mut Visibility, With<OneUpSign>>,
+    mut flash_timer: ResMut<FlashTimer>,
+    time: Res<Time>,
+) {
+    if !flash_timer.0.tick(time.delta()).just_finished() {
+        return;
+    }
 
-    if points_before < 10000 && points.score >= 10000 {
-        extra_life_events.send(GetExtraLife);
+    for mut visibility in query.iter_mut() {
+        *visibility = match *visibility {
+            Visibility::Inherited => Visibility::Hidden,
+            Visibility::Hidden => Visibility::Inherited,
+            Visibility::Visible => unreachable!(),
+        };
     }
 }
 
+fn show_one_up(mut query: Query<&mut Visibility, With<OneUpSign>>) {
+    for mut visibility in query.iter_mut() {
+        *visibility = Visibility::Inherited;
+    }
+}
+
+fn award_extra_life(
+    points: Res<Points>,
+    mut extra_life: ResMut<ExtraLife>,
+    mut extra_life_events: EventWriter<GetExtraLife>,
+    asset_server: Res<AssetServer>,
+    audio: Res<Audio>,
+) {
+    if extra_life.awarded || points.score < extra_life.threshold {
+        return;
+    }
+
+    extra_life.awarded = true;
+    extra_life_events.send(GetExtraLife);
+    audio.play(asset_server.load("sounds/gain_life.wav"));
+}
+
+fn reset_score(mut points: ResMut<Points>, mut extra_life: ResMut<ExtraLife>) {
+    points.score = 0;
+    extra_life.awarded = false;
+}
+
 fn generate_bonus_symbol(
     mut command: Commands,
-    mut pellet_eaten_events: EventReader<PelletEaten>,
-    mut pellets_eaten_counter: ResMut<PelletEatenCounter>,
+    pellets_eaten: Res<PelletsEaten>,
+    mut bonus_symbols_spawned: ResMut<BonusSymbolsSpawned>,
     levels: Res<Levels>,
     asset_server: Res<AssetServer>,
+    mut rng: ResMut<GameRng>,
+    map: Res<Map>,
 ) {
-    for _ in pellet_eaten_events.read() {
-        pellets_eaten_counter.0 += 1;
+    if bonus_symbols_spawned.spawn_next(pellets_eaten.0, &levels.bonus_symbol_dots()) {
+        let bonus_symbol = levels.bonus_symbol();
+        let sprite = SpriteBundle {
+            texture: asset_server.load(bonus_symbol.asset()),
+            transform: Transform::from_xyz(0.0, 0.0, Layers::BonusSymbols.as_f32()),
+            ..default()
+        };
 
-        if pellets_eaten_counter.0 == 70 || pellets_eaten_counter.0 == 170 {
-            let bonus_symbol = levels.bonus_symbol();
-            let symbol_timer = Timer::from_seconds(9.0 + fastrand::f32(), TimerMode::Once);
+        let tunnel_rows = map.tunnel_rows();
+        if (levels.hard_mode || levels.endless) && !tunnel_rows.is_empty() {
+            let enter_row = tunnel_rows[rng.usize(0..tunnel_rows.len())] as f32;
+            let exit_row = tunnel_rows[rng.usize(0..tunnel_rows.len())] as f32;
+            let left = Location::new(-1.0, 0.0);
+            let right = Location::new(map.width() as f32, 0.0);
+            let (enter, exit, direction) = if rng.usize(0..2) == 0 {
+                (left, right, Direction::Right)
+            } else {
+                (right, left, Direction::Left)
+            };
+
+            command.spawn((
+                bonus_symbol,
+                WanderingSymbol {
+                    exit: exit + Location::new(0.0, exit_row),
+                    reached_center: false,
+                },
+                direction,
+                CharacterSpeed::new(WANDERING_SYMBOL_SPEED),
+                NoMapWrap,
+                enter + Location::new(0.0, enter_row),
+                sprite,
+            ));
+        } else {
+            let symbol_timer = Timer::from_seconds(9.0 + rng.f32(), TimerMode::Once);
 
             command.spawn((
                 bonus_symbol,
                 SymbolTimer(symbol_timer),
                 NoMapWrap,
-                Location::new(13.5, 13.0),
-                SpriteBundle {
-                    texture: asset_server.load(bonus_symbol.asset()),
-                    transform: Transform::from_xyz(0.0, 0.0, Layers::BonusSymbols.as_f32()),
-                    ..default()
-                },
+                map.markers().bonus_symbol,
+                sprite,
             ));
         }
     }
 }
 
+/// At every tile the symbol picks the open direction that brings it closest to
+/// the center of the maze, and once there, to its exit. It never turns back.
+fn move_wandering_symbol(
+    mut commands: Commands,
+    mut query: Query<(
+        Entity,
+        &mut Location,
+        &mut Direction,
+        &mut CharacterSpeed,
+        &mut WanderingSymbol,
+    )>,
+    map: Res<Map>,
+    pause_timer: Res<CollisionPauseTimer>,
+) {
+    if !pause_timer.0.finished() {
+        return;
+    }
+
+    let center = map.markers().bonus_symbol;
+    for (entity, mut location, mut direction, mut speed, mut symbol) in query.iter_mut() {
+        speed.tick();
+        if speed.should_miss {
+            continue;
+        }
+
+        if !symbol.reached_center && location.distance_squared(center) <= 1.0 {
+            symbol.reached_center = true;
+        }
+        let target = if symbol.reached_center {
+            symbol.exit
+        } else {
+            center
+        };
+
+        if location.is_tile_center() || !map.is_in_map(*location) {
+            let possible_directions = map.possible_directions(*location);
+            *direction = possible_directions
+                .iter()
+                .filter(|possible| **possible != direction.opposite())
+                .min_by(|a, b| {
+                    let distance_a = location.next_tile(**a).distance_squared(target);
+                    let distance_b = location.next_tile(**b).distance_squared(target);
+                    distance_a.total_cmp(&distance_b)
+                })
+                .copied()
+                .unwrap_or(direction.opposite());
+        }
+
+        location.advance(*direction);
+
+        if location.x() < -1.0 || location.x() > map.width() as f32 {
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
 #[derive(Component)]
 struct BonusText;
 
+#[allow(clippy::too_many_arguments)]
 fn bonus_symbol_collision(
     mut commands: Commands,
     mut query: Query<(Entity, &Location, &BonusSymbol)>,
@@ -410,30 +733,42 @@ fn bonus_symbol_collision(
     asset_server: Res<AssetServer>,
     mut text_timer: ResMut<BonusTextTimer>,
     audio: Res<Audio>,
+    mut stats: StatsRecorder,
 ) {
     let player_location = player_query.single();
-    let (entity, location, bonus_symbol) = query.single_mut();
 
-    if player_location == location {
-        points.score += bonus_symbol.points();
-        commands.entity(entity).despawn();
-
-        commands.spawn((
-            BonusText,
-            NoMapWrap,
-            location.clone(),
-            SpriteBundle {
-                texture: asset_server.load(bonus_symbol.eaten_asset()),
-                transform: Transform::from_xyz(0.0, 0.0, Layers::OnMapText.as_f32()),
-                ..default()
-            },
-        ));
-        text_timer.0.reset();
+    for (entity, location, bonus_symbol) in query.iter_mut() {
+        if touches_symbol(*location, *player_location) {
+            points.score += bonus_symbol.points();
+            stats.record(|stats| {
+                stats.fruit += 1;
+                stats.bonus_points += bonus_symbol.points();
+            });
+            commands.entity(entity).despawn();
+
+            commands.spawn((
+                BonusText,
+                NoMapWrap,
+                *location,
+                SpriteBundle {
+                    texture: asset_server.load(bonus_symbol.eaten_asset()),
+                    transform: Transform::from_xyz(0.0, 0.0, Layers::OnMapText.as_f32()),
+                    ..default()
+                },
+            ));
+            text_timer.0.reset();
 
-        audio.play(asset_server.load("sounds/eat_fruit.wav"));
+            audio.play(asset_server.load("sounds/eat_fruit.wav"));
+        }
     }
 }
 
+/// The player moves in fractional steps, so it can pass through the symbol
+/// without ever landing exactly on its location.
+fn touches_symbol(symbol: Location, player: Location) -> bool {
+    symbol.distance_squared(player) < 0.5 * 0.5
+}
+
 fn bonus_symbol_timer(
     mut commands: Commands,
     mut query: Query<(Entity, &mut SymbolTimer)>,
@@ -443,9 +778,10 @@ fn bonus_symbol_timer(
     if !pause_timer.0.finished() {
         return;
     }
-    let (entity, mut timer) = query.single_mut();
-    if timer.0.tick(time.delta()).just_finished() {
-        commands.entity(entity).despawn();
+    for (entity, mut timer) in query.iter_mut() {
+        if timer.0.tick(time.delta()).just_finished() {
+            commands.entity(entity).despawn();
+        }
     }
 }
 
@@ -468,6 +804,7 @@ fn remove_bonus_text(
     }
 }
 
+#[allow(clippy::type_complexity)]
 fn despawn_symbol(
     mut commands: Commands,
     query: Query<Entity, Or<(With<BonusText>, With<BonusSymbol>)>>,
@@ -476,3 +813,135 @@ fn despawn_symbol(
         commands.entity(entity).despawn_recursive();
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        ghosts::Ghost,
+        level_stats::{GameStats, LevelStats},
+        services::{map::Direction, text::TextProviderPlugin},
+    };
+    use strum::IntoEnumIterator;
+
+    #[test]
+    fn a_fifth_power_pellet_is_counted_safely() {
+        let mut counter = GhostsEatenCounter([None; 4], None);
+        let mut bonuses = 0;
+
+        for _ in 0..5 {
+            counter.power_pellet_eaten();
+            for _ in 0..4 {
+                if counter.ghost_eaten() {
+                    bonuses += 1;
+                }
+            }
+        }
+
+        assert_eq!(counter.total(), 16);
+        assert_eq!(bonuses, 1);
+    }
+
+    #[test]
+    fn ghost_eaten_without_a_power_pellet_starts_no_chain() {
+        let mut counter = GhostsEatenCounter([None; 4], None);
+
+        assert!(!counter.ghost_eaten());
+        assert_eq!(counter.total(), 0);
+
+        // A later power pellet still starts the first chain.
+        counter.power_pellet_eaten();
+        assert_eq!(counter.1, Some(0));
+        assert!(!counter.ghost_eaten());
+        assert_eq!(counter.0[0], Some(1));
+    }
+
+    #[test]
+    fn sixteen_ghosts_are_worth_the_bonus() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, AssetPlugin::default(), TextProviderPlugin))
+            .init_asset::<Image>()
+            .add_event::<PelletEaten>()
+            .add_event::<GhostEaten>()
+            .insert_resource(Points {
+                score: 0,
+                high_score: 0,
+            })
+            .insert_resource(GhostsEatenCounter([None; 4], None))
+            .insert_resource(BonusTextTimer(Timer::from_seconds(3.0, TimerMode::Once)))
+            .insert_resource(LevelStats::default())
+            .insert_resource(GameStats::default())
+            .insert_resource(Map::parse(include_str!("map")).unwrap())
+            .add_systems(Update, update_points);
+
+        for _ in 0..4 {
+            app.world
+                .resource_mut::<Events<PelletEaten>>()
+                .send(PelletEaten { power: true });
+            for (eaten_ghosts, ghost) in Ghost::iter().enumerate() {
+                app.world
+                    .resource_mut::<Events<GhostEaten>>()
+                    .send(GhostEaten {
+                        ghost,
+                        eaten_ghosts,
+                    });
+            }
+            app.update();
+        }
+
+        // 50 for each power pellet, 200, 400, 800 and 1600 for its ghosts.
+        let chain = 50 + 200 + 400 + 800 + 1600;
+        assert_eq!(
+            app.world.resource::<Points>().score,
+            4 * chain + ALL_GHOSTS_BONUS
+        );
+        assert_eq!(
+            app.world.resource::<LevelStats>().0.bonus_points,
+            ALL_GHOSTS_BONUS
+        );
+        assert_eq!(app.world.resource::<GameStats>().0.ghosts, 16);
+
+        let mut bonus_text = app.world.query_filtered::<&Location, With<BonusText>>();
+        let bonus_text = bonus_text.iter(&app.world).collect::<Vec<_>>();
+        assert_eq!(
+            bonus_text,
+            [&app.world.resource::<Map>().markers().bonus_symbol]
+        );
+    }
+
+    #[test]
+    fn symbols_appear_once_their_threshold_is_crossed() {
+        let thresholds = [70, 170];
+        let mut spawned = BonusSymbolsSpawned::default();
+
+        assert!(!spawned.spawn_next(69, &thresholds));
+        // Two pellets in one tick skip over 70.
+        assert!(spawned.spawn_next(71, &thresholds));
+        assert!(!spawned.spawn_next(72, &thresholds));
+
+        // Past both at once, the second follows on the next tick.
+        let mut spawned = BonusSymbolsSpawned::default();
+        assert!(spawned.spawn_next(200, &thresholds));
+        assert!(spawned.spawn_next(200, &thresholds));
+        assert!(!spawned.spawn_next(201, &thresholds));
+    }
+
+    #[test]
+    fn player_passing_through_eats_the_symbol() {
+        let symbol = Location::new(13.5, 13.0);
+
+        for direction in [Direction::Left, Direction::Right] {
+            let mut player = symbol + direction.opposite().get_vec() * 3.0;
+            let mut steps_touching = 0;
+            for _ in 0..6 * 8 {
+                player.advance(direction);
+                if touches_symbol(symbol, player) {
+                    steps_touching += 1;
+                }
+            }
+
+            // The steps less than half a tile away, on either side of it.
+            assert_eq!(steps_touching, 7, "Moving {direction:?}");
+        }
+    }
+}
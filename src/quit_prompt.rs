@@ -0,0 +1,127 @@
+use bevy::{app::AppExit, prelude::*};
+
+use crate::{
+    common::layers::Layers,
+    services::{
+        input::{GamepadInput, KeyBindings, PointerInput},
+        map::Location,
+        text::TextProvider,
+    },
+};
+
+const PROMPT_TEXT: &str = "QUIT? Y/N";
+
+/// The "QUIT? Y/N" prompt shown before exiting the game. Screens that can open
+/// it ignore their own input while it's open and on the frame it closes, so the
+/// answer isn't also handled by them.
+#[derive(Resource, Default)]
+pub struct QuitPrompt(bool);
+
+impl QuitPrompt {
+    pub fn open(&mut self) {
+        self.0 = true;
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.0
+    }
+}
+
+#[derive(Component)]
+struct QuitPromptSign;
+
+pub struct QuitPromptPlugin;
+
+impl Plugin for QuitPromptPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(QuitPrompt::default());
+        app.add_systems(Update, (answer_quit_prompt, draw_quit_prompt).chain());
+    }
+}
+
+pub fn answer_quit_prompt(
+    mut quit_prompt: ResMut<QuitPrompt>,
+    key: Res<Input<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    gamepad: GamepadInput,
+    pointer: PointerInput,
+    text_provider: Res<TextProvider>,
+    mut exit_events: EventWriter<AppExit>,
+) {
+    if !quit_prompt.is_open() {
+        return;
+    }
+
+    // A click or tap on the prompt answers yes, anywhere else no.
+    let clicked_prompt = pointer
+        .just_pressed()
+        .map(|position| prompt_bounds(&text_provider).contains(position));
+
+    let yes = key.any_just_pressed([KeyCode::Y, bindings.confirm])
+        || gamepad.confirm_just_pressed()
+        || clicked_prompt == Some(true);
+    let no = key.any_just_pressed([KeyCode::N, KeyCode::Escape, KeyCode::Back])
+        || gamepad.cancel_just_pressed()
+        || clicked_prompt == Some(false);
+    if !yes && !no {
+        return;
+    }
+
+    quit_prompt.0 = false;
+    if yes {
+        exit_events.send(AppExit);
+    }
+}
+
+fn prompt_location() -> Location {
+    Location::new(13.5, 13.0)
+}
+
+fn prompt_bounds(text_provider: &TextProvider) -> Rect {
+    Rect::from_center_size(
+        prompt_location().world_position(),
+        text_provider.get_size(PROMPT_TEXT) + Vec2::splat(8.0),
+    )
+}
+
+fn draw_quit_prompt(
+    mut commands: Commands,
+    quit_prompt: Res<QuitPrompt>,
+    query: Query<Entity, With<QuitPromptSign>>,
+    mut text_provider: ResMut<TextProvider>,
+    asset_server: Res<AssetServer>,
+) {
+    if !quit_prompt.is_changed() {
+        return;
+    }
+
+    for entity in query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+
+    if !quit_prompt.is_open() {
+        return;
+    }
+
+    commands
+        .spawn((
+            QuitPromptSign,
+            prompt_location(),
+            SpriteBundle {
+                sprite: Sprite {
+                    color: Color::BLACK,
+                    custom_size: Some(prompt_bounds(&text_provider).size()),
+                    ..default()
+                },
+                transform: Transform::from_xyz(0.0, 0.0, Layers::HUD.as_f32() + 1.0),
+                ..default()
+            },
+        ))
+        .with_children(|parent| {
+            parent.spawn(SpriteBundle {
+                texture: text_provider.get_image(PROMPT_TEXT, Color::YELLOW, &asset_server),
+                transform: Transform::from_xyz(0.0, 0.0, 0.5),
+                ..default()
+            });
+        });
+}
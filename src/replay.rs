@@ -0,0 +1,271 @@
+use std::{fmt::Write as _, path::PathBuf};
+
+use bevy::prelude::*;
+use strum::IntoEnumIterator;
+
+use crate::{
+    advance_level,
+    common::{
+        app_state::{AppState, NewRun},
+        game_config::GameConfig,
+        levels::Levels,
+    },
+    ghost_strategy::GhostStrategies,
+    services::{map::Direction, rng::GameRng},
+};
+
+const REPLAY_ARG: &str = "--replay";
+const REPLAYS_DIR: &str = "replays";
+
+/// Everything needed to play a game again: the settings it started with and
+/// the direction held on every game loop tick. Only ticks where the direction
+/// changed are stored.
+#[derive(Default)]
+struct Recording {
+    seed: u64,
+    hard_mode: bool,
+    endless: bool,
+    random_mode_timing: bool,
+    nightmare: bool,
+    starting_lives: usize,
+    first_level: Option<usize>,
+    inputs: Vec<(u64, Option<Direction>)>,
+}
+
+impl Recording {
+    fn to_text(&self) -> String {
+        let mut text = format!(
+            "seed={}\nhard_mode={}\nendless={}\nrandom_mode_timing={}\nnightmare={}\nlives={}\nlevel={}\n",
+            self.seed,
+            self.hard_mode,
+            self.endless,
+            self.random_mode_timing,
+            self.nightmare,
+            self.starting_lives,
+            self.first_level.unwrap_or(1),
+        );
+
+        for (tick, direction) in &self.inputs {
+            match direction {
+                Some(direction) => writeln!(text, "{}={:?}", tick, direction),
+                None => writeln!(text, "{}=None", tick),
+            }
+            .expect("Writing to a string can't fail");
+        }
+
+        text
+    }
+
+    fn parse(text: &str) -> Result<Self, String> {
+        let mut recording = Self {
+            starting_lives: GameConfig::default().starting_lives(),
+            ..default()
+        };
+
+        for (i, line) in text.lines().map(str::trim).enumerate() {
+            if line.is_empty() {
+                continue;
+            }
+
+            let bad_line = || format!("Bad replay line {}: {:?}", i + 1, line);
+            let (key, value) = line.split_once('=').ok_or_else(bad_line)?;
+            let (key, value) = (key.trim(), value.trim());
+
+            match key {
+                "seed" => recording.seed = value.parse().map_err(|_| bad_line())?,
+                "hard_mode" => recording.hard_mode = value.parse().map_err(|_| bad_line())?,
+                "endless" => recording.endless = value.parse().map_err(|_| bad_line())?,
+                "random_mode_timing" => {
+                    recording.random_mode_timing = value.parse().map_err(|_| bad_line())?
+                }
+                "nightmare" => recording.nightmare = value.parse().map_err(|_| bad_line())?,
+                "lives" => recording.starting_lives = value.parse().map_err(|_| bad_line())?,
+                "level" => recording.first_level = Some(value.parse().map_err(|_| bad_line())?),
+                tick => {
+                    let tick = tick.parse().map_err(|_| bad_line())?;
+                    let direction = if value == "None" {
+                        None
+                    } else {
+                        let direction = Direction::iter()
+                            .find(|direction| format!("{:?}", direction) == value)
+                            .ok_or_else(bad_line)?;
+                        Some(direction)
+                    };
+                    recording.inputs.push((tick, direction));
+                }
+            }
+        }
+
+        Ok(recording)
+    }
+}
+
+/// Records the player's input for the current game, so it can be saved next
+/// to the final score.
+#[derive(Resource, Default)]
+pub struct Recorder {
+    tick: u64,
+    recording: Recording,
+}
+
+impl Recorder {
+    pub fn tick(&self) -> u64 {
+        self.tick
+    }
+
+    /// Called once per game loop tick with the direction the player is holding.
+    pub fn record(&mut self, direction: Option<Direction>) {
+        let last_direction = self
+            .recording
+            .inputs
+            .last()
+            .and_then(|(_, direction)| *direction);
+        if self.recording.inputs.is_empty() || last_direction != direction {
+            self.recording.inputs.push((self.tick, direction));
+        }
+        self.tick += 1;
+    }
+
+    /// Writes the recording to `replays/<name>_<score>.replay`.
+    pub fn save(&self, name: &str, score: u32) {
+        let path = PathBuf::from(REPLAYS_DIR).join(format!("{}_{}.replay", name, score));
+        let result = std::fs::create_dir_all(REPLAYS_DIR)
+            .and_then(|_| std::fs::write(&path, self.recording.to_text()));
+
+        if let Err(error) = result {
+            warn!("Failed to save replay to {}: {}", path.display(), error);
+        }
+    }
+}
+
+/// A recording being played back instead of reading the keyboard. Loaded from
+/// the file given with `--replay <file>`.
+#[derive(Resource)]
+pub struct Replay {
+    recording: Recording,
+    next_input: usize,
+    direction: Option<Direction>,
+    started: bool,
+}
+
+impl Replay {
+    fn load() -> Option<Self> {
+        let path = std::env::args()
+            .skip_while(|arg| arg != REPLAY_ARG)
+            .nth(1)?;
+
+        let recording = std::fs::read_to_string(&path)
+            .map_err(|error| error.to_string())
+            .and_then(|text| Recording::parse(&text))
+            .map_err(|error| error!("Failed to load replay {}: {}", path, error))
+            .ok()?;
+
+        info!("Playing replay {}", path);
+        Some(Self {
+            recording,
+            next_input: 0,
+            direction: None,
+            started: false,
+        })
+    }
+
+    /// The direction that was held on the given tick.
+    pub fn direction(&mut self, tick: u64) -> Option<Direction> {
+        while let Some((input_tick, direction)) = self.recording.inputs.get(self.next_input) {
+            if *input_tick > tick {
+                break;
+            }
+            self.direction = *direction;
+            self.next_input += 1;
+        }
+
+        self.direction
+    }
+}
+
+pub struct ReplayPlugin;
+
+impl Plugin for ReplayPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Recorder::default());
+        if let Some(replay) = Replay::load() {
+            app.insert_resource(replay);
+        }
+
+        app.add_systems(Update, start_replay.run_if(in_state(AppState::MainMenu)));
+        app.add_systems(NewRun, start_recording);
+        app.add_systems(
+            OnEnter(AppState::LevelStart),
+            record_first_level.after(advance_level),
+        );
+    }
+}
+
+/// Skips the menu and starts the replayed game with its recorded settings. The
+/// replay is dropped once it's over and the menu is shown again. Runs in
+/// `Update` rather than on entering the menu, so the level start delay is set
+/// up like for any other game.
+fn start_replay(
+    mut commands: Commands,
+    replay: Option<ResMut<Replay>>,
+    mut levels: ResMut<Levels>,
+    mut strategies: ResMut<GhostStrategies>,
+    mut game_config: ResMut<GameConfig>,
+    mut next_state: ResMut<NextState<AppState>>,
+) {
+    let Some(mut replay) = replay else {
+        return;
+    };
+
+    if replay.started {
+        commands.remove_resource::<Replay>();
+        return;
+    }
+    replay.started = true;
+
+    let recording = &replay.recording;
+    levels.hard_mode = recording.hard_mode;
+    levels.endless = recording.endless;
+    levels.random_mode_timing = recording.random_mode_timing;
+    strategies.set_nightmare(recording.nightmare);
+    levels.select(recording.first_level.unwrap_or(1));
+    game_config.set_starting_lives(recording.starting_lives);
+
+    next_state.set(AppState::LevelStart);
+}
+
+/// Every game gets its own seed, drawn from the global one unless a replay
+/// dictates it.
+fn start_recording(
+    mut recorder: ResMut<Recorder>,
+    mut rng: ResMut<GameRng>,
+    replay: Option<Res<Replay>>,
+    levels: Res<Levels>,
+    strategies: Res<GhostStrategies>,
+    game_config: Res<GameConfig>,
+) {
+    let seed = match replay {
+        Some(replay) => replay.recording.seed,
+        None => rng.u64(),
+    };
+    *rng = GameRng::with_seed(seed);
+
+    *recorder = Recorder {
+        tick: 0,
+        recording: Recording {
+            seed,
+            hard_mode: levels.hard_mode,
+            endless: levels.endless,
+            random_mode_timing: levels.random_mode_timing,
+            nightmare: strategies.nightmare(),
+            starting_lives: game_config.starting_lives(),
+            ..default()
+        },
+    };
+}
+
+fn record_first_level(mut recorder: ResMut<Recorder>, levels: Res<Levels>) {
+    if recorder.recording.first_level.is_none() {
+        recorder.recording.first_level = Some(levels.current());
+    }
+}
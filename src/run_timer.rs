@@ -0,0 +1,111 @@
+use std::time::Duration;
+
+use bevy::prelude::*;
+
+use crate::{
+    common::{
+        app_state::{AppState, NewRun},
+        events::CollisionPauseTimer,
+        layers::Layers,
+        sets::{GameLoop, Rendering},
+    },
+    map_render::NoMapWrap,
+    services::{map::Location, text::TextProvider},
+};
+
+/// Play time of the current game. Only counts while the game loop is running,
+/// so level transitions, deaths, collision pauses and the pause screen don't
+/// add to it.
+#[derive(Resource, Default)]
+pub struct RunTimer(Duration);
+
+impl RunTimer {
+    pub fn secs(&self) -> u64 {
+        self.0.as_secs()
+    }
+
+    pub fn from_secs(secs: u64) -> Self {
+        Self(Duration::from_secs(secs))
+    }
+}
+
+/// Formats a number of seconds as MM:SS.
+pub fn format_time(secs: u64) -> String {
+    format!("{:02}:{:02}", secs / 60, secs % 60)
+}
+
+#[derive(Component)]
+struct RunTimerText;
+
+pub struct RunTimerPlugin;
+
+impl Plugin for RunTimerPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(RunTimer::default());
+        app.add_systems(NewRun, reset_run_timer);
+        app.add_systems(OnEnter(AppState::LevelStart), spawn_run_timer);
+        app.add_systems(OnExit(AppState::LevelComplete), despawn_run_timer);
+        app.add_systems(OnEnter(AppState::GameOver), despawn_run_timer);
+        app.add_systems(FixedUpdate, tick_run_timer.in_set(GameLoop::Planning));
+        app.add_systems(
+            Update,
+            draw_run_timer
+                .in_set(Rendering)
+                .run_if(in_state(AppState::MainGame)),
+        );
+    }
+}
+
+fn reset_run_timer(mut run_timer: ResMut<RunTimer>) {
+    *run_timer = RunTimer::default();
+}
+
+fn tick_run_timer(
+    mut run_timer: ResMut<RunTimer>,
+    pause_timer: Res<CollisionPauseTimer>,
+    time: Res<Time>,
+) {
+    if pause_timer.0.finished() {
+        run_timer.0 += time.delta();
+    }
+}
+
+fn spawn_run_timer(
+    mut commands: Commands,
+    run_timer: Res<RunTimer>,
+    mut text_provider: ResMut<TextProvider>,
+    asset_server: Res<AssetServer>,
+) {
+    commands.spawn((
+        RunTimerText,
+        NoMapWrap,
+        Location::new(24.5, 33.0),
+        SpriteBundle {
+            texture: text_provider.get_image(
+                format_time(run_timer.secs()),
+                Color::WHITE,
+                &asset_server,
+            ),
+            transform: Transform::from_xyz(0.0, 0.0, Layers::HUD.as_f32()),
+            ..default()
+        },
+    ));
+}
+
+fn draw_run_timer(
+    mut query: Query<&mut Handle<Image>, With<RunTimerText>>,
+    run_timer: Res<RunTimer>,
+    mut text_provider: ResMut<TextProvider>,
+    asset_server: Res<AssetServer>,
+) {
+    for mut image in query.iter_mut() {
+        *image =
+            text_provider.get_image(format_time(run_timer.secs()), Color::WHITE, &asset_server);
+    }
+}
+
+fn despawn_run_timer(mut commands: Commands, query: Query<Entity, With<RunTimerText>>) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
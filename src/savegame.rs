@@ -0,0 +1,393 @@
+use std::fmt::Debug;
+
+use bevy::prelude::*;
+use strum::IntoEnumIterator;
+
+use crate::{
+    common::{app_state::AppState, levels::Levels},
+    ghost_strategy::GhostStrategies,
+    ghosts::{
+        Ghost, GhostDirections, GhostMode, GhostPlace, GhostState, GlobalGhostModeTimer,
+        SavedGhosts,
+    },
+    pellets::{PelletType, SavedPellets},
+    player::PlayerLives,
+    points::{DisplayedScore, ExtraLife, Points},
+    run_timer::RunTimer,
+    services::{
+        files::write_atomically,
+        map::{Direction, Location},
+    },
+    start_new_run,
+};
+
+const SAVEGAME_FILE: &str = "savegame";
+
+/// A game left from the pause screen, kept in the `savegame` file so it can be
+/// continued from the menu. Along with what carries over between levels it
+/// keeps the pellets left on the board and the ghosts, snapped to the tile. A
+/// continued game starts with "READY" and the player at the start, like after
+/// losing a life.
+#[derive(Default, Debug, PartialEq)]
+struct SavedGame {
+    level: usize,
+    advancements: usize,
+    hard_mode: bool,
+    endless: bool,
+    random_mode_timing: bool,
+    nightmare: bool,
+    lives: usize,
+    score: u32,
+    extra_life_awarded: bool,
+    run_secs: u64,
+    pellets: Vec<Location>,
+    ghosts: GhostState,
+}
+
+impl SavedGame {
+    fn exists() -> bool {
+        std::path::Path::new(SAVEGAME_FILE).exists()
+    }
+
+    /// Reads the saved game and deletes the file, so it can only be continued
+    /// once. Quitting the continued game saves it again.
+    fn take() -> Option<Self> {
+        let text = std::fs::read_to_string(SAVEGAME_FILE);
+        Self::delete();
+
+        text.map_err(|error| error.to_string())
+            .and_then(|text| Self::parse(&text))
+            .map_err(|error| error!("Failed to load saved game: {}", error))
+            .ok()
+    }
+
+    fn save(&self) {
+        if let Err(error) = write_atomically(SAVEGAME_FILE, self.to_text()) {
+            warn!("Failed to save game: {}", error);
+        }
+    }
+
+    fn delete() {
+        if !Self::exists() {
+            return;
+        }
+
+        if let Err(error) = std::fs::remove_file(SAVEGAME_FILE) {
+            warn!("Failed to delete saved game: {}", error);
+        }
+    }
+
+    fn to_text(&self) -> String {
+        let mut text = format!(
+            "level={}\nadvancements={}\nhard_mode={}\nendless={}\nrandom_mode_timing={}\n\
+             nightmare={}\nlives={}\nscore={}\nextra_life_awarded={}\nrun_secs={}\n",
+            self.level,
+            self.advancements,
+            self.hard_mode,
+            self.endless,
+            self.random_mode_timing,
+            self.nightmare,
+            self.lives,
+            self.score,
+            self.extra_life_awarded,
+            self.run_secs,
+        );
+
+        for pellet in &self.pellets {
+            text += &format!("pellet={},{}\n", pellet.x(), pellet.y());
+        }
+
+        text += &format!(
+            "ghost_mode={:?}\nmode_period={}\n",
+            self.ghosts.mode, self.ghosts.mode_period
+        );
+        if let Some(secs) = self.ghosts.mode_secs_left {
+            text += &format!("mode_secs_left={}\n", secs);
+        }
+
+        for (ghost, place) in &self.ghosts.ghosts {
+            text += &match place {
+                GhostPlace::Home { leaving: false } => format!("ghost={:?},home\n", ghost),
+                GhostPlace::Home { leaving: true } => format!("ghost={:?},leaving\n", ghost),
+                GhostPlace::Board { tile, direction } => format!(
+                    "ghost={:?},{},{},{:?}\n",
+                    ghost,
+                    tile.x(),
+                    tile.y(),
+                    direction
+                ),
+            };
+        }
+
+        text
+    }
+
+    fn parse(text: &str) -> Result<Self, String> {
+        let mut saved_game = Self::default();
+        let mut has_ghost_mode = false;
+
+        for (i, line) in text.lines().map(str::trim).enumerate() {
+            if line.is_empty() {
+                continue;
+            }
+
+            let bad_line = || format!("Bad saved game line {}: {:?}", i + 1, line);
+            let (key, value) = line.split_once('=').ok_or_else(bad_line)?;
+            let (key, value) = (key.trim(), value.trim());
+
+            match key {
+                "level" => saved_game.level = value.parse().map_err(|_| bad_line())?,
+                "advancements" => {
+                    saved_game.advancements = value.parse().map_err(|_| bad_line())?
+                }
+                "hard_mode" => saved_game.hard_mode = value.parse().map_err(|_| bad_line())?,
+                "endless" => saved_game.endless = value.parse().map_err(|_| bad_line())?,
+                "random_mode_timing" => {
+                    saved_game.random_mode_timing = value.parse().map_err(|_| bad_line())?
+                }
+                "nightmare" => saved_game.nightmare = value.parse().map_err(|_| bad_line())?,
+                "lives" => saved_game.lives = value.parse().map_err(|_| bad_line())?,
+                "score" => saved_game.score = value.parse().map_err(|_| bad_line())?,
+                "extra_life_awarded" => {
+                    saved_game.extra_life_awarded = value.parse().map_err(|_| bad_line())?
+                }
+                "run_secs" => saved_game.run_secs = value.parse().map_err(|_| bad_line())?,
+                "pellet" => {
+                    let (x, y) = value.split_once(',').ok_or_else(bad_line)?;
+                    let tile = parse_tile(x, y).ok_or_else(bad_line)?;
+                    saved_game.pellets.push(tile);
+                }
+                "ghost_mode" => {
+                    saved_game.ghosts.mode = [GhostMode::Scatter, GhostMode::Chase]
+                        .into_iter()
+                        .find(|mode| format!("{:?}", mode) == value)
+                        .ok_or_else(bad_line)?;
+                    has_ghost_mode = true;
+                }
+                "mode_period" => {
+                    saved_game.ghosts.mode_period = value.parse().map_err(|_| bad_line())?
+                }
+                "mode_secs_left" => {
+                    let secs: f32 = value.parse().map_err(|_| bad_line())?;
+                    if !secs.is_finite() || secs < 0.0 {
+                        return Err(bad_line());
+                    }
+                    saved_game.ghosts.mode_secs_left = Some(secs);
+                }
+                "ghost" => {
+                    let parts = value.split(',').map(str::trim).collect::<Vec<_>>();
+                    let ghost = parse_name(parts[0], Ghost::iter()).ok_or_else(bad_line)?;
+                    let place = match parts[1..] {
+                        ["home"] => GhostPlace::Home { leaving: false },
+                        ["leaving"] => GhostPlace::Home { leaving: true },
+                        [x, y, direction] => GhostPlace::Board {
+                            tile: parse_tile(x, y).ok_or_else(bad_line)?,
+                            direction: parse_name(direction, Direction::all())
+                                .ok_or_else(bad_line)?,
+                        },
+                        _ => return Err(bad_line()),
+                    };
+                    saved_game.ghosts.ghosts.push((ghost, place));
+                }
+                _ => return Err(bad_line()),
+            }
+        }
+
+        if saved_game.level == 0
+            || saved_game.lives == 0
+            || saved_game.pellets.is_empty()
+            || !has_ghost_mode
+        {
+            return Err("Saved game has no level, lives, pellets or ghost mode".to_string());
+        }
+
+        Ok(saved_game)
+    }
+}
+
+/// A tile's center, refusing anything between tiles.
+fn parse_tile(x: &str, y: &str) -> Option<Location> {
+    let x: f32 = x.trim().parse().ok()?;
+    let y: f32 = y.trim().parse().ok()?;
+    (x.fract() == 0.0 && y.fract() == 0.0).then(|| Location::new(x, y))
+}
+
+/// The one of `variants` written as `name` by `{:?}`.
+fn parse_name<T: Debug>(name: &str, variants: impl IntoIterator<Item = T>) -> Option<T> {
+    variants
+        .into_iter()
+        .find(|variant| format!("{:?}", variant) == name)
+}
+
+/// Set by the menu's "Continue" item, so the game that's about to start picks
+/// up the saved one instead of starting over.
+#[derive(Resource, Default)]
+pub struct ContinueGame(pub bool);
+
+impl ContinueGame {
+    /// Whether there's a saved game to continue.
+    pub fn available() -> bool {
+        SavedGame::exists()
+    }
+}
+
+pub struct SaveGamePlugin;
+
+impl Plugin for SaveGamePlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(ContinueGame::default());
+        app.add_systems(
+            OnTransition {
+                from: AppState::MainGame,
+                to: AppState::MainMenu,
+            },
+            save_game,
+        );
+        app.add_systems(
+            OnTransition {
+                from: AppState::LevelStart,
+                to: AppState::MainMenu,
+            },
+            save_game,
+        );
+        app.add_systems(
+            OnExit(AppState::MainMenu),
+            continue_game.after(start_new_run),
+        );
+        app.add_systems(OnEnter(AppState::GameOver), delete_saved_game);
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn save_game(
+    levels: Res<Levels>,
+    strategies: Res<GhostStrategies>,
+    player_lives: Res<PlayerLives>,
+    points: Res<Points>,
+    extra_life: Res<ExtraLife>,
+    run_timer: Res<RunTimer>,
+    pellets: Query<&Location, With<PelletType>>,
+    global_ghost_mode: Res<GhostMode>,
+    global_mode_timer: Res<GlobalGhostModeTimer>,
+    ghosts: Query<(&Ghost, &GhostMode, &Location, &GhostDirections)>,
+) {
+    SavedGame {
+        level: levels.current(),
+        advancements: levels.advancements(),
+        hard_mode: levels.hard_mode,
+        endless: levels.endless,
+        random_mode_timing: levels.random_mode_timing,
+        nightmare: strategies.nightmare(),
+        lives: player_lives.0,
+        score: points.score,
+        extra_life_awarded: extra_life.awarded,
+        run_secs: run_timer.secs(),
+        pellets: pellets.iter().copied().collect(),
+        ghosts: GhostState::capture(*global_ghost_mode, &global_mode_timer, ghosts.iter()),
+    }
+    .save();
+}
+
+/// Runs after the new game's resets, overwriting them with the saved game.
+#[allow(clippy::too_many_arguments)]
+fn continue_game(
+    mut continue_game: ResMut<ContinueGame>,
+    mut levels: ResMut<Levels>,
+    mut strategies: ResMut<GhostStrategies>,
+    mut player_lives: ResMut<PlayerLives>,
+    mut points: ResMut<Points>,
+    mut displayed_score: ResMut<DisplayedScore>,
+    mut extra_life: ResMut<ExtraLife>,
+    mut run_timer: ResMut<RunTimer>,
+    mut saved_pellets: ResMut<SavedPellets>,
+    mut saved_ghosts: ResMut<SavedGhosts>,
+) {
+    if !continue_game.0 {
+        return;
+    }
+    continue_game.0 = false;
+
+    let Some(saved_game) = SavedGame::take() else {
+        return;
+    };
+
+    levels.hard_mode = saved_game.hard_mode;
+    levels.endless = saved_game.endless;
+    levels.random_mode_timing = saved_game.random_mode_timing;
+    strategies.set_nightmare(saved_game.nightmare);
+    levels.resume(saved_game.level, saved_game.advancements);
+    player_lives.0 = saved_game.lives;
+    points.score = saved_game.score;
+    displayed_score.0 = saved_game.score;
+    extra_life.awarded = saved_game.extra_life_awarded;
+    *run_timer = RunTimer::from_secs(saved_game.run_secs);
+    saved_pellets.0 = Some(saved_game.pellets);
+    saved_ghosts.0 = Some(saved_game.ghosts);
+}
+
+fn delete_saved_game() {
+    SavedGame::delete();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn saved_game() -> SavedGame {
+        SavedGame {
+            level: 3,
+            advancements: 2,
+            hard_mode: true,
+            endless: false,
+            random_mode_timing: true,
+            nightmare: true,
+            lives: 2,
+            score: 4560,
+            extra_life_awarded: true,
+            run_secs: 95,
+            pellets: vec![Location::new(1.0, 1.0), Location::new(26.0, 29.0)],
+            ghosts: GhostState {
+                mode: GhostMode::Chase,
+                mode_period: 3,
+                mode_secs_left: Some(12.5),
+                ghosts: vec![
+                    (
+                        Ghost::Blinky,
+                        GhostPlace::Board {
+                            tile: Location::new(13.0, 19.0),
+                            direction: Direction::Left,
+                        },
+                    ),
+                    (Ghost::Pinky, GhostPlace::Home { leaving: true }),
+                    (Ghost::Inky, GhostPlace::Home { leaving: false }),
+                ],
+            },
+        }
+    }
+
+    #[test]
+    fn saved_game_is_read_back() {
+        let saved_game = saved_game();
+        assert_eq!(SavedGame::parse(&saved_game.to_text()), Ok(saved_game));
+
+        let mut last_period = self::saved_game();
+        last_period.ghosts.mode_secs_left = None;
+        assert_eq!(SavedGame::parse(&last_period.to_text()), Ok(last_period));
+    }
+
+    #[test]
+    fn ghosts_between_tiles_are_refused() {
+        let text = saved_game().to_text();
+        for (line, bad_line) in [
+            ("ghost=Blinky,13,19,Left", "ghost=Blinky,13.5,19,Left"),
+            ("ghost=Blinky,13,19,Left", "ghost=Blinky,13,19,Sideways"),
+            ("ghost=Pinky,leaving", "ghost=Sue,leaving"),
+            ("ghost_mode=Chase", "ghost_mode=Frightened"),
+            ("ghost_mode=Chase\n", ""),
+        ] {
+            assert!(text.contains(line));
+            let text = text.replace(line, bad_line);
+            assert!(SavedGame::parse(&text).is_err(), "{bad_line:?}");
+        }
+    }
+}
@@ -0,0 +1,97 @@
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap},
+};
+
+use crate::services::map::{Direction, Location, Map};
+
+/// A shortest path through the maze, kept as the direction to take from each
+/// tile on it so it can be followed without searching again.
+pub struct AStar {
+    goal: Location,
+    steps: HashMap<Location, Direction>,
+}
+
+impl AStar {
+    /// Searches from the `start` tile to the `goal` tile over `Map::neighbors`,
+    /// counting every tile as one step and staying inside the map, so the
+    /// tunnels don't wrap. Only the directions `first_step_allowed` accepts are
+    /// taken out of `start`. `None` when the goal can't be reached.
+    pub fn find(
+        map: &Map,
+        start: Location,
+        goal: Location,
+        first_step_allowed: impl Fn(Direction) -> bool,
+    ) -> Option<Self> {
+        let heuristic = |tile: Location| {
+            let distance = (tile.to_vec() - goal.to_vec()).abs();
+            (distance.x + distance.y) as u32
+        };
+
+        let mut tiles = vec![start];
+        let mut distances = HashMap::from([(start, 0)]);
+        let mut came_from = HashMap::new();
+        let mut open = BinaryHeap::from([Reverse((heuristic(start), 0, 0))]);
+
+        while let Some(Reverse((_, distance, index))) = open.pop() {
+            let tile = tiles[index];
+            if tile == goal {
+                return Some(Self {
+                    goal,
+                    steps: Self::trace_back(goal, &came_from),
+                });
+            }
+            if distance > distances[&tile] {
+                continue;
+            }
+
+            for (direction, next) in map.neighbors(tile) {
+                if !map.is_in_map(next) || (tile == start && !first_step_allowed(direction)) {
+                    continue;
+                }
+
+                let next_distance = distance + 1;
+                if distances
+                    .get(&next)
+                    .is_some_and(|known| *known <= next_distance)
+                {
+                    continue;
+                }
+
+                distances.insert(next, next_distance);
+                came_from.insert(next, (tile, direction));
+                tiles.push(next);
+                open.push(Reverse((
+                    next_distance + heuristic(next),
+                    next_distance,
+                    tiles.len() - 1,
+                )));
+            }
+        }
+
+        None
+    }
+
+    fn trace_back(
+        goal: Location,
+        came_from: &HashMap<Location, (Location, Direction)>,
+    ) -> HashMap<Location, Direction> {
+        let mut steps = HashMap::new();
+        let mut tile = goal;
+        while let Some((previous, direction)) = came_from.get(&tile) {
+            steps.insert(*previous, *direction);
+            tile = *previous;
+        }
+
+        steps
+    }
+
+    pub fn goal(&self) -> Location {
+        self.goal
+    }
+
+    /// The direction to take from `tile` towards the goal, if it's on the path.
+    pub fn step(&self, tile: Location) -> Option<Direction> {
+        self.steps.get(&tile).copied()
+    }
+}
@@ -0,0 +1,55 @@
+use bevy::prelude::*;
+use bevy_kira_audio::prelude::*;
+
+pub const MAX_VOLUME_LEVEL: u8 = 10;
+
+#[derive(Resource, Clone, Copy, Debug)]
+pub struct AudioSettings {
+    pub volume: f64,
+    pub muted: bool,
+}
+
+impl Default for AudioSettings {
+    fn default() -> Self {
+        Self {
+            volume: 1.0,
+            muted: false,
+        }
+    }
+}
+
+impl AudioSettings {
+    pub fn volume_level(&self) -> u8 {
+        (self.volume * MAX_VOLUME_LEVEL as f64).round() as u8
+    }
+
+    pub fn set_volume_level(&mut self, level: u8) {
+        self.volume = level.min(MAX_VOLUME_LEVEL) as f64 / MAX_VOLUME_LEVEL as f64;
+    }
+
+    fn effective_volume(&self) -> f64 {
+        if self.muted {
+            0.0
+        } else {
+            self.volume
+        }
+    }
+}
+
+pub struct AudioSettingsPlugin;
+
+impl Plugin for AudioSettingsPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(AudioSettings::default());
+        app.add_systems(
+            Update,
+            apply_audio_settings.run_if(resource_changed::<AudioSettings>()),
+        );
+    }
+}
+
+/// The main channel keeps its volume for sounds started later, so every sound
+/// played through `Audio` follows the setting.
+fn apply_audio_settings(settings: Res<AudioSettings>, audio: Res<Audio>) {
+    audio.set_volume(settings.effective_volume());
+}
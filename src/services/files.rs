@@ -0,0 +1,22 @@
+use std::{io, path::PathBuf};
+
+use bevy::asset::io::file::FileAssetReader;
+
+const ASSETS_DIR: &str = "assets";
+
+/// Writes `contents` to a temporary file next to `path` and renames it over the
+/// old file, so a crash mid-write leaves the previous contents in place.
+pub fn write_atomically(path: &str, contents: impl AsRef<[u8]>) -> io::Result<()> {
+    let temp_file = format!("{}.tmp", path);
+    std::fs::write(&temp_file, contents)?;
+    std::fs::rename(&temp_file, path)
+}
+
+/// Where Bevy loads `asset` from: the `assets` folder under `BEVY_ASSET_ROOT`,
+/// `CARGO_MANIFEST_DIR` or the executable's directory, whichever is set first,
+/// rather than under the working directory.
+pub fn asset_path(asset: &str) -> PathBuf {
+    FileAssetReader::get_base_path()
+        .join(ASSETS_DIR)
+        .join(asset)
+}
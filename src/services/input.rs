@@ -0,0 +1,250 @@
+use bevy::{ecs::system::SystemParam, prelude::*, window::PrimaryWindow};
+use strum::IntoEnumIterator;
+
+use crate::services::map::Direction;
+
+const STICK_DEADZONE: f32 = 0.5;
+const KEY_BINDINGS_FILE: &str = "keybindings";
+
+#[derive(Resource, Clone, Copy, Debug)]
+pub struct KeyBindings {
+    pub up: KeyCode,
+    pub down: KeyCode,
+    pub left: KeyCode,
+    pub right: KeyCode,
+    pub pause: KeyCode,
+    pub confirm: KeyCode,
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        Self {
+            up: KeyCode::Up,
+            down: KeyCode::Down,
+            left: KeyCode::Left,
+            right: KeyCode::Right,
+            pause: KeyCode::P,
+            confirm: KeyCode::Return,
+        }
+    }
+}
+
+impl KeyBindings {
+    /// Reads overrides from the `keybindings` file, one `action=Key` per line.
+    /// Missing files, unknown actions and unknown keys keep the defaults.
+    pub fn load() -> Self {
+        let mut bindings = Self::default();
+
+        let Ok(text) = std::fs::read_to_string(KEY_BINDINGS_FILE) else {
+            return bindings;
+        };
+
+        for line in text.lines().map(str::trim).filter(|line| !line.is_empty()) {
+            let Some((action, key_name)) = line.split_once('=') else {
+                warn!("Ignoring malformed key binding line: {}", line);
+                continue;
+            };
+
+            let Some(key) = parse_key_code(key_name.trim()) else {
+                warn!("Unknown key {:?} for action {:?}", key_name.trim(), action);
+                continue;
+            };
+
+            match action.trim() {
+                "up" => bindings.up = key,
+                "down" => bindings.down = key,
+                "left" => bindings.left = key,
+                "right" => bindings.right = key,
+                "pause" => bindings.pause = key,
+                "confirm" => bindings.confirm = key,
+                other => warn!("Unknown key binding action {:?}", other),
+            }
+        }
+
+        bindings
+    }
+
+    pub fn direction(&self, direction: Direction) -> KeyCode {
+        match direction {
+            Direction::Up => self.up,
+            Direction::Down => self.down,
+            Direction::Left => self.left,
+            Direction::Right => self.right,
+        }
+    }
+}
+
+fn parse_key_code(name: &str) -> Option<KeyCode> {
+    use KeyCode::*;
+
+    #[rustfmt::skip]
+    const KEYS: &[KeyCode] = &[
+        A, B, C, D, E, F, G, H, I, J, K, L, M, N, O, P, Q, R, S, T, U, V, W, X, Y, Z,
+        Key0, Key1, Key2, Key3, Key4, Key5, Key6, Key7, Key8, Key9,
+        Numpad0, Numpad1, Numpad2, Numpad3, Numpad4,
+        Numpad5, Numpad6, Numpad7, Numpad8, Numpad9,
+        Up, Down, Left, Right, Space, Return, NumpadEnter, Escape, Back, Tab,
+        ShiftLeft, ShiftRight, ControlLeft, ControlRight, AltLeft, AltRight,
+        F1, F2, F3, F4, F5, F6, F7, F8, F9, F10, F11, F12,
+    ];
+
+    KEYS.iter()
+        .find(|key| format!("{:?}", key).eq_ignore_ascii_case(name))
+        .copied()
+}
+
+#[derive(SystemParam)]
+pub struct GamepadInput<'w, 's> {
+    gamepads: Res<'w, Gamepads>,
+    buttons: Res<'w, Input<GamepadButton>>,
+    axes: Res<'w, Axis<GamepadAxis>>,
+    bindings: Res<'w, KeyBindings>,
+    last_stick_direction: Local<'s, Option<Direction>>,
+}
+
+impl GamepadInput<'_, '_> {
+    pub fn pressed(&self, direction: Direction) -> bool {
+        let Some(gamepad) = self.gamepad() else {
+            return false;
+        };
+
+        self.buttons.pressed(Self::dpad_button(gamepad, direction))
+            || self.stick_direction(gamepad) == Some(direction)
+    }
+
+    /// Returns a direction that started being pressed this frame, on either the
+    /// D-pad or the left stick. Should be called at most once per frame since it
+    /// tracks the stick's previous position.
+    pub fn just_pressed(&mut self) -> Option<Direction> {
+        let gamepad = self.gamepad()?;
+
+        let stick_direction = self.stick_direction(gamepad);
+        let stick_just_pressed = if stick_direction != *self.last_stick_direction {
+            stick_direction
+        } else {
+            None
+        };
+        *self.last_stick_direction = stick_direction;
+
+        Direction::iter()
+            .find(|direction| {
+                self.buttons
+                    .just_pressed(Self::dpad_button(gamepad, *direction))
+            })
+            .or(stick_just_pressed)
+    }
+
+    /// Translates this frame's gamepad presses into the keys bound to the same
+    /// actions, so screens driven by keyboard events can handle both the same way.
+    pub fn just_pressed_keys(&mut self) -> Vec<KeyCode> {
+        let mut keys = Vec::new();
+
+        if let Some(direction) = self.just_pressed() {
+            keys.push(self.bindings.direction(direction));
+        }
+
+        if self.confirm_just_pressed() {
+            keys.push(self.bindings.confirm);
+        }
+
+        keys
+    }
+
+    pub fn confirm_just_pressed(&self) -> bool {
+        let Some(gamepad) = self.gamepad() else {
+            return false;
+        };
+
+        self.buttons.any_just_pressed([
+            GamepadButton::new(gamepad, GamepadButtonType::South),
+            GamepadButton::new(gamepad, GamepadButtonType::Start),
+        ])
+    }
+
+    pub fn cancel_just_pressed(&self) -> bool {
+        let Some(gamepad) = self.gamepad() else {
+            return false;
+        };
+
+        self.buttons.any_just_pressed([
+            GamepadButton::new(gamepad, GamepadButtonType::East),
+            GamepadButton::new(gamepad, GamepadButtonType::Select),
+        ])
+    }
+
+    fn gamepad(&self) -> Option<Gamepad> {
+        self.gamepads.iter().min_by_key(|gamepad| gamepad.id)
+    }
+
+    fn dpad_button(gamepad: Gamepad, direction: Direction) -> GamepadButton {
+        let button_type = match direction {
+            Direction::Up => GamepadButtonType::DPadUp,
+            Direction::Down => GamepadButtonType::DPadDown,
+            Direction::Left => GamepadButtonType::DPadLeft,
+            Direction::Right => GamepadButtonType::DPadRight,
+        };
+
+        GamepadButton::new(gamepad, button_type)
+    }
+
+    fn stick_direction(&self, gamepad: Gamepad) -> Option<Direction> {
+        let x = self
+            .axes
+            .get(GamepadAxis::new(gamepad, GamepadAxisType::LeftStickX))
+            .unwrap_or(0.0);
+        let y = self
+            .axes
+            .get(GamepadAxis::new(gamepad, GamepadAxisType::LeftStickY))
+            .unwrap_or(0.0);
+
+        if x.abs().max(y.abs()) < STICK_DEADZONE {
+            None
+        } else if x.abs() > y.abs() {
+            Some(if x > 0.0 {
+                Direction::Right
+            } else {
+                Direction::Left
+            })
+        } else {
+            Some(if y > 0.0 {
+                Direction::Up
+            } else {
+                Direction::Down
+            })
+        }
+    }
+}
+
+/// Mouse and touch input, in world coordinates.
+#[derive(SystemParam)]
+pub struct PointerInput<'w, 's> {
+    windows: Query<'w, 's, &'static Window, With<PrimaryWindow>>,
+    cameras: Query<'w, 's, (&'static Camera, &'static GlobalTransform)>,
+    mouse_buttons: Res<'w, Input<MouseButton>>,
+    touches: Res<'w, Touches>,
+    cursor_moved: EventReader<'w, 's, CursorMoved>,
+}
+
+impl PointerInput<'_, '_> {
+    /// Where a left click or a touch started this frame.
+    pub fn just_pressed(&self) -> Option<Vec2> {
+        let viewport_position = if self.mouse_buttons.just_pressed(MouseButton::Left) {
+            self.windows.get_single().ok()?.cursor_position()?
+        } else {
+            self.touches.iter_just_pressed().next()?.position()
+        };
+
+        self.viewport_to_world(viewport_position)
+    }
+
+    /// Where the mouse cursor is, if it moved this frame.
+    pub fn moved(&mut self) -> Option<Vec2> {
+        let viewport_position = self.cursor_moved.read().last()?.position;
+        self.viewport_to_world(viewport_position)
+    }
+
+    fn viewport_to_world(&self, viewport_position: Vec2) -> Option<Vec2> {
+        let (camera, camera_transform) = self.cameras.get_single().ok()?;
+        camera.viewport_to_world_2d(camera_transform, viewport_position)
+    }
+}
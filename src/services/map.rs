@@ -1,8 +1,18 @@
+use std::{fmt, ops::Mul};
+
 use bevy::prelude::*;
 
-use derive_more::{Add, AddAssign, Deref, DerefMut, Mul, Sub};
+use derive_more::{Add, AddAssign, Sub};
 use strum::{EnumIter, IntoEnumIterator};
 
+/// Width and height of a tile in world space, which is in pixels.
+pub const TILE_SIZE: f32 = 8.0;
+
+/// The location shown at the center of the screen, the world origin. The
+/// screen is the arcade's 28 by 36 tiles: the maze with three rows of score
+/// above it and two rows of lives and fruit below.
+const SCREEN_CENTER: Vec2 = Vec2::new(13.5, 15.5);
+
 enum Tile {
     Wall,
     Empty,
@@ -10,9 +20,11 @@ enum Tile {
     GhostHouseDoor,
 }
 
-#[derive(Component, Copy, Clone, Debug, PartialEq, Add, AddAssign, Sub, Mul, Deref, DerefMut)]
+/// A position in tiles, stored as a whole number of movement steps
+/// (`ADVANCEMENT_DELTA`) so that moving and comparing locations is exact.
+#[derive(Component, Copy, Clone, Debug, PartialEq, Eq, Hash, Add, AddAssign, Sub)]
 pub struct Location {
-    vec: Vec2,
+    steps: IVec2,
 }
 
 impl Location {
@@ -22,21 +34,53 @@ impl Location {
         Self::from_vec(Vec2::new(x, y))
     }
 
+    /// Snaps `vec` to the movement grid, which it's expected to already be on
+    /// up to float error.
     pub fn from_vec(vec: Vec2) -> Self {
-        assert!(vec.x.fract() % Self::ADVANCEMENT_DELTA == 0.0);
-        assert!(vec.y.fract() % Self::ADVANCEMENT_DELTA == 0.0);
+        let location = Self::snapped(vec);
+        debug_assert!(
+            vec.abs_diff_eq(location.to_vec(), 1e-3),
+            "Location {} is off the movement grid",
+            vec
+        );
+
+        location
+    }
+
+    /// The closest location on the movement grid.
+    fn snapped(vec: Vec2) -> Self {
+        Self {
+            steps: (vec / Self::ADVANCEMENT_DELTA).round().as_ivec2(),
+        }
+    }
+
+    pub fn to_vec(self) -> Vec2 {
+        self.steps.as_vec2() * Self::ADVANCEMENT_DELTA
+    }
 
-        Self { vec }
+    pub fn x(&self) -> f32 {
+        self.to_vec().x
+    }
+
+    pub fn y(&self) -> f32 {
+        self.to_vec().y
+    }
+
+    pub fn set_x(&mut self, x: f32) {
+        *self = Self::new(x, self.y());
+    }
+
+    pub fn set_y(&mut self, y: f32) {
+        *self = Self::new(self.x(), y);
     }
 
     pub fn get_tile(&self, direction: Direction) -> Self {
-        let in_tile_vec = (*self + direction.get_vec() * 0.01).vec;
-        let center_tile_vec = in_tile_vec.round();
-        Self::from_vec(center_tile_vec)
+        let in_tile_vec = self.to_vec() + direction.get_vec().to_vec() * 0.01;
+        Self::from_vec(in_tile_vec.round())
     }
 
     pub fn advance(&mut self, direction: Direction) {
-        *self += direction.get_vec() * Self::ADVANCEMENT_DELTA;
+        self.steps += direction.get_vec().steps / Self::steps_per_tile();
     }
 
     pub fn next_tile(&self, direction: Direction) -> Self {
@@ -45,7 +89,54 @@ impl Location {
     }
 
     pub fn is_tile_center(&self) -> bool {
-        self.x.fract() == 0.0 && self.y.fract() == 0.0
+        self.steps % Self::steps_per_tile() == IVec2::ZERO
+    }
+
+    /// Squared distance in tiles, for comparing distances without a square root.
+    pub fn distance_squared(&self, other: Location) -> f32 {
+        self.to_vec().distance_squared(other.to_vec())
+    }
+
+    /// The center of the last tile passed going `direction`, this location
+    /// itself when it's on one.
+    pub fn last_tile(&self, direction: Direction) -> Self {
+        let forward = direction.get_vec().steps.signum();
+        let past_tile = (self.steps * forward).rem_euclid(IVec2::splat(Self::steps_per_tile()));
+        Self {
+            steps: self.steps - past_tile * forward,
+        }
+    }
+
+    /// Whether both locations are in the same tile, the one whose center is
+    /// closest.
+    pub fn tile_eq(&self, other: Location) -> bool {
+        self.to_vec().round() == other.to_vec().round()
+    }
+
+    /// The location `t` of the way from this one to `other`, with `0.5` being
+    /// the middle and values past `1.0` continuing beyond `other`, rounded to
+    /// the closest step.
+    pub fn lerp(&self, other: Location, t: f32) -> Self {
+        Self::snapped(self.to_vec().lerp(other.to_vec(), t))
+    }
+
+    /// Position in world space, where each tile is `TILE_SIZE` pixels and the
+    /// origin is the center of the screen.
+    pub fn world_position(&self) -> Vec2 {
+        (self.to_vec() - SCREEN_CENTER) * TILE_SIZE
+    }
+
+    fn steps_per_tile() -> i32 {
+        (1.0 / Self::ADVANCEMENT_DELTA) as i32
+    }
+}
+
+/// Scales the location, rounding to the closest step.
+impl Mul<f32> for Location {
+    type Output = Self;
+
+    fn mul(self, rhs: f32) -> Self {
+        Self::snapped(self.to_vec() * rhs)
     }
 }
 
@@ -58,6 +149,25 @@ pub enum Direction {
 }
 
 impl Direction {
+    /// Every direction, in the order the arcade ghosts prefer when two turns
+    /// are equally good: up, left, down, then right.
+    pub fn all() -> [Direction; 4] {
+        [
+            Direction::Up,
+            Direction::Left,
+            Direction::Down,
+            Direction::Right,
+        ]
+    }
+
+    /// The direction's place in `all`, lower being preferred.
+    pub fn turn_priority(&self) -> usize {
+        Self::all()
+            .iter()
+            .position(|direction| direction == self)
+            .expect("Every direction is in `all`")
+    }
+
     pub fn get_vec(&self) -> Location {
         let vec = match self {
             Direction::Up => Vec2::new(0.0, 1.0),
@@ -88,63 +198,296 @@ impl Direction {
     }
 }
 
+/// Locations marked in the map text. A character may be repeated on adjacent
+/// tiles, in which case the marker sits between them.
+#[derive(Clone, Copy, Debug)]
+pub struct Markers {
+    pub player: Location,
+    pub blinky: Location,
+    pub pinky: Location,
+    pub inky: Location,
+    pub clyde: Location,
+    /// Where bonus symbols appear, marked with `f`. Optional, defaulting to
+    /// below the ghost house of the original maze.
+    pub bonus_symbol: Location,
+}
+
+/// Why a map text couldn't be parsed. Lines and columns start at 1, counting
+/// from the top of the text.
+#[derive(Debug, Clone, PartialEq)]
+pub enum MapParseError {
+    EmptyMap,
+    RaggedLine {
+        line: usize,
+        expected: usize,
+        found: usize,
+    },
+    BadChar {
+        line: usize,
+        col: usize,
+        ch: char,
+    },
+    MissingMarker(char),
+    BadMarker(char),
+}
+
+impl fmt::Display for MapParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MapParseError::EmptyMap => write!(f, "The map is empty"),
+            MapParseError::RaggedLine {
+                line,
+                expected,
+                found,
+            } => write!(
+                f,
+                "Line {} has {} characters, expected {}",
+                line, found, expected
+            ),
+            MapParseError::BadChar { line, col, ch } => write!(
+                f,
+                "Invalid character {:?} at line {}, column {}",
+                ch, line, col
+            ),
+            MapParseError::MissingMarker(marker) => {
+                write!(f, "The map has no {:?} marker", marker)
+            }
+            MapParseError::BadMarker(marker) => {
+                write!(f, "The {:?} marker must cover one or two tiles", marker)
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct MapPellet {
+    pub location: Location,
+    pub power: bool,
+}
+
 #[derive(Resource)]
 pub struct Map {
     width: usize,
     height: usize,
     map: Vec<Tile>,
+    markers: Markers,
+    pellets: Vec<MapPellet>,
 }
 
 impl Map {
-    pub fn parse(map_text: &str) -> Self {
-        let height = map_text.lines().next().expect("Got empty map").len();
-        let width = map_text.lines().count();
-
-        let map = map_text
+    /// Parses a map written row by row, with the top row of the maze first.
+    pub fn parse(map_text: &str) -> Result<Self, MapParseError> {
+        let width = map_text
             .lines()
-            .flat_map(|line| {
-                assert_eq!(line.len(), height, "All lines must have the same length");
-                line.chars().map(|c| match c {
+            .next()
+            .ok_or(MapParseError::EmptyMap)?
+            .len();
+        let height = map_text.lines().count();
+
+        let mut map = Vec::with_capacity(width * height);
+        let mut marker_tiles: Vec<(char, Vec2)> = Vec::new();
+        let mut pellets = Vec::new();
+
+        for (line_index, line) in map_text.lines().rev().enumerate() {
+            let line_number = height - line_index;
+            if line.len() != width {
+                return Err(MapParseError::RaggedLine {
+                    line: line_number,
+                    expected: width,
+                    found: line.len(),
+                });
+            }
+
+            for (x, c) in line.chars().enumerate() {
+                let tile = match c {
                     'W' => Tile::Wall,
                     ' ' => Tile::Empty,
                     'H' => Tile::GhostHouse,
                     'D' => Tile::GhostHouseDoor,
-                    _ => panic!("Invalid character in map"),
-                })
-            })
-            .collect();
-        Self { width, height, map }
+                    'P' | 'b' | 'f' | '.' | 'o' => Tile::Empty,
+                    'p' | 'i' | 'c' => Tile::GhostHouse,
+                    _ => {
+                        return Err(MapParseError::BadChar {
+                            line: line_number,
+                            col: x + 1,
+                            ch: c,
+                        })
+                    }
+                };
+
+                let location = Vec2::new(x as f32, line_index as f32);
+                if matches!(c, 'P' | 'b' | 'p' | 'i' | 'c' | 'f') {
+                    marker_tiles.push((c, location));
+                } else if matches!(c, '.' | 'o') {
+                    pellets.push(MapPellet {
+                        location: Location::from_vec(location),
+                        power: c == 'o',
+                    });
+                }
+                map.push(tile);
+            }
+        }
+
+        let optional_marker = |marker: char| {
+            let tiles = marker_tiles
+                .iter()
+                .filter(|(c, _)| *c == marker)
+                .map(|(_, tile)| *tile)
+                .collect::<Vec<_>>();
+
+            if tiles.is_empty() {
+                return Ok(None);
+            }
+
+            let center = tiles.iter().sum::<Vec2>() / tiles.len() as f32;
+            if (center * 2.0).fract() != Vec2::ZERO {
+                return Err(MapParseError::BadMarker(marker));
+            }
+
+            Ok(Some(Location::from_vec(center)))
+        };
+        let marker =
+            |marker: char| optional_marker(marker)?.ok_or(MapParseError::MissingMarker(marker));
+
+        let markers = Markers {
+            player: marker('P')?,
+            blinky: marker('b')?,
+            pinky: marker('p')?,
+            inky: marker('i')?,
+            clyde: marker('c')?,
+            bonus_symbol: optional_marker('f')?.unwrap_or(Location::new(13.5, 13.0)),
+        };
+
+        Ok(Self {
+            width,
+            height,
+            map,
+            markers,
+            pellets,
+        })
+    }
+
+    pub fn markers(&self) -> &Markers {
+        &self.markers
+    }
+
+    /// Pellets marked with `.` (regular) and `o` (power) in the map text.
+    pub fn pellets(&self) -> &[MapPellet] {
+        &self.pellets
+    }
+
+    /// Rows whose left edge can be reached from the player's start, which are the
+    /// tunnels leading off the side of the maze.
+    pub fn tunnel_rows(&self) -> Vec<usize> {
+        let player = self.markers.player.to_vec().floor();
+        let mut visited = vec![false; self.width * self.height];
+        let mut to_visit = vec![(player.x as usize, player.y as usize)];
+
+        while let Some((x, y)) = to_visit.pop() {
+            if visited[y * self.width + x] {
+                continue;
+            }
+            visited[y * self.width + x] = true;
+
+            for direction in Direction::iter() {
+                let next = Location::new(x as f32, y as f32) + direction.get_vec();
+                if self.is_in_map(next) && !self.is_blocked(next) {
+                    to_visit.push((next.x() as usize, next.y() as usize));
+                }
+            }
+        }
+
+        (0..self.height)
+            .filter(|y| visited[y * self.width])
+            .collect()
     }
 
+    /// Whether the location is in one of the tunnels at the sides of the maze:
+    /// a row open at both edges, from the edge to where the walls above and
+    /// below it end. Past the edges of the map, while wrapping around, counts too.
+    pub fn is_tunnel(&self, location: Location) -> bool {
+        if location.y().fract() != 0.0 || !self.y_is_in_map(location.y()) {
+            return false;
+        }
+
+        let y = location.y();
+        let walled = |x: usize| {
+            let x = x as f32;
+            !self.is_blocked(Location::new(x, y))
+                && self.is_blocked(Location::new(x, y + 1.0))
+                && self.is_blocked(Location::new(x, y - 1.0))
+        };
+        if !walled(0) || !walled(self.width - 1) {
+            return false;
+        }
+
+        let left_end = (0..self.width).take_while(|x| walled(*x)).count() - 1;
+        let right_start = self.width - (0..self.width).rev().take_while(|x| walled(*x)).count();
+
+        location.x() <= left_end as f32 || location.x() >= right_start as f32
+    }
+
+    /// The directions leading into an open tile. Halfway between two tiles only
+    /// the directions along that axis are considered, each checked against the
+    /// tile it moves into rather than the one after it. Past the edges of the
+    /// map, in a tunnel, there are no walls to check.
     pub fn possible_directions(&self, location: Location) -> Vec<Direction> {
-        if location.x.fract() == 0.5 || !self.x_is_in_map(location.x) {
+        if !self.x_is_in_map(location.x()) {
             return vec![Direction::Left, Direction::Right];
-        } else if location.y.fract() == 0.5 || !self.y_is_in_map(location.y) {
+        } else if !self.y_is_in_map(location.y()) {
             return vec![Direction::Up, Direction::Down];
         }
 
+        let axis = if location.x().fract() == 0.5 {
+            Some([Direction::Left, Direction::Right])
+        } else if location.y().fract() == 0.5 {
+            Some([Direction::Up, Direction::Down])
+        } else {
+            None
+        };
+        if let Some(axis) = axis {
+            return axis
+                .into_iter()
+                .filter(|direction| !self.is_blocked(location.get_tile(*direction)))
+                .collect();
+        }
+
         Direction::iter()
             .filter(|direction| {
                 let tile_to_check = location.next_tile(*direction);
-                return !self.is_blocked(tile_to_check);
+                !self.is_blocked(tile_to_check)
             })
             .collect()
     }
 
+    /// The tiles reachable in one step from `tile`, with the direction leading to
+    /// each, following the same rules as `possible_directions`.
+    pub fn neighbors(&self, tile: Location) -> impl Iterator<Item = (Direction, Location)> {
+        self.possible_directions(tile)
+            .into_iter()
+            .map(move |direction| (direction, tile.next_tile(direction)))
+    }
+
+    /// Whether characters can move through the tile. Tiles outside the map are
+    /// open, which is how the tunnels wrap around.
+    pub fn is_open(&self, tile: Location) -> bool {
+        !self.is_blocked(tile)
+    }
+
     pub fn is_blocked(&self, location: Location) -> bool {
         !matches!(self.get(location), Some(Tile::Empty) | None)
     }
 
     fn get(&self, location: Location) -> Option<&Tile> {
-        let tile_vec = location.round();
+        let tile_vec = location.to_vec().round();
 
-        if !self.is_in_map(Location { vec: tile_vec }) {
+        if !self.is_in_map(Location::from_vec(tile_vec)) {
             None
         } else {
             let x = tile_vec.x as usize;
             let y = tile_vec.y as usize;
 
-            self.map.get(x * self.height + y)
+            self.map.get(y * self.width + x)
         }
     }
 
@@ -156,8 +499,16 @@ impl Map {
         self.height
     }
 
+    /// The middle of the maze, where its picture is centered.
+    pub fn center(&self) -> Location {
+        Location::new(
+            (self.width - 1) as f32 / 2.0,
+            (self.height - 1) as f32 / 2.0,
+        )
+    }
+
     pub fn is_in_map(&self, location: Location) -> bool {
-        self.x_is_in_map(location.x) && self.y_is_in_map(location.y)
+        self.x_is_in_map(location.x()) && self.y_is_in_map(location.y())
     }
 
     fn y_is_in_map(&self, y: f32) -> bool {
@@ -176,8 +527,8 @@ impl Map {
             .map(|direction| next_tile.next_tile(*direction))
             .collect::<Vec<_>>();
 
-        let start_x = current_tile.x as i32 - 3;
-        let start_y = current_tile.y as i32 - 3;
+        let start_x = current_tile.x() as i32 - 3;
+        let start_y = current_tile.y() as i32 - 3;
         let end_x = start_x + 7;
         let end_y = start_y + 7;
 
@@ -185,9 +536,9 @@ impl Map {
         for y in start_y..end_y {
             for x in start_x..end_x {
                 let vec = Vec2::new(x as f32, y as f32);
-                if vec == *current_tile {
+                if vec == current_tile.to_vec() {
                     result.push('C');
-                } else if vec == *next_tile {
+                } else if vec == next_tile.to_vec() {
                     result.push('N');
                 } else if possible_locations.contains(&Location::new(x as f32, y as f32)) {
                     result.push('P');
@@ -203,3 +554,203 @@ impl Map {
         println!("{}", result);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A maze wider than it's tall, so swapped axes show up.
+    const SMALL_MAP: &str = "\
+WWWWWWW
+WPb  .W
+W pic W
+WWWWWWW";
+
+    #[test]
+    fn asymmetric_map_keeps_its_axes() {
+        let map = Map::parse(SMALL_MAP).unwrap();
+
+        assert_eq!(map.width(), 7);
+        assert_eq!(map.height(), 4);
+        assert!(map.is_in_map(Location::new(6.0, 3.0)));
+        assert!(!map.is_in_map(Location::new(3.0, 6.0)));
+
+        // The bottom row is y = 0.
+        assert!(map.is_blocked(Location::new(1.0, 0.0)));
+        assert!(!map.is_blocked(Location::new(1.0, 1.0)));
+        assert!(map.is_blocked(Location::new(2.0, 1.0)));
+        assert!(!map.is_blocked(Location::new(5.0, 2.0)));
+        assert_eq!(map.markers().player, Location::new(1.0, 2.0));
+        assert_eq!(map.markers().inky, Location::new(3.0, 1.0));
+    }
+
+    #[test]
+    fn last_tile_is_behind_the_direction() {
+        let location = Location::new(5.0, 2.0).lerp(Location::new(6.0, 2.0), 0.75);
+
+        assert_eq!(
+            location.last_tile(Direction::Right),
+            Location::new(5.0, 2.0)
+        );
+        assert_eq!(location.last_tile(Direction::Left), Location::new(6.0, 2.0));
+        assert_eq!(
+            Location::new(5.0, 2.0).last_tile(Direction::Up),
+            Location::new(5.0, 2.0)
+        );
+    }
+
+    #[test]
+    fn empty_map_is_refused() {
+        assert_eq!(Map::parse("").err(), Some(MapParseError::EmptyMap));
+    }
+
+    #[test]
+    fn ragged_line_is_refused() {
+        let map_text = SMALL_MAP.replace("W pic W", "W pic  W");
+
+        assert_eq!(
+            Map::parse(&map_text).err(),
+            Some(MapParseError::RaggedLine {
+                line: 3,
+                expected: 7,
+                found: 8,
+            })
+        );
+    }
+
+    #[test]
+    fn bad_char_is_refused() {
+        let map_text = SMALL_MAP.replace("WPb  .W", "WPb  ?W");
+
+        assert_eq!(
+            Map::parse(&map_text).err(),
+            Some(MapParseError::BadChar {
+                line: 2,
+                col: 6,
+                ch: '?',
+            })
+        );
+    }
+
+    #[test]
+    fn missing_marker_is_refused() {
+        let map_text = SMALL_MAP.replace('b', " ");
+
+        assert_eq!(
+            Map::parse(&map_text).err(),
+            Some(MapParseError::MissingMarker('b'))
+        );
+    }
+
+    #[test]
+    fn scattered_marker_is_refused() {
+        let map_text = SMALL_MAP.replace("WPb  .W", "WPbPP.W");
+
+        assert_eq!(
+            Map::parse(&map_text).err(),
+            Some(MapParseError::BadMarker('P'))
+        );
+    }
+
+    #[test]
+    fn tunnel_runs_from_the_edges_to_the_walls_around_it() {
+        let map = Map::parse(include_str!("../map")).unwrap();
+        let tunnel = |x: f32, y: f32| map.is_tunnel(Location::new(x, y));
+
+        for x in [-2.0, -0.5, 0.0, 5.0, 22.0, 27.0, 28.5, 29.0] {
+            assert!(tunnel(x, 16.0), "x = {x}");
+        }
+        for x in [5.5, 6.0, 13.0, 21.0, 21.875] {
+            assert!(!tunnel(x, 16.0), "x = {x}");
+        }
+        assert!(!tunnel(2.0, 15.0));
+        assert!(!tunnel(2.0, 16.5));
+        assert!(!tunnel(1.0, 1.0));
+        assert!(!tunnel(0.0, -1.0));
+    }
+
+    #[test]
+    fn location_helpers() {
+        let a = Location::new(1.0, 2.0);
+        let b = Location::new(4.0, 6.0);
+
+        assert_eq!(a.distance_squared(b), 25.0);
+        assert_eq!(b.distance_squared(a), 25.0);
+        assert_eq!(a.distance_squared(a), 0.0);
+
+        assert!(a.tile_eq(Location::new(1.375, 1.625)));
+        assert!(!a.tile_eq(Location::new(1.625, 2.0)));
+        assert!(!a.tile_eq(b));
+
+        assert_eq!(a.lerp(b, 0.0), a);
+        assert_eq!(a.lerp(b, 1.0), b);
+        assert_eq!(a.lerp(b, 0.5), Location::new(2.5, 4.0));
+        assert_eq!(a.lerp(b, 2.0), Location::new(7.0, 10.0));
+        // A third of the way is off the grid, so it's rounded to the closest step.
+        assert_eq!(a.lerp(b, 1.0 / 3.0), Location::new(2.0, 3.375));
+    }
+
+    #[test]
+    fn possible_directions_at_centers_and_between_tiles() {
+        use Direction::*;
+
+        let map = Map::parse(include_str!("../map")).unwrap();
+        let directions = |x: f32, y: f32| map.possible_directions(Location::new(x, y));
+
+        // Tile centers check every direction.
+        assert_eq!(directions(1.0, 1.0), vec![Up, Right]);
+        assert_eq!(directions(6.0, 7.0), vec![Up, Right, Down]);
+
+        // Halfway between tiles only the axis being moved along, even where
+        // there's an opening to the side of one of the two tiles.
+        assert_eq!(directions(13.5, 7.0), vec![Left, Right]);
+        assert_eq!(directions(12.5, 7.0), vec![Left, Right]);
+        assert_eq!(directions(1.0, 1.5), vec![Up, Down]);
+        // Each checked against the tile it moves into.
+        assert_eq!(directions(3.5, 7.0), vec![Left]);
+    }
+
+    #[test]
+    fn neighbors_are_the_open_adjacent_tiles() {
+        let map = Map::parse(include_str!("../map")).unwrap();
+
+        assert_eq!(
+            map.neighbors(Location::new(6.0, 7.0)).collect::<Vec<_>>(),
+            vec![
+                (Direction::Up, Location::new(6.0, 8.0)),
+                (Direction::Right, Location::new(7.0, 7.0)),
+                (Direction::Down, Location::new(6.0, 6.0)),
+            ]
+        );
+        for (_, tile) in map.neighbors(Location::new(1.0, 1.0)) {
+            assert!(map.is_open(tile));
+        }
+
+        assert!(map.is_open(Location::new(1.0, 1.0)));
+        assert!(!map.is_open(Location::new(0.0, 1.0)));
+        // The ghost house and its door are closed to the player.
+        assert!(!map.is_open(Location::new(13.0, 16.0)));
+        assert!(!map.is_open(Location::new(13.0, 18.0)));
+        // Past the edges, where the tunnels wrap around, is open.
+        assert!(map.is_open(Location::new(-1.0, 16.0)));
+        assert!(map.is_open(Location::new(28.0, 16.0)));
+    }
+
+    #[test]
+    fn world_position_is_centered_on_the_screen() {
+        assert_eq!(Location::new(13.5, 15.5).world_position(), Vec2::ZERO);
+        assert_eq!(
+            Location::new(14.5, 14.5).world_position(),
+            Vec2::new(TILE_SIZE, -TILE_SIZE)
+        );
+        // The corners of the stock maze.
+        assert_eq!(
+            Location::new(0.0, 0.0).world_position(),
+            Vec2::new(-108.0, -124.0)
+        );
+        assert_eq!(
+            Location::new(27.0, 30.0).world_position(),
+            Vec2::new(108.0, 116.0)
+        );
+    }
+}
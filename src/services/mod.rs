@@ -1,3 +1,9 @@
+pub mod a_star;
+pub mod audio;
+pub mod files;
+pub mod input;
 pub mod map;
+pub mod rng;
+pub mod scores;
 pub mod speed;
 pub mod text;
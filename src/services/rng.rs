@@ -0,0 +1,83 @@
+use std::ops::Range;
+
+use bevy::prelude::*;
+
+const SEED_ENV_VAR: &str = "PACMAN_SEED";
+const SEED_ARG: &str = "--seed";
+
+/// Source of every random choice in the game, so a run can be replayed by
+/// starting with the same seed.
+#[derive(Resource)]
+pub struct GameRng {
+    rng: fastrand::Rng,
+}
+
+impl GameRng {
+    pub fn with_seed(seed: u64) -> Self {
+        Self {
+            rng: fastrand::Rng::with_seed(seed),
+        }
+    }
+
+    /// Seeds from the `--seed <n>` argument, then the `PACMAN_SEED` variable,
+    /// falling back to a random seed. The chosen seed is logged either way.
+    pub fn load() -> Self {
+        let seed = seed_from_args()
+            .or_else(seed_from_env)
+            .unwrap_or_else(|| fastrand::u64(..));
+        info!("Using random seed {}", seed);
+
+        Self::with_seed(seed)
+    }
+
+    pub fn usize(&mut self, range: Range<usize>) -> usize {
+        self.rng.usize(range)
+    }
+
+    pub fn u64(&mut self) -> u64 {
+        self.rng.u64(..)
+    }
+
+    pub fn f32(&mut self) -> f32 {
+        self.rng.f32()
+    }
+}
+
+fn seed_from_args() -> Option<u64> {
+    let mut args = std::env::args().skip_while(|arg| arg != SEED_ARG).skip(1);
+    let value = args.next()?;
+
+    value
+        .parse()
+        .map_err(|_| warn!("Ignoring invalid {} value {:?}", SEED_ARG, value))
+        .ok()
+}
+
+fn seed_from_env() -> Option<u64> {
+    let value = std::env::var(SEED_ENV_VAR).ok()?;
+
+    value
+        .parse()
+        .map_err(|_| warn!("Ignoring invalid {} value {:?}", SEED_ENV_VAR, value))
+        .ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Draws from every kind of random choice the game makes.
+    fn draws(rng: &mut GameRng) -> Vec<(usize, u64, u32)> {
+        (0..100)
+            .map(|_| (rng.usize(0..4), rng.u64(), rng.f32().to_bits()))
+            .collect()
+    }
+
+    #[test]
+    fn same_seed_gives_the_same_run() {
+        let run = draws(&mut GameRng::with_seed(7));
+
+        assert_eq!(draws(&mut GameRng::with_seed(7)), run);
+        assert_ne!(draws(&mut GameRng::with_seed(8)), run);
+    }
+}
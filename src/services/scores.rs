@@ -0,0 +1,105 @@
+use bevy::prelude::*;
+
+pub const SCORES_FILE: &str = "scores";
+
+/// One saved score: the player's name, their score, and the play time in
+/// seconds when it was recorded.
+pub type ScoreEntry = (String, u32, Option<u64>);
+
+/// Lines are `name:score:time`. Scores saved before the time was recorded
+/// only have the first two fields.
+pub fn parse_line(line: &str) -> Option<ScoreEntry> {
+    let mut fields = line.split(':');
+    let name = fields.next()?;
+    let score = fields.next()?.parse::<u32>().ok()?;
+    let time = fields.next().and_then(|time| time.parse::<u64>().ok());
+
+    Some((name.to_string(), score, time))
+}
+
+/// Reads every score in the scores file, skipping malformed lines with a
+/// warning. With `strict` a malformed line panics instead.
+pub fn load(strict: bool) -> Vec<ScoreEntry> {
+    let Ok(text) = std::fs::read_to_string(SCORES_FILE) else {
+        return vec![];
+    };
+
+    parse(&text, strict)
+}
+
+fn parse(text: &str, strict: bool) -> Vec<ScoreEntry> {
+    text.lines()
+        .filter_map(|line| {
+            let entry = parse_line(line);
+            if entry.is_none() {
+                assert!(!strict, "Scores file is corrupted: {:?}", line);
+                warn!("Skipping malformed scores line {:?}", line);
+            }
+            entry
+        })
+        .collect()
+}
+
+/// The best of `entries`, 0 without any.
+pub fn best_score(entries: &[ScoreEntry]) -> u32 {
+    entries
+        .iter()
+        .map(|(_, score, _)| *score)
+        .max()
+        .unwrap_or(0)
+}
+
+/// Empties the scores file. Without one there's nothing to clear.
+pub fn clear() {
+    if !std::path::Path::new(SCORES_FILE).exists() {
+        return;
+    }
+
+    if let Err(error) = std::fs::write(SCORES_FILE, "") {
+        warn!("Failed to clear scores: {}", error);
+    }
+}
+
+/// Every saved score, highest first.
+pub fn load_best_first(strict: bool) -> Vec<ScoreEntry> {
+    let mut entries = load(strict);
+    entries.sort_by(|(_, a, _), (_, b, _)| b.cmp(a));
+    entries
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MIXED_SCORES: &str = "\
+ADAR:3000:120
+garbage
+BOB:notanumber
+BIG:99999999999:60
+OLD:1500
+
+:
+ZED:10:soon";
+
+    #[test]
+    fn malformed_lines_are_skipped() {
+        let entries = parse(MIXED_SCORES, false);
+        assert_eq!(
+            entries,
+            vec![
+                ("ADAR".to_string(), 3000, Some(120)),
+                ("OLD".to_string(), 1500, None),
+                ("ZED".to_string(), 10, None),
+            ]
+        );
+
+        // The score too big to read doesn't count towards the high score.
+        assert_eq!(best_score(&entries), 3000);
+    }
+
+    #[test]
+    #[should_panic(expected = "Scores file is corrupted")]
+    fn strict_load_panics_on_malformed_lines() {
+        parse(MIXED_SCORES, true);
+    }
+}
@@ -1,5 +1,12 @@
 use bevy::prelude::*;
 
+/// Speeds are fractions of the arcade's 100% speed. The game loop ticks at the
+/// fastest speed any character can reach, which is 105%.
+pub const MAX_SPEED: f32 = 1.05;
+
+/// Decides on each game loop tick whether a character moves, so that over time
+/// it moves on `speed / MAX_SPEED` of the ticks. Skipped ticks are spread out
+/// evenly rather than bunched together, e.g. 0.75 moves on 5 of every 7 ticks.
 #[derive(Component)]
 pub struct CharacterSpeed {
     speed: f32,
@@ -10,18 +17,28 @@ pub struct CharacterSpeed {
 
 impl CharacterSpeed {
     pub fn new(speed: f32) -> Self {
-        assert!(speed >= 0.0 && speed <= 1.05);
+        let clamped = Self::clamp(speed);
+        if clamped != speed {
+            warn!("Speed {} is out of range, using {}", speed, clamped);
+        }
 
         Self {
-            speed,
+            speed: clamped,
             advancement_counter: 0.0,
             missed_counter: 0.0,
             should_miss: false,
         }
     }
 
+    /// Speeds outside `0.0..=MAX_SPEED`, like ones from a levels file, are
+    /// clamped into it. The warning is only logged when the speed changes, not
+    /// on every tick it's set again.
     pub fn set_speed(&mut self, speed: f32) {
-        assert!(speed >= 0.0 && speed <= 1.05);
+        let clamped = Self::clamp(speed);
+        if clamped != speed && clamped != self.speed {
+            warn!("Speed {} is out of range, using {}", speed, clamped);
+        }
+        let speed = clamped;
 
         if speed != self.speed {
             self.speed = speed;
@@ -31,11 +48,21 @@ impl CharacterSpeed {
         }
     }
 
+    fn clamp(speed: f32) -> f32 {
+        if speed.is_nan() {
+            0.0
+        } else {
+            speed.clamp(0.0, MAX_SPEED)
+        }
+    }
+
+    /// Misses this tick whenever moving on it would put the realized speed
+    /// above the target, which keeps the two within one tick of each other.
     pub fn tick(&mut self) {
         self.advancement_counter += 1.0;
 
         let precent_missed = self.missed_counter / self.advancement_counter;
-        let precent_hit = (1.0 - precent_missed) * 1.05;
+        let precent_hit = (1.0 - precent_missed) * MAX_SPEED;
 
         if precent_hit > self.speed {
             self.missed_counter += 1.0;
@@ -45,3 +72,65 @@ impl CharacterSpeed {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The ticks out of `ticks` on which a character at `speed` moves.
+    fn moves(speed: f32, ticks: usize) -> usize {
+        let mut character_speed = CharacterSpeed::new(speed);
+        (0..ticks)
+            .filter(|_| {
+                character_speed.tick();
+                !character_speed.should_miss
+            })
+            .count()
+    }
+
+    #[test]
+    fn moves_on_its_share_of_the_ticks() {
+        const TICKS: usize = 10_000;
+        for speed in [0.4, 0.75, 0.95, 1.05] {
+            let ratio = moves(speed, TICKS) as f32 / TICKS as f32;
+            assert!(
+                (ratio - speed / MAX_SPEED).abs() <= 1.0 / TICKS as f32,
+                "Speed {speed} moved on {ratio} of the ticks"
+            );
+        }
+    }
+
+    #[test]
+    fn stays_within_a_tick_of_its_speed() {
+        for speed in [0.4, 0.75, 0.95, 1.05] {
+            for ticks in 1..=500 {
+                let expected = ticks as f32 * speed / MAX_SPEED;
+                let moved = moves(speed, ticks) as f32;
+                assert!(
+                    (moved - expected).abs() <= 1.0,
+                    "Speed {speed} moved {moved} times in {ticks} ticks"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn three_quarters_speed_moves_on_five_of_seven_ticks() {
+        assert_eq!(moves(0.75, 7), 5);
+        assert_eq!(moves(0.75, 700), 500);
+    }
+
+    #[test]
+    fn out_of_range_speeds_are_clamped() {
+        assert_eq!(CharacterSpeed::new(1.06).speed, MAX_SPEED);
+        assert_eq!(CharacterSpeed::new(-0.5).speed, 0.0);
+        assert_eq!(CharacterSpeed::new(f32::NAN).speed, 0.0);
+        assert_eq!(CharacterSpeed::new(0.8).speed, 0.8);
+
+        let mut speed = CharacterSpeed::new(0.8);
+        speed.set_speed(2.0);
+        assert_eq!(speed.speed, MAX_SPEED);
+        assert_eq!(moves(2.0, 100), 100);
+        assert_eq!(moves(-1.0, 100), 0);
+    }
+}
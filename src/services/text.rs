@@ -1,32 +1,55 @@
-use std::fmt::Display;
+use std::{fmt::Display, sync::Mutex};
 
 use bevy::{prelude::*, utils::HashMap};
 use text_to_png::{FontSize, TextRenderer};
 
-const ASSET_DIR: &str = "assets";
-const TEMP_FONTS_DIR: &str = "temp_fonts";
+use crate::services::files::asset_path;
+use crate::settings::Settings;
 
 #[derive(Resource)]
 pub struct TextProvider {
     renderer: TextRenderer,
     cache: HashMap<String, Handle<Image>>,
+    /// Measuring renders the whole text, so sizes are kept like the images.
+    /// Behind a lock because layout code measures through a shared reference.
+    size_cache: Mutex<HashMap<String, Vec2>>,
 }
 
 pub struct TextProviderPlugin;
 
 impl Plugin for TextProviderPlugin {
     fn build(&self, app: &mut App) {
-        std::fs::create_dir_all(format!("{}/{}", ASSET_DIR, TEMP_FONTS_DIR)).unwrap();
+        let renderer = app
+            .world
+            .get_resource::<Settings>()
+            .and_then(|settings| settings.font.as_deref())
+            .and_then(load_font)
+            .unwrap_or_else(|| {
+                TextRenderer::try_new_with_ttf_font_data(include_bytes!("../../assets/joystix.otf"))
+                    .expect("Failed to create text renderer")
+            });
+
         app.insert_resource(TextProvider {
-            renderer: TextRenderer::try_new_with_ttf_font_data(include_bytes!(
-                "../../assets/joystix.otf"
-            ))
-            .expect("Failed to create text renderer"),
+            renderer,
             cache: HashMap::new(),
+            size_cache: Mutex::new(HashMap::new()),
         });
     }
 }
 
+/// The renderer for the font file named in the settings, looked up in `assets`.
+/// `None` if it can't be read or isn't a font, so the built in one is used.
+fn load_font(font: &str) -> Option<TextRenderer> {
+    let path = asset_path(font);
+    let data = std::fs::read(&path)
+        .map_err(|error| warn!("Failed to read font {}: {}", path.display(), error))
+        .ok()?;
+
+    TextRenderer::try_new_with_ttf_font_data(data)
+        .map_err(|error| warn!("Failed to load font {}: {}", path.display(), error))
+        .ok()
+}
+
 impl TextProvider {
     pub fn get_image<T: Display>(
         &mut self,
@@ -39,16 +62,9 @@ impl TextProvider {
 
         let [r, g, b, _] = color.as_rgba_u8();
 
-        let file_name = format!(
-            "{}/{}_{:02x}{:02x}{:02x}.png",
-            TEMP_FONTS_DIR,
-            text.replace(":", "c"),
-            r,
-            g,
-            b
-        );
+        let key = format!("{}_{:02x}{:02x}{:02x}", text, r, g, b);
 
-        if let Some(handle) = self.cache.get(&file_name) {
+        if let Some(handle) = self.cache.get(&key) {
             return handle.clone();
         }
 
@@ -58,31 +74,33 @@ impl TextProvider {
             .render_text_to_png_data(&text, FontSize::Direct(10.0), color)
             .expect("Failed to render text");
 
-        let path = format!("{}/{}", ASSET_DIR, file_name);
-        std::fs::write(path, &png.data).expect("Failed to write image to file");
+        let image = image::load_from_memory_with_format(&png.data, image::ImageFormat::Png)
+            .expect("Failed to decode rendered text");
 
-        let handle = asset_server.load(&file_name);
-        self.cache.insert(file_name, handle.clone());
-        return handle;
+        let handle = asset_server.add(Image::from_dynamic(image, true));
+        self.cache.insert(key, handle.clone());
+        handle
     }
 
     pub fn get_size<T: Display>(&self, text: T) -> Vec2 {
         let text = format!("{}", text);
         let text = text.to_uppercase();
+
+        let mut size_cache = self.size_cache.lock().expect("Text size cache poisoned");
+        if let Some(size) = size_cache.get(&text) {
+            return *size;
+        }
+
         let png = self
             .renderer
             .render_text_to_png_data(
-                text,
+                &text,
                 FontSize::FillHeight(7.0),
                 text_to_png::Color::default(),
             )
             .expect("Failed to measure text");
-        return Vec2::new(png.size.width as f32, png.size.height as f32);
-    }
-}
-
-impl Drop for TextProviderPlugin {
-    fn drop(&mut self) {
-        std::fs::remove_dir_all("assets/temp_fonts").unwrap();
+        let size = Vec2::new(png.size.width as f32, png.size.height as f32);
+        size_cache.insert(text, size);
+        size
     }
 }
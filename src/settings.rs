@@ -0,0 +1,178 @@
+use std::str::FromStr;
+
+use bevy::{app::AppExit, prelude::*};
+
+use crate::{
+    camera::{PixelPerfect, WindowScale, MAX_WINDOW_SCALE},
+    ghosts::ColorblindMode,
+    player::{PracticeMode, TurnIndicator},
+    services::{audio::AudioSettings, files::write_atomically},
+};
+
+const SETTINGS_FILE: &str = "settings";
+
+/// The options the player picked, kept in the `settings` file between runs.
+/// The file has one `key=value` per line:
+///
+/// - `volume`: from `0.0` to `1.0`
+/// - `muted`, `colorblind`, `pixel_perfect`, `turn_indicator`, `practice_mode`:
+///   `true` or `false`
+/// - `window_scale`: from `1` to `4`
+/// - `font`: a `.ttf` or `.otf` file in `assets` to draw all text with, empty
+///   for the built in Joystix
+///
+/// Lines are read one by one, so a missing, unknown or unreadable line only
+/// loses that setting, which keeps its default.
+///
+/// The resources the rest of the game reads, like `AudioSettings`, are set from
+/// this at startup, and any change to them is written back and saved.
+#[derive(Resource, Clone, Debug, PartialEq)]
+pub struct Settings {
+    pub volume: f64,
+    pub muted: bool,
+    pub colorblind: bool,
+    pub pixel_perfect: bool,
+    pub turn_indicator: bool,
+    pub practice_mode: bool,
+    pub window_scale: u8,
+    pub font: Option<String>,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        let audio = AudioSettings::default();
+        Self {
+            volume: audio.volume,
+            muted: audio.muted,
+            colorblind: false,
+            pixel_perfect: false,
+            turn_indicator: false,
+            practice_mode: false,
+            window_scale: WindowScale::default().0,
+            font: None,
+        }
+    }
+}
+
+impl Settings {
+    pub fn load() -> Self {
+        let mut settings = Self::default();
+
+        let Ok(text) = std::fs::read_to_string(SETTINGS_FILE) else {
+            return settings;
+        };
+
+        for line in text.lines().map(str::trim).filter(|line| !line.is_empty()) {
+            let Some((key, value)) = line.split_once('=') else {
+                warn!("Ignoring malformed settings line: {}", line);
+                continue;
+            };
+
+            let (key, value) = (key.trim(), value.trim());
+            match key {
+                "volume" => {
+                    parse_setting(key, value, &mut settings.volume);
+                    settings.volume = settings.volume.clamp(0.0, 1.0);
+                }
+                "muted" => parse_setting(key, value, &mut settings.muted),
+                "colorblind" => parse_setting(key, value, &mut settings.colorblind),
+                "pixel_perfect" => parse_setting(key, value, &mut settings.pixel_perfect),
+                "turn_indicator" => parse_setting(key, value, &mut settings.turn_indicator),
+                "practice_mode" => parse_setting(key, value, &mut settings.practice_mode),
+                "window_scale" => {
+                    parse_setting(key, value, &mut settings.window_scale);
+                    settings.window_scale = settings.window_scale.clamp(1, MAX_WINDOW_SCALE);
+                }
+                "font" => settings.font = Some(value.to_string()).filter(|font| !font.is_empty()),
+                other => warn!("Unknown setting {:?}", other),
+            }
+        }
+
+        settings
+    }
+
+    pub fn save(&self) {
+        let text = format!(
+            "volume={}\nmuted={}\ncolorblind={}\npixel_perfect={}\nturn_indicator={}\npractice_mode={}\nwindow_scale={}\nfont={}\n",
+            self.volume,
+            self.muted,
+            self.colorblind,
+            self.pixel_perfect,
+            self.turn_indicator,
+            self.practice_mode,
+            self.window_scale,
+            self.font.as_deref().unwrap_or("")
+        );
+
+        if let Err(error) = write_atomically(SETTINGS_FILE, text) {
+            warn!("Failed to write settings file: {}", error);
+        }
+    }
+}
+
+fn parse_setting<T: FromStr>(key: &str, value: &str, setting: &mut T) {
+    match value.parse() {
+        Ok(value) => *setting = value,
+        Err(_) => warn!("Invalid {} value {:?}", key, value),
+    }
+}
+
+pub struct SettingsPlugin;
+
+impl Plugin for SettingsPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Settings::load());
+        app.add_systems(Startup, apply_settings);
+        app.add_systems(Last, (store_settings, save_on_exit).chain());
+    }
+}
+
+fn apply_settings(
+    settings: Res<Settings>,
+    mut audio: ResMut<AudioSettings>,
+    mut colorblind_mode: ResMut<ColorblindMode>,
+    mut pixel_perfect: ResMut<PixelPerfect>,
+    mut turn_indicator: ResMut<TurnIndicator>,
+    mut practice_mode: ResMut<PracticeMode>,
+    mut window_scale: ResMut<WindowScale>,
+) {
+    audio.volume = settings.volume;
+    audio.muted = settings.muted;
+    colorblind_mode.0 = settings.colorblind;
+    pixel_perfect.0 = settings.pixel_perfect;
+    turn_indicator.0 = settings.turn_indicator;
+    practice_mode.0 = settings.practice_mode;
+    window_scale.0 = settings.window_scale;
+}
+
+fn store_settings(
+    mut settings: ResMut<Settings>,
+    audio: Res<AudioSettings>,
+    colorblind_mode: Res<ColorblindMode>,
+    pixel_perfect: Res<PixelPerfect>,
+    turn_indicator: Res<TurnIndicator>,
+    practice_mode: Res<PracticeMode>,
+    window_scale: Res<WindowScale>,
+) {
+    let current = Settings {
+        volume: audio.volume,
+        muted: audio.muted,
+        colorblind: colorblind_mode.0,
+        pixel_perfect: pixel_perfect.0,
+        turn_indicator: turn_indicator.0,
+        practice_mode: practice_mode.0,
+        window_scale: window_scale.0,
+        font: settings.font.clone(),
+    };
+
+    if *settings != current {
+        *settings = current;
+        settings.save();
+    }
+}
+
+fn save_on_exit(settings: Res<Settings>, mut exit_events: EventReader<AppExit>) {
+    if exit_events.read().last().is_some() {
+        settings.save();
+    }
+}
@@ -0,0 +1,278 @@
+use bevy::{
+    ecs::system::{EntityCommands, SystemParam},
+    prelude::*,
+};
+use strum::{Display, EnumCount, EnumIter};
+
+use crate::{
+    camera::{PixelPerfect, WindowScale, MAX_WINDOW_SCALE},
+    ghosts::ColorblindMode,
+    player::{PracticeMode, TurnIndicator},
+    services::{
+        audio::{AudioSettings, MAX_VOLUME_LEVEL},
+        text::TextProvider,
+    },
+};
+
+/// The settings that can be changed from the options of the pause screen, since
+/// none of them affect the game itself. All but the window scale, which doesn't
+/// fit, are on the main menu too.
+#[derive(Component, Debug, EnumCount, EnumIter, Display, Clone, Copy, PartialEq)]
+#[allow(non_camel_case_types)]
+pub enum SettingItem {
+    Colorblind,
+    Pixel_Perfect,
+    Window_Scale,
+    Turn_Indicator,
+    Practice_Mode,
+    Volume,
+}
+
+impl SettingItem {
+    pub fn name(&self) -> String {
+        self.to_string().replace('_', " ").to_uppercase()
+    }
+
+    /// Width of what's shown to the right of the item's name, including the
+    /// gap before it.
+    pub fn value_width(&self, text_provider: &TextProvider) -> f32 {
+        match self {
+            SettingItem::Volume => {
+                text_provider
+                    .get_size("=".repeat(MAX_VOLUME_LEVEL as usize))
+                    .x
+                    + 8.0
+            }
+            SettingItem::Window_Scale => {
+                text_provider
+                    .get_size(window_scale_text(MAX_WINDOW_SCALE))
+                    .x
+                    + 8.0
+            }
+            _ => toggle_width(text_provider),
+        }
+    }
+}
+
+/// The resources behind the setting items.
+#[derive(SystemParam)]
+pub struct SettingValues<'w> {
+    audio: ResMut<'w, AudioSettings>,
+    colorblind_mode: ResMut<'w, ColorblindMode>,
+    pixel_perfect: ResMut<'w, PixelPerfect>,
+    turn_indicator: ResMut<'w, TurnIndicator>,
+    practice_mode: ResMut<'w, PracticeMode>,
+    window_scale: ResMut<'w, WindowScale>,
+}
+
+impl SettingValues<'_> {
+    pub fn toggle_state(&self, item: SettingItem) -> Option<bool> {
+        match item {
+            SettingItem::Colorblind => Some(self.colorblind_mode.0),
+            SettingItem::Pixel_Perfect => Some(self.pixel_perfect.0),
+            SettingItem::Turn_Indicator => Some(self.turn_indicator.0),
+            SettingItem::Practice_Mode => Some(self.practice_mode.0),
+            SettingItem::Window_Scale | SettingItem::Volume => None,
+        }
+    }
+
+    /// Flips a toggle, mutes and unmutes the volume, or goes to the next window
+    /// scale, back to the smallest after the largest.
+    pub fn confirm(&mut self, item: SettingItem) {
+        match item {
+            SettingItem::Colorblind => self.colorblind_mode.0 = !self.colorblind_mode.0,
+            SettingItem::Pixel_Perfect => self.pixel_perfect.0 = !self.pixel_perfect.0,
+            SettingItem::Turn_Indicator => self.turn_indicator.0 = !self.turn_indicator.0,
+            SettingItem::Practice_Mode => self.practice_mode.0 = !self.practice_mode.0,
+            SettingItem::Window_Scale => {
+                self.window_scale.0 = self.window_scale.0 % MAX_WINDOW_SCALE + 1;
+            }
+            SettingItem::Volume => self.audio.muted = !self.audio.muted,
+        }
+    }
+
+    /// Left and right change the volume a level at a time, and the window scale
+    /// one step at a time.
+    pub fn adjust(&mut self, item: SettingItem, increase: bool) {
+        match item {
+            SettingItem::Volume => {
+                let level = self.audio.volume_level();
+                let level = if increase {
+                    level + 1
+                } else {
+                    level.saturating_sub(1)
+                };
+                self.audio.set_volume_level(level);
+            }
+            SettingItem::Window_Scale => {
+                let scale = if increase {
+                    self.window_scale.0 + 1
+                } else {
+                    self.window_scale.0.saturating_sub(1)
+                };
+                let scale = scale.clamp(1, MAX_WINDOW_SCALE);
+                // Resizing to the same size would still re-center the window.
+                if scale != self.window_scale.0 {
+                    self.window_scale.0 = scale;
+                }
+            }
+            _ => (),
+        }
+    }
+}
+
+#[derive(Component, Clone, Debug, Copy, Default, PartialEq)]
+pub enum Toggle {
+    On,
+    #[default]
+    Off,
+}
+
+/// Whether the ON or the OFF sign among the item's children is shown. Kept in
+/// sync with the setting for setting items, and set by the menu for its own.
+#[derive(Component, Default)]
+pub struct ToggleState(pub bool);
+
+#[derive(Component)]
+pub struct VolumeBar;
+
+#[derive(Component)]
+pub struct WindowScaleValue;
+
+fn window_scale_text(scale: u8) -> String {
+    format!("{}X", scale)
+}
+
+/// Width of an ON/OFF sign, including the gap before it.
+pub fn toggle_width(text_provider: &TextProvider) -> f32 {
+    text_provider.get_size("OFF").x + 8.0
+}
+
+/// Spawns the ON and OFF signs to the right of an item called `name`. The item
+/// needs a `ToggleState` to pick the one shown.
+pub fn spawn_toggle(
+    parent: &mut ChildBuilder,
+    name: &str,
+    text_provider: &mut TextProvider,
+    asset_server: &AssetServer,
+) {
+    let on_location = Vec2::new(8.0 * ((name.len() + 4) as f32 / 2.0), 0.0);
+    parent.spawn((
+        Toggle::On,
+        SpriteBundle {
+            texture: text_provider.get_image("ON", Color::GREEN, asset_server),
+            transform: Transform::from_translation(on_location.extend(0.0)),
+            ..default()
+        },
+    ));
+
+    let off_location = Vec2::new(8.0 * ((name.len() + 5) as f32 / 2.0) - 0.5, 0.0);
+    parent.spawn((
+        Toggle::Off,
+        SpriteBundle {
+            texture: text_provider.get_image("OFF", Color::RED, asset_server),
+            transform: Transform::from_translation(off_location.extend(0.0)),
+            ..default()
+        },
+    ));
+}
+
+/// Spawns the item's name and value as children of `item_entity`, giving it
+/// the components that keep the value up to date.
+pub fn spawn_setting_item(
+    item_entity: &mut EntityCommands,
+    item: SettingItem,
+    text_provider: &mut TextProvider,
+    asset_server: &AssetServer,
+) {
+    let name = item.name();
+    item_entity.insert(item);
+    if !matches!(item, SettingItem::Volume | SettingItem::Window_Scale) {
+        item_entity.insert(ToggleState::default());
+    }
+
+    item_entity.with_children(|parent| {
+        parent.spawn(SpriteBundle {
+            texture: text_provider.get_image(&name, Color::WHITE, asset_server),
+            ..default()
+        });
+
+        if item == SettingItem::Window_Scale {
+            let value_location = Vec2::new(8.0 * ((name.len() + 4) as f32 / 2.0), 0.0);
+            parent.spawn((
+                WindowScaleValue,
+                SpriteBundle {
+                    transform: Transform::from_translation(value_location.extend(0.0)),
+                    ..default()
+                },
+            ));
+        } else if item == SettingItem::Volume {
+            let bar_location = Vec2::new(
+                8.0 * ((name.len() + MAX_VOLUME_LEVEL as usize + 2) as f32 / 2.0),
+                0.0,
+            );
+            parent.spawn((
+                VolumeBar,
+                SpriteBundle {
+                    transform: Transform::from_translation(bar_location.extend(0.0)),
+                    ..default()
+                },
+            ));
+        } else {
+            spawn_toggle(parent, &name, text_provider, asset_server);
+        }
+    });
+}
+
+pub struct SettingsMenuPlugin;
+
+impl Plugin for SettingsMenuPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, draw_setting_values);
+    }
+}
+
+/// Shows the current value of every item on screen, wherever it was spawned.
+pub fn draw_setting_values(
+    values: SettingValues,
+    mut items: Query<(Option<&SettingItem>, &mut ToggleState, &Children)>,
+    mut toggles: Query<(&Toggle, &mut Visibility)>,
+    mut volume_bars: Query<&mut Handle<Image>, (With<VolumeBar>, Without<WindowScaleValue>)>,
+    mut window_scale_values: Query<&mut Handle<Image>, With<WindowScaleValue>>,
+    mut text_provider: ResMut<TextProvider>,
+    asset_server: Res<AssetServer>,
+) {
+    for (item, mut state, children) in items.iter_mut() {
+        if let Some(setting) = item.and_then(|item| values.toggle_state(*item)) {
+            if state.0 != setting {
+                state.0 = setting;
+            }
+        }
+
+        let mut children_toggles = toggles.iter_many_mut(children);
+        while let Some((toggle, mut visibility)) = children_toggles.fetch_next() {
+            *visibility = match (toggle, state.0) {
+                (Toggle::On, true) | (Toggle::Off, false) => Visibility::Visible,
+                _ => Visibility::Hidden,
+            };
+        }
+    }
+
+    for mut image in volume_bars.iter_mut() {
+        *image = if values.audio.muted {
+            text_provider.get_image("MUTED", Color::RED, &asset_server)
+        } else {
+            let level = values.audio.volume_level() as usize;
+            let bar = "=".repeat(level) + &"-".repeat(MAX_VOLUME_LEVEL as usize - level);
+            text_provider.get_image(bar, Color::GREEN, &asset_server)
+        };
+    }
+
+    for mut image in window_scale_values.iter_mut() {
+        *image = text_provider.get_image(
+            window_scale_text(values.window_scale.0),
+            Color::WHITE,
+            &asset_server,
+        );
+    }
+}
@@ -0,0 +1,46 @@
+//! Drives the game as a library, the way a bot outside of it would: through
+//! `BotInterface` only, with nothing but the crate's public API.
+
+use pacman::{headless_app, BotInterface, Direction};
+
+#[test]
+fn bot_steers_the_player_from_outside() {
+    let mut app = headless_app();
+    app.insert_resource(BotInterface::default());
+    app.finish();
+    app.cleanup();
+
+    while app.world.resource::<BotInterface>().snapshot().is_none() {
+        app.update();
+    }
+    let start = app
+        .world
+        .resource::<BotInterface>()
+        .snapshot()
+        .unwrap()
+        .clone();
+
+    // Through the READY pause, until the player gets going.
+    for _ in 0..600 {
+        app.world
+            .resource_mut::<BotInterface>()
+            .set_input(Some(Direction::Left));
+        app.update();
+
+        let snapshot = app.world.resource::<BotInterface>().snapshot().unwrap();
+        if snapshot.player.location != start.player.location {
+            break;
+        }
+    }
+
+    let snapshot = app.world.resource::<BotInterface>().snapshot().unwrap();
+    assert!(snapshot.tick > start.tick);
+    assert_eq!(snapshot.ghosts.len(), 4);
+    assert_eq!(snapshot.player.direction, Direction::Left);
+    assert!(
+        snapshot.player.location.0 < start.player.location.0,
+        "{:?} isn't left of {:?}",
+        snapshot.player.location,
+        start.player.location
+    );
+}
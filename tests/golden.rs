@@ -0,0 +1,28 @@
+//! Plays `golden.replay` headless and checks it still ends where it did when it
+//! was recorded. A change to movement, eating or the ghosts that alters the
+//! game shows up here; if it's intended, record the new values.
+
+use std::process::Command;
+
+const TICKS: &str = "600";
+const SCORE: &str = "190";
+const PLAYER_TILE: &str = "6,19";
+
+#[test]
+fn golden_replay_ends_in_the_recorded_place() {
+    let output = Command::new(env!("CARGO_BIN_EXE_pacman"))
+        .current_dir(env!("CARGO_MANIFEST_DIR"))
+        .args(["--headless", TICKS])
+        .args(["--replay", "tests/golden.replay"])
+        .args(["--expect-score", SCORE])
+        .args(["--expect-player", PLAYER_TILE])
+        .output()
+        .expect("Failed to run the game");
+
+    assert!(
+        output.status.success(),
+        "{}{}",
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    );
+}